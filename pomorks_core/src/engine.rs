@@ -0,0 +1,276 @@
+//! The pomodoro timer as a plain state machine.
+//!
+//! `Engine` does no I/O and never reads the clock: every call gets `now`
+//! and returns the events it caused. Front ends (TUI, daemon, GUI) drive it
+//! and persist, log or notify in response to the events.
+
+use anyhow::{anyhow, Result};
+use chrono::prelude::*;
+use pomorks_data_manage::config::TimerConfig;
+use pomorks_data_manage::data_manage_trait::TaskDealing;
+use pomorks_data_manage::todo::State;
+use serde::Serialize;
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum EngineEvent {
+    PhaseStarted {
+        state: State,
+    },
+    Paused {
+        state: State,
+        elapsed_seconds: i64,
+    },
+    Extended {
+        state: State,
+        limit_seconds: i64,
+    },
+    /// time is up but the phase keeps running until it is finished by hand.
+    Overtime {
+        state: State,
+    },
+    PhaseFinished {
+        finished: State,
+        next: State,
+        /// false when the phase was skipped without ever being started.
+        started: bool,
+        skipped: bool,
+        actual_seconds: i64,
+        planned_seconds: i64,
+    },
+    /// moved back by hand while no phase was running.
+    StateChanged {
+        state: State,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Engine {
+    state: State,
+    // start of the running phase, moved forward by the time spent paused.
+    start_time: Option<DateTime<Local>>,
+    paused_seconds: Option<i64>,
+    extended_seconds: usize,
+    overtime_notified: bool,
+    overtime: bool,
+    auto_start: bool,
+}
+
+impl Engine {
+    pub fn new(state: State, timer_config: &TimerConfig) -> Self {
+        Engine {
+            state,
+            start_time: None,
+            paused_seconds: None,
+            extended_seconds: 0,
+            overtime_notified: false,
+            overtime: timer_config.overtime,
+            auto_start: timer_config.auto_start_next,
+        }
+    }
+
+    /// Picks up a phase that was running when the previous process stopped.
+    pub fn resumed_from(self, task_dealing: &TaskDealing, now: DateTime<Local>) -> Self {
+        let mut engine = Engine {
+            start_time: task_dealing.date,
+            paused_seconds: task_dealing
+                .paused_seconds
+                .filter(|_| task_dealing.date.is_none()),
+            extended_seconds: task_dealing.extended_seconds,
+            ..self
+        };
+        // a session resumed while in overtime must not notify again.
+        engine.overtime_notified = engine.is_running() && engine.remaining_seconds(now) <= 0;
+        engine
+    }
+
+    /// What to persist for the todo `id` so that `resumed_from` can pick the phase up.
+    pub fn task_dealing(&self, id: &str) -> TaskDealing {
+        TaskDealing {
+            id: Some(id.to_string()),
+            date: self.start_time,
+            state: Some(self.state.clone()),
+            extended_seconds: self.extended_seconds,
+            paused_seconds: self.paused_seconds,
+        }
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    pub fn start_time(&self) -> Option<DateTime<Local>> {
+        self.start_time
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.start_time.is_some()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_seconds.is_some()
+    }
+
+    pub fn auto_start(&self) -> bool {
+        self.auto_start
+    }
+
+    pub fn extended_seconds(&self) -> usize {
+        self.extended_seconds
+    }
+
+    /// Time spent in the phase before it was paused.
+    pub fn paused_seconds(&self) -> Option<i64> {
+        self.paused_seconds
+    }
+
+    pub fn limit_seconds(&self) -> i64 {
+        (State::get_limit_time(&self.state) + self.extended_seconds) as i64
+    }
+
+    pub fn elapsed_seconds(&self, now: DateTime<Local>) -> i64 {
+        match (self.start_time, self.paused_seconds) {
+            (Some(start_time), _) => (now - start_time).num_seconds(),
+            (None, Some(paused_seconds)) => paused_seconds,
+            (None, None) => 0,
+        }
+    }
+
+    /// Negative while in overtime.
+    pub fn remaining_seconds(&self, now: DateTime<Local>) -> i64 {
+        self.limit_seconds() - self.elapsed_seconds(now)
+    }
+
+    /// Starts the current phase, or resumes it when paused.
+    pub fn start(&mut self, now: DateTime<Local>) -> Result<Vec<EngineEvent>> {
+        if self.is_running() {
+            return Err(anyhow!("a phase is already running."));
+        }
+
+        self.start_time = Some(now - chrono::Duration::seconds(self.paused_seconds.unwrap_or(0)));
+        self.paused_seconds = None;
+
+        Ok(vec![EngineEvent::PhaseStarted {
+            state: self.state.clone(),
+        }])
+    }
+
+    pub fn pause(&mut self, now: DateTime<Local>) -> Result<Vec<EngineEvent>> {
+        if !self.is_running() {
+            return Err(anyhow!("no phase is running."));
+        }
+
+        let elapsed_seconds = self.elapsed_seconds(now);
+        self.paused_seconds = Some(elapsed_seconds);
+        self.start_time = None;
+
+        Ok(vec![EngineEvent::Paused {
+            state: self.state.clone(),
+            elapsed_seconds,
+        }])
+    }
+
+    pub fn extend(&mut self, seconds: usize) -> Result<Vec<EngineEvent>> {
+        if !self.is_running() {
+            return Err(anyhow!("no phase is running."));
+        }
+
+        self.extended_seconds += seconds;
+        self.overtime_notified = false;
+
+        Ok(vec![EngineEvent::Extended {
+            state: self.state.clone(),
+            limit_seconds: self.limit_seconds(),
+        }])
+    }
+
+    pub fn set_auto_start(&mut self, auto_start: bool) {
+        self.auto_start = auto_start;
+    }
+
+    /// Switches to other timer settings; a running phase keeps its time.
+    pub fn set_timer_config(&mut self, timer_config: &TimerConfig) {
+        self.overtime = timer_config.overtime;
+        self.auto_start = timer_config.auto_start_next;
+    }
+
+    /// Ends the phase once its time is up, unless overtime is on.
+    pub fn tick(&mut self, now: DateTime<Local>) -> Vec<EngineEvent> {
+        if !self.is_running() || self.remaining_seconds(now) > 0 {
+            return vec![];
+        }
+
+        if self.overtime {
+            if self.overtime_notified {
+                return vec![];
+            }
+            self.overtime_notified = true;
+            return vec![EngineEvent::Overtime {
+                state: self.state.clone(),
+            }];
+        }
+
+        self.end_phase(now, false)
+    }
+
+    /// Ends the running phase as completed.
+    pub fn finish(&mut self, now: DateTime<Local>) -> Result<Vec<EngineEvent>> {
+        if !self.is_running() {
+            return Err(anyhow!("no phase is running."));
+        }
+
+        Ok(self.end_phase(now, false))
+    }
+
+    /// Ends the current phase as skipped, whether it was started or not.
+    pub fn skip(&mut self, now: DateTime<Local>) -> Vec<EngineEvent> {
+        self.end_phase(now, true)
+    }
+
+    pub fn move_prev(&mut self) -> Result<Vec<EngineEvent>> {
+        if self.is_running() {
+            return Err(anyhow!("a phase is running."));
+        }
+
+        self.state = State::get_prev_state(&self.state);
+        self.reset_phase();
+
+        Ok(vec![EngineEvent::StateChanged {
+            state: self.state.clone(),
+        }])
+    }
+
+    fn end_phase(&mut self, now: DateTime<Local>, skipped: bool) -> Vec<EngineEvent> {
+        let finished = self.state.clone();
+        let started = self.is_running() || self.is_paused();
+        let actual_seconds = self.elapsed_seconds(now);
+        let planned_seconds = self.limit_seconds();
+
+        self.state = State::get_next_state(&self.state);
+        self.reset_phase();
+
+        let mut events = vec![EngineEvent::PhaseFinished {
+            finished,
+            next: self.state.clone(),
+            started,
+            skipped,
+            actual_seconds,
+            planned_seconds,
+        }];
+        if self.auto_start {
+            self.start_time = Some(now);
+            events.push(EngineEvent::PhaseStarted {
+                state: self.state.clone(),
+            });
+        }
+
+        events
+    }
+
+    fn reset_phase(&mut self) {
+        self.start_time = None;
+        self.paused_seconds = None;
+        self.extended_seconds = 0;
+        self.overtime_notified = false;
+    }
+}
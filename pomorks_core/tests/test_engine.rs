@@ -0,0 +1,222 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{prelude::*, Duration};
+    use pomorks_core::engine::*;
+    use pomorks_data_manage::config::TimerConfig;
+    use pomorks_data_manage::data_manage_trait::TaskDealing;
+    use pomorks_data_manage::todo::*;
+
+    fn engine(overtime: bool, auto_start: bool) -> Engine {
+        Engine::new(
+            State::WORK(1),
+            &TimerConfig {
+                overtime,
+                extend_minutes: 5,
+                auto_start_next: auto_start,
+            },
+        )
+    }
+
+    fn limit(state: &State) -> Duration {
+        Duration::seconds(State::get_limit_time(state) as i64)
+    }
+
+    #[test]
+    fn test_runs_through_a_whole_cycle() {
+        let mut engine = engine(false, true);
+        let mut now = Local::now();
+        engine.start(now).unwrap();
+
+        let mut states = vec![];
+        for _ in 0..8 {
+            now = now + limit(engine.state());
+            match engine.tick(now).as_slice() {
+                [EngineEvent::PhaseFinished { next, .. }, EngineEvent::PhaseStarted { state }] => {
+                    assert_eq!(next, state);
+                    states.push(State::get_state_name(state));
+                }
+                events => panic!("unexpected events: {:?}", events),
+            }
+        }
+
+        assert_eq!(
+            states,
+            vec!["BREAK", "WORK_2", "BREAK", "WORK_3", "BREAK", "WORK_4", "LUNCH", "WORK_1"]
+        );
+    }
+
+    #[test]
+    fn test_tick_waits_for_the_limit() {
+        let mut engine = engine(false, false);
+        let now = Local::now();
+        assert!(engine.tick(now + Duration::hours(1)).is_empty());
+
+        engine.start(now).unwrap();
+        assert!(engine
+            .tick(now + limit(&State::WORK(1)) - Duration::seconds(1))
+            .is_empty());
+        match engine.tick(now + limit(&State::WORK(1))).as_slice() {
+            [EngineEvent::PhaseFinished {
+                finished,
+                next,
+                started,
+                skipped,
+                ..
+            }] => {
+                assert_eq!(finished, &State::WORK(1));
+                assert_eq!(next, &State::BREAK(1));
+                assert!(started);
+                assert!(!skipped);
+            }
+            events => panic!("unexpected events: {:?}", events),
+        }
+        assert!(!engine.is_running());
+    }
+
+    #[test]
+    fn test_pause_keeps_the_elapsed_time() {
+        let mut engine = engine(false, false);
+        let now = Local::now();
+        engine.start(now).unwrap();
+        assert!(engine.start(now).is_err());
+
+        engine.pause(now + Duration::seconds(10)).unwrap();
+        assert!(engine.is_paused());
+        assert!(engine.pause(now).is_err());
+        assert!(engine.tick(now + Duration::hours(1)).is_empty());
+
+        let resumed = now + Duration::hours(1);
+        engine.start(resumed).unwrap();
+        assert_eq!(engine.elapsed_seconds(resumed + Duration::seconds(5)), 15);
+        assert_eq!(
+            engine.remaining_seconds(resumed),
+            engine.limit_seconds() - 10
+        );
+    }
+
+    #[test]
+    fn test_overtime_is_reported_once() {
+        let mut engine = engine(true, false);
+        let now = Local::now();
+        engine.start(now).unwrap();
+
+        let over = now + limit(&State::WORK(1));
+        assert!(matches!(
+            engine.tick(over).as_slice(),
+            [EngineEvent::Overtime { .. }]
+        ));
+        assert!(engine.tick(over + Duration::seconds(1)).is_empty());
+
+        // extending gives a new limit to run over.
+        engine.extend(60).unwrap();
+        assert!(engine.tick(over + Duration::seconds(1)).is_empty());
+        assert!(matches!(
+            engine.tick(over + Duration::seconds(60)).as_slice(),
+            [EngineEvent::Overtime { .. }]
+        ));
+
+        match engine
+            .finish(over + Duration::seconds(90))
+            .unwrap()
+            .as_slice()
+        {
+            [EngineEvent::PhaseFinished {
+                actual_seconds,
+                planned_seconds,
+                ..
+            }] => {
+                assert_eq!(*actual_seconds, limit(&State::WORK(1)).num_seconds() + 90);
+                assert_eq!(*planned_seconds, limit(&State::WORK(1)).num_seconds() + 60);
+            }
+            events => panic!("unexpected events: {:?}", events),
+        }
+        assert_eq!(engine.extended_seconds(), 0);
+    }
+
+    #[test]
+    fn test_skip_and_move_by_hand() {
+        let mut engine = engine(false, false);
+        let now = Local::now();
+        assert!(engine.finish(now).is_err());
+
+        match engine.skip(now).as_slice() {
+            [EngineEvent::PhaseFinished {
+                started, skipped, ..
+            }] => {
+                assert!(!started);
+                assert!(skipped);
+            }
+            events => panic!("unexpected events: {:?}", events),
+        }
+        assert_eq!(engine.state(), &State::BREAK(1));
+
+        engine.move_prev().unwrap();
+        assert_eq!(engine.state(), &State::WORK(1));
+        engine.move_prev().unwrap();
+        assert_eq!(engine.state(), &State::LUNCH(4));
+
+        engine.start(now).unwrap();
+        assert!(engine.move_prev().is_err());
+    }
+
+    #[test]
+    fn test_resumed_in_overtime_does_not_report_again() {
+        let now = Local::now();
+        let engine_of = |start_time| {
+            let task_dealing = TaskDealing {
+                date: Some(start_time),
+                ..TaskDealing::default()
+            };
+            engine(true, false).resumed_from(&task_dealing, now)
+        };
+
+        let mut late = engine_of(now - limit(&State::WORK(1)) - Duration::seconds(5));
+        assert!(late.is_running());
+        assert!(late.tick(now).is_empty());
+
+        let mut on_time = engine_of(now - limit(&State::WORK(1)) + Duration::seconds(5));
+        assert!(on_time.tick(now).is_empty());
+        assert!(matches!(
+            on_time.tick(now + Duration::seconds(5)).as_slice(),
+            [EngineEvent::Overtime { .. }]
+        ));
+    }
+
+    #[test]
+    fn test_extension_survives_a_restart() {
+        let now = Local::now();
+        let mut engine = engine(false, false);
+        engine.start(now).unwrap();
+        engine.extend(300).unwrap();
+
+        let task_dealing = engine.task_dealing("id");
+        assert_eq!(task_dealing.extended_seconds, 300);
+
+        let resumed = self::engine(false, false).resumed_from(&task_dealing, now);
+        assert_eq!(resumed.limit_seconds(), engine.limit_seconds());
+        assert_eq!(resumed.start_time(), Some(now));
+    }
+
+    #[test]
+    fn test_pause_survives_a_restart() {
+        let now = Local::now();
+        let mut engine = engine(false, false);
+        engine.start(now).unwrap();
+        engine.pause(now + Duration::seconds(90)).unwrap();
+
+        let task_dealing = engine.task_dealing("id");
+        assert_eq!(task_dealing.date, None);
+        assert_eq!(task_dealing.paused_seconds, Some(90));
+
+        let later = now + Duration::hours(1);
+        let mut resumed = self::engine(false, false).resumed_from(&task_dealing, later);
+        assert!(resumed.is_paused());
+        assert_eq!(resumed.elapsed_seconds(later), 90);
+
+        resumed.start(later).unwrap();
+        assert_eq!(
+            resumed.remaining_seconds(later),
+            limit(&State::WORK(1)).num_seconds() - 90
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use crate::protocol::{DaemonStatus, Event, Request, Response};
+use crate::server::write_line;
+use anyhow::{anyhow, Result};
+use std::io::{BufRead, BufReader};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+
+pub struct Client {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+}
+
+impl Client {
+    pub fn connect(socket_path: &Path) -> Result<Client> {
+        let stream = UnixStream::connect(socket_path)?;
+
+        Ok(Client {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        })
+    }
+
+    pub fn request(&mut self, request: &Request) -> Result<DaemonStatus> {
+        write_line(&mut self.writer, request)?;
+
+        match self.read_response()? {
+            Response::Status(status) => Ok(status),
+            Response::Error { message } => Err(anyhow!(message)),
+            Response::Event(_) => Err(anyhow!("got an event before the reply.")),
+        }
+    }
+
+    /// Turns this connection into an event stream.
+    pub fn subscribe(mut self) -> Result<(DaemonStatus, Subscription)> {
+        let status = self.request(&Request::Subscribe)?;
+
+        Ok((status, Subscription { client: self }))
+    }
+
+    fn read_response(&mut self) -> Result<Response> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(anyhow!("daemon closed the connection."));
+        }
+
+        Ok(serde_json::from_str(&line)?)
+    }
+}
+
+pub struct Subscription {
+    client: Client,
+}
+
+impl Subscription {
+    pub fn next_event(&mut self) -> Result<Event> {
+        match self.client.read_response()? {
+            Response::Event(event) => Ok(event),
+            response => Err(anyhow!("expected an event: {:?}", response)),
+        }
+    }
+}
@@ -0,0 +1,331 @@
+use crate::protocol::{DaemonStatus, Event, Request, Response};
+use anyhow::{anyhow, Result};
+use chrono::prelude::*;
+use pomorks_core::engine::{Engine, EngineEvent};
+use pomorks_data_manage::config::{RecoveryConfig, TimerConfig};
+use pomorks_data_manage::data_manage_trait::{DataManage, TaskDealing, TaskLogJson, UNASSIGNED_ID};
+use pomorks_data_manage::hooks::{HookEvent, HookPayload, HookRunner};
+use pomorks_data_manage::notifications::Notifier;
+use pomorks_data_manage::recovery::{InFlightSession, RecoveryAction};
+use pomorks_data_manage::status::StatusSnapshot;
+use pomorks_data_manage::todo::{State, ONE_MINUTE};
+use pomorks_data_manage::webhook::{WebhookNotifier, WebhookPayload};
+use std::sync::mpsc::{self, Receiver, Sender};
+
+pub struct Daemon {
+    data_manager: Box<dyn DataManage + Send>,
+    notifier: Box<dyn Notifier + Send>,
+    hooks: HookRunner,
+    webhook: WebhookNotifier,
+    engine: Engine,
+    focus: Option<String>,
+    title: String,
+    subscribers: Vec<Sender<Event>>,
+}
+
+impl Daemon {
+    pub fn new(
+        data_manager: Box<dyn DataManage + Send>,
+        notifier: Box<dyn Notifier + Send>,
+        hooks: HookRunner,
+        webhook: WebhookNotifier,
+        timer_config: TimerConfig,
+        recovery_config: RecoveryConfig,
+    ) -> Result<Daemon> {
+        let task_dealing = recover_task_dealing(data_manager.as_ref(), &recovery_config)?;
+        let state = task_dealing.state.clone().unwrap_or(State::WORK(1));
+        let engine = Engine::new(state, &timer_config).resumed_from(&task_dealing, Local::now());
+        let mut daemon = Daemon {
+            data_manager,
+            notifier,
+            hooks,
+            webhook,
+            engine,
+            focus: None,
+            title: String::new(),
+            subscribers: vec![],
+        };
+        // the todo may have been archived or deleted since; the phase goes on unassigned.
+        if let Err(e) = daemon.set_focus(task_dealing.id.filter(|id| id != UNASSIGNED_ID)) {
+            eprintln!("{} the focus is dropped.", e);
+        }
+        // deliveries an earlier run could not send.
+        daemon.webhook.retry_in_background();
+
+        Ok(daemon)
+    }
+
+    pub fn handle(&mut self, request: Request) -> Response {
+        let now = Local::now();
+        let result = match request {
+            Request::Status | Request::Subscribe | Request::Shutdown => Ok(()),
+            Request::Start => self.engine.start(now).and_then(|events| {
+                self.move_focus_on_board(None)?;
+                self.on_engine_events(events, now)
+            }),
+            Request::Pause => self
+                .engine
+                .pause(now)
+                .and_then(|events| self.on_engine_events(events, now)),
+            Request::Skip => {
+                let events = self.engine.skip(now);
+                self.on_engine_events(events, now)
+            }
+            Request::Finish => self
+                .engine
+                .finish(now)
+                .and_then(|events| self.on_engine_events(events, now)),
+            Request::Extend { seconds } => self
+                .engine
+                .extend(seconds)
+                .and_then(|events| self.on_engine_events(events, now)),
+            Request::Prev => self
+                .engine
+                .move_prev()
+                .and_then(|events| self.on_engine_events(events, now)),
+            Request::Focus { id } => self.focus(id),
+        };
+
+        match result {
+            Ok(()) => Response::Status(self.status()),
+            Err(e) => Response::Error {
+                message: e.to_string(),
+            },
+        }
+    }
+
+    pub fn subscribe(&mut self) -> Receiver<Event> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.push(tx);
+        rx
+    }
+
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    pub fn status(&self) -> DaemonStatus {
+        DaemonStatus {
+            state: State::get_state_name(self.engine.state()),
+            running: self.engine.is_running(),
+            paused: self.engine.is_paused(),
+            remaining_seconds: self.engine.remaining_seconds(Local::now()),
+            focus: self.focus.clone(),
+            title: self.title.clone(),
+            phase: self.task_dealing(),
+        }
+    }
+
+    // called periodically; ends the phase once its time is up.
+    pub fn tick(&mut self) -> Result<()> {
+        let mut errors = self.hooks.take_errors();
+        errors.append(&mut self.webhook.take_errors());
+        errors.append(&mut self.notifier.take_errors());
+        for error in errors {
+            eprintln!("{}", error);
+        }
+
+        let now = Local::now();
+        self.webhook.retry_if_due(now);
+        let events = self.engine.tick(now);
+        for event in &events {
+            match event {
+                EngineEvent::PhaseFinished { finished, .. } => self.notify(finished),
+                EngineEvent::Overtime { state } => self.notify(state),
+                _ => {}
+            }
+        }
+        self.on_engine_events(events, now)
+    }
+
+    fn focus(&mut self, id: Option<String>) -> Result<()> {
+        let previous = self.focus.clone();
+        self.set_focus(id)?;
+        self.move_focus_on_board(previous.as_deref())?;
+        if self.engine.is_running() || self.engine.is_paused() {
+            self.write_task_dealing()?;
+        }
+
+        self.publish(Event::FocusChanged {
+            status: self.status(),
+        });
+        Ok(())
+    }
+
+    // the board follows the focus as it does in the TUI.
+    fn move_focus_on_board(&self, previous: Option<&str>) -> Result<()> {
+        if let Some(mut todo_list) = self.data_manager.read_all_todo()? {
+            if todo_list.move_focus(previous, self.focus.as_deref()) {
+                self.data_manager.write_all_todo(todo_list)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn set_focus(&mut self, id: Option<String>) -> Result<()> {
+        self.title = match &id {
+            Some(id) => {
+                self.data_manager
+                    .read_all_todo()?
+                    .and_then(|todo_list| todo_list.get_todo(id))
+                    .ok_or_else(|| anyhow!("todo {} is not exist.", id))?
+                    .title
+            }
+            None => String::new(),
+        };
+        self.focus = id;
+
+        Ok(())
+    }
+
+    fn on_engine_events(&mut self, events: Vec<EngineEvent>, now: DateTime<Local>) -> Result<()> {
+        for event in events {
+            match event {
+                EngineEvent::PhaseStarted { state } => {
+                    self.write_task_dealing()?;
+                    self.publish(Event::PhaseStarted {
+                        status: self.status(),
+                    });
+                    self.fire_phase_hook(&state, true);
+                }
+                EngineEvent::Paused { .. } => {
+                    self.write_task_dealing()?;
+                    self.publish(Event::Paused {
+                        status: self.status(),
+                    });
+                }
+                EngineEvent::Overtime { .. } => self.publish(Event::Overtime {
+                    status: self.status(),
+                }),
+                EngineEvent::PhaseFinished {
+                    finished,
+                    started,
+                    skipped,
+                    actual_seconds,
+                    planned_seconds,
+                    ..
+                } => {
+                    let id = match (&finished, &self.focus) {
+                        (State::WORK(_), Some(focus)) if started => focus.clone(),
+                        _ => UNASSIGNED_ID.to_string(),
+                    };
+                    let mut todo_list = self.data_manager.read_all_todo()?;
+                    if let (State::WORK(_), false, Some(todo_list)) =
+                        (&finished, skipped, &mut todo_list)
+                    {
+                        todo_list.increment_executed_count(&id);
+                        self.data_manager.write_all_todo(todo_list.clone())?;
+                    }
+                    let log = TaskLogJson::new(&id, &now, &finished, actual_seconds, skipped)
+                        .extended(planned_seconds - State::get_limit_time(&finished) as i64);
+                    self.data_manager.add_task_log(&log)?;
+                    self.data_manager.delete_task_dealing()?;
+                    self.webhook.session_completed(WebhookPayload::from_session(
+                        &log,
+                        &finished,
+                        todo_list
+                            .and_then(|todo_list| todo_list.get_todo(&id))
+                            .as_ref(),
+                    ));
+                    if started {
+                        self.fire_phase_hook(&finished, false);
+                    }
+
+                    self.publish(Event::PhaseFinished {
+                        finished,
+                        skipped,
+                        actual_seconds,
+                        status: self.status(),
+                    });
+                }
+                EngineEvent::Extended { .. } => self.write_task_dealing()?,
+                EngineEvent::StateChanged { .. } => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_task_dealing(&self) -> Result<()> {
+        self.data_manager.write_task_dealing(&self.task_dealing())
+    }
+
+    fn task_dealing(&self) -> TaskDealing {
+        let id = self.focus.as_deref().unwrap_or(UNASSIGNED_ID);
+        self.engine.task_dealing(id)
+    }
+
+    // a broken notifier must not keep the phase from ending.
+    fn notify(&self, state: &State) {
+        if let Err(e) = self.notifier.notify(state) {
+            eprintln!("{}", e);
+        }
+    }
+
+    fn fire_phase_hook(&self, state: &State, is_start: bool) {
+        let todos = match (state, &self.focus) {
+            (State::WORK(_), Some(focus)) => self
+                .data_manager
+                .read_all_todo()
+                .ok()
+                .flatten()
+                .and_then(|todo_list| todo_list.get_todo(focus))
+                .into_iter()
+                .collect(),
+            _ => vec![],
+        };
+        self.hooks.fire(HookPayload::new(
+            HookEvent::from_phase(state, is_start),
+            state,
+            todos,
+        ));
+    }
+
+    fn publish(&mut self, event: Event) {
+        self.subscribers.retain(|tx| tx.send(event.clone()).is_ok());
+
+        // keeps `pomorks_tui status` cheap while the daemon runs.
+        let _ = StatusSnapshot {
+            state: self.engine.state().clone(),
+            start_time: self.engine.start_time(),
+            extended_seconds: self.engine.extended_seconds(),
+            paused_seconds: self.engine.paused_seconds(),
+            id: self.focus.clone(),
+            title: self.title.clone(),
+            todays_count: self
+                .data_manager
+                .get_executed_count_by_day(&Local::now())
+                .unwrap_or(0),
+            written: Local::now(),
+        }
+        .write_cache();
+    }
+}
+
+// a phase left behind while no daemon ran is settled the way the TUI settles it by default,
+// so one left hours ago is not credited on the first tick.
+fn recover_task_dealing(
+    data_manager: &dyn DataManage,
+    recovery_config: &RecoveryConfig,
+) -> Result<TaskDealing> {
+    let mut task_dealing = data_manager.read_task_dealing()?;
+    let now = Local::now();
+    if let Some(session) = InFlightSession::from_task_dealing(&task_dealing, &now) {
+        let grace_seconds = (recovery_config.grace_minutes * ONE_MINUTE) as i64;
+        let action = session.default_action(grace_seconds);
+        let mut todo_list = data_manager.read_all_todo()?.unwrap_or_default();
+        session.recover(
+            action,
+            &mut task_dealing,
+            &mut todo_list,
+            data_manager,
+            &now,
+        )?;
+        if action == RecoveryAction::Credit {
+            data_manager.write_all_todo(todo_list)?;
+        }
+    }
+
+    Ok(task_dealing)
+}
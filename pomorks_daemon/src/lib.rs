@@ -0,0 +1,8 @@
+pub mod daemon;
+pub mod protocol;
+pub mod remote;
+
+#[cfg(unix)]
+pub mod client;
+#[cfg(unix)]
+pub mod server;
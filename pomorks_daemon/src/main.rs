@@ -0,0 +1,70 @@
+use anyhow::Result;
+
+#[cfg(unix)]
+fn main() -> Result<()> {
+    use anyhow::{anyhow, Context};
+    use pomorks_daemon::daemon::Daemon;
+    use pomorks_daemon::remote::default_socket_path;
+    use pomorks_daemon::server::Server;
+    use pomorks_data_manage::config::Config;
+    use pomorks_data_manage::data_manage_firebase::DataManageFirebase;
+    use pomorks_data_manage::data_manage_json::DataManageJson;
+    use pomorks_data_manage::data_manage_trait::{DataManage, TypeDataManager};
+    use pomorks_data_manage::hooks::HookRunner;
+    use pomorks_data_manage::notifications;
+    use pomorks_data_manage::webhook::WebhookNotifier;
+    use std::env;
+    use std::path::PathBuf;
+
+    let working_dir = env::current_dir()?;
+    let mut backend = TypeDataManager::DataManageJson;
+    let mut socket_path = default_socket_path();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value.", arg));
+        match arg.as_str() {
+            "--backend" => {
+                let name = value()?;
+                backend = TypeDataManager::from_name_ignore_case(&name)
+                    .ok_or_else(|| anyhow!("unknown backend: {}", name))?;
+            }
+            "--data-dir" => {
+                let data_dir = value()?;
+                env::set_current_dir(&data_dir)
+                    .with_context(|| format!("can't open data dir: {}", data_dir))?;
+            }
+            "--socket" => socket_path = PathBuf::from(value()?),
+            "-h" | "--help" => {
+                println!("Usage: pomorksd [--backend <json|firebase>] [--data-dir <dir>] [--socket <path>]");
+                return Ok(());
+            }
+            _ => return Err(anyhow!("unexpected argument: {}", arg)),
+        }
+    }
+    // relative socket paths stay where they were given, not in the data dir.
+    let socket_path = working_dir.join(socket_path);
+
+    let config = Config::load()?;
+    let data_manager: Box<dyn DataManage + Send> = match backend {
+        TypeDataManager::DataManageJson => Box::new(DataManageJson {}),
+        TypeDataManager::DataManageFirebase => Box::new(DataManageFirebase {}),
+    };
+    data_manager.migrate()?;
+    let daemon = Daemon::new(
+        data_manager,
+        notifications::get_notifier(&config.notifier),
+        HookRunner::new(config.hooks),
+        WebhookNotifier::new(config.webhook),
+        config.timer,
+        config.recovery,
+    )?;
+
+    println!("listening on {}", socket_path.display());
+    Server::bind(&socket_path, daemon)?.serve()
+}
+
+#[cfg(not(unix))]
+fn main() -> Result<()> {
+    Err(anyhow::anyhow!("pomorksd needs Unix domain sockets."))
+}
@@ -0,0 +1,107 @@
+//! JSON lines spoken over the `pomorksd` Unix socket.
+//!
+//! Every message is one JSON object terminated by `\n`. A client sends a request
+//! and reads exactly one reply for it, in order:
+//!
+//! ```text
+//! -> {"cmd":"focus","id":"abcdefghij"}
+//! <- {"type":"status","state":"WORK_1","running":false,"paused":false,"remaining_seconds":1500,"focus":"abcdefghij","title":"write","phase":{...}}
+//! -> {"cmd":"start"}
+//! <- {"type":"status","state":"WORK_1","running":true,...}
+//! -> {"cmd":"pause"}
+//! <- {"type":"error","message":"no phase is running."}
+//! ```
+//!
+//! Requests (`cmd`):
+//! - `status`: current phase.
+//! - `start`: start the current phase, or resume it when paused.
+//! - `pause`: pause the running phase.
+//! - `skip`: finish the current phase as skipped and move to the next one.
+//! - `finish`: finish the running phase as completed, e.g. while in overtime.
+//! - `extend`: add `seconds` to the running phase.
+//! - `prev`: move back to the previous phase while none is running.
+//! - `focus`: work on the todo `id`, or on nothing when `id` is null.
+//! - `subscribe`: reply with the status, then stream events on this connection.
+//!   A subscribed connection takes no more requests.
+//! - `shutdown`: reply with the status and stop the daemon.
+//!
+//! A status carries the phase as `TaskDealing` stores it in `phase`, which is what
+//! a client needs to draw the timer itself between two replies.
+//!
+//! Events (`"type":"event"`, named by `event`):
+//! - `phase_started`, `paused`, `phase_finished` (with the `finished` state, `skipped`
+//!   and `actual_seconds`),
+//!   `overtime`, `focus_changed`. Each carries the status after the change.
+
+use chrono::prelude::*;
+use pomorks_core::engine::Engine;
+use pomorks_data_manage::config::TimerConfig;
+use pomorks_data_manage::data_manage_trait::TaskDealing;
+use pomorks_data_manage::todo::State;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Request {
+    Status,
+    Start,
+    Pause,
+    Skip,
+    Finish,
+    Extend { seconds: usize },
+    Prev,
+    Focus { id: Option<String> },
+    Subscribe,
+    Shutdown,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DaemonStatus {
+    pub state: String,
+    pub running: bool,
+    pub paused: bool,
+    pub remaining_seconds: i64,
+    pub focus: Option<String>,
+    pub title: String,
+    #[serde(default)]
+    pub phase: TaskDealing,
+}
+
+impl DaemonStatus {
+    /// A copy of the daemon's timer to draw from; only the daemon ends its phases.
+    pub fn engine(&self, timer_config: &TimerConfig, now: DateTime<Local>) -> Engine {
+        let state = self.phase.state.clone().unwrap_or(State::WORK(1));
+        Engine::new(state, timer_config).resumed_from(&self.phase, now)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    PhaseStarted {
+        status: DaemonStatus,
+    },
+    Paused {
+        status: DaemonStatus,
+    },
+    PhaseFinished {
+        finished: State,
+        skipped: bool,
+        actual_seconds: i64,
+        status: DaemonStatus,
+    },
+    Overtime {
+        status: DaemonStatus,
+    },
+    FocusChanged {
+        status: DaemonStatus,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    Status(DaemonStatus),
+    Error { message: String },
+    Event(Event),
+}
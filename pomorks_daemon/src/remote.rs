@@ -0,0 +1,80 @@
+//! How the TUI, the CLI and the GUI reach a running `pomorksd`.
+//!
+//! When a daemon listens it owns the timer and the front ends only send it requests;
+//! without one they run the timer themselves, as they did before the daemon existed.
+
+#[cfg(unix)]
+use crate::client::Client;
+use crate::protocol::{DaemonStatus, Event, Request};
+use anyhow::Result;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+#[cfg(unix)]
+use std::{sync::mpsc, thread};
+
+#[cfg(unix)]
+pub struct Remote {
+    client: Client,
+    socket_path: PathBuf,
+}
+
+// there is no daemon to reach without Unix domain sockets.
+#[cfg(not(unix))]
+pub enum Remote {}
+
+#[cfg(unix)]
+impl Remote {
+    /// None when no daemon is listening on `socket_path`.
+    pub fn connect(socket_path: &Path) -> Option<Remote> {
+        Some(Remote {
+            client: Client::connect(socket_path).ok()?,
+            socket_path: socket_path.to_path_buf(),
+        })
+    }
+
+    pub fn request(&mut self, request: &Request) -> Result<DaemonStatus> {
+        self.client.request(request)
+    }
+
+    /// The daemon's events, read on a second connection until the daemon goes away.
+    pub fn events(&self) -> Result<Receiver<Event>> {
+        let (_, mut subscription) = Client::connect(&self.socket_path)?.subscribe()?;
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            while let Ok(event) = subscription.next_event() {
+                if tx.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+}
+
+#[cfg(not(unix))]
+impl Remote {
+    pub fn connect(_socket_path: &Path) -> Option<Remote> {
+        None
+    }
+
+    pub fn request(&mut self, _request: &Request) -> Result<DaemonStatus> {
+        match *self {}
+    }
+
+    pub fn events(&self) -> Result<Receiver<Event>> {
+        match *self {}
+    }
+}
+
+/// `$XDG_RUNTIME_DIR/pomorks.sock`, or a per-user socket in the temp dir.
+pub fn default_socket_path() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(runtime_dir) => PathBuf::from(runtime_dir).join("pomorks.sock"),
+        None => env::temp_dir().join(format!(
+            "pomorks-{}.sock",
+            env::var("USER").unwrap_or_default()
+        )),
+    }
+}
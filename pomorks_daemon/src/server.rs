@@ -0,0 +1,146 @@
+use crate::daemon::Daemon;
+use crate::protocol::{Request, Response};
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+const TICK_RATE: Duration = Duration::from_millis(200);
+
+pub struct Server {
+    listener: UnixListener,
+    socket_path: PathBuf,
+    daemon: Arc<Mutex<Daemon>>,
+    should_stop: Arc<AtomicBool>,
+}
+
+impl Server {
+    pub fn bind(socket_path: &Path, daemon: Daemon) -> Result<Server> {
+        // a socket left behind by a daemon that is no longer there.
+        if socket_path.exists() && UnixStream::connect(socket_path).is_err() {
+            std::fs::remove_file(socket_path)?;
+        }
+
+        Ok(Server {
+            listener: UnixListener::bind(socket_path)?,
+            socket_path: socket_path.to_path_buf(),
+            daemon: Arc::new(Mutex::new(daemon)),
+            should_stop: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    pub fn serve(self) -> Result<()> {
+        let ticker = {
+            let daemon = self.daemon.clone();
+            let should_stop = self.should_stop.clone();
+            thread::spawn(move || {
+                while !should_stop.load(Ordering::Relaxed) {
+                    if let Err(e) = lock(&daemon).and_then(|mut daemon| daemon.tick()) {
+                        eprintln!("{}", e);
+                    }
+                    thread::sleep(TICK_RATE);
+                }
+            })
+        };
+
+        for stream in self.listener.incoming() {
+            if self.should_stop.load(Ordering::Relaxed) {
+                break;
+            }
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    continue;
+                }
+            };
+
+            let daemon = self.daemon.clone();
+            let should_stop = self.should_stop.clone();
+            let socket_path = self.socket_path.clone();
+            thread::spawn(move || {
+                if let Err(e) = handle_connection(stream, &daemon, &should_stop, &socket_path) {
+                    eprintln!("{}", e);
+                }
+            });
+        }
+
+        ticker
+            .join()
+            .map_err(|_| anyhow!("timer thread panicked."))?;
+        std::fs::remove_file(&self.socket_path)?;
+
+        Ok(())
+    }
+}
+
+fn handle_connection(
+    stream: UnixStream,
+    daemon: &Mutex<Daemon>,
+    should_stop: &AtomicBool,
+    socket_path: &Path,
+) -> Result<()> {
+    let reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+
+    for line in reader.lines() {
+        let request: Request = match serde_json::from_str(&line?) {
+            Ok(request) => request,
+            Err(e) => {
+                write_line(
+                    &mut writer,
+                    &Response::Error {
+                        message: e.to_string(),
+                    },
+                )?;
+                continue;
+            }
+        };
+
+        match request {
+            Request::Subscribe => {
+                let (status, events) = {
+                    let mut daemon = lock(daemon)?;
+                    (daemon.status(), daemon.subscribe())
+                };
+                write_line(&mut writer, &Response::Status(status))?;
+                for event in events {
+                    write_line(&mut writer, &Response::Event(event))?;
+                }
+                return Ok(());
+            }
+            Request::Shutdown => {
+                let response = lock(daemon)?.handle(Request::Shutdown);
+                write_line(&mut writer, &response)?;
+                should_stop.store(true, Ordering::Relaxed);
+                // wakes the accept loop up so it sees the stop flag.
+                let _ = UnixStream::connect(socket_path);
+                return Ok(());
+            }
+            request => {
+                let response = lock(daemon)?.handle(request);
+                write_line(&mut writer, &response)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn lock(daemon: &Mutex<Daemon>) -> Result<MutexGuard<'_, Daemon>> {
+    daemon
+        .lock()
+        .map_err(|_| anyhow!("daemon state is poisoned."))
+}
+
+pub fn write_line(writer: &mut impl Write, message: &impl Serialize) -> Result<()> {
+    writeln!(writer, "{}", serde_json::to_string(message)?)?;
+    writer.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,318 @@
+#![cfg(unix)]
+
+#[cfg(test)]
+mod tests {
+    use chrono::prelude::*;
+    use pomorks_daemon::client::Client;
+    use pomorks_daemon::protocol::*;
+    use pomorks_daemon::remote::Remote;
+    use pomorks_data_manage::config::TimerConfig;
+    use pomorks_data_manage::data_manage_trait::{SessionKind, TaskDealing, TaskLogJson};
+    use pomorks_data_manage::todo::*;
+    use std::path::{Path, PathBuf};
+    use std::process::{Child, Command, Stdio};
+    use std::str::FromStr;
+    use std::{env, fs, thread, time::Duration};
+
+    // kills the daemon and removes its data dir even when an assertion fails.
+    struct DaemonProcess {
+        child: Child,
+        data_dir: PathBuf,
+    }
+
+    impl Drop for DaemonProcess {
+        fn drop(&mut self) {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+            let _ = fs::remove_dir_all(&self.data_dir);
+        }
+    }
+
+    // a daemon on a fresh data dir holding one todo, plus whatever `seed` writes there first.
+    fn spawn_daemon(
+        name: &str,
+        seed: impl FnOnce(&Path, &TodoItem),
+    ) -> (DaemonProcess, PathBuf, TodoItem) {
+        let data_dir = env::temp_dir().join(format!("pomorksd-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&data_dir);
+        fs::create_dir_all(&data_dir).unwrap();
+
+        let todo = TodoItem::from_str("write doc pomorks 2").unwrap();
+        let mut todo_list = TodoList::new();
+        todo_list.add_todo(todo.clone()).unwrap();
+        fs::write(
+            data_dir.join("task.json"),
+            serde_json::to_string(&todo_list).unwrap(),
+        )
+        .unwrap();
+        fs::write(
+            data_dir.join("config.toml"),
+            "[notifier]\nkind = \"none\"\n",
+        )
+        .unwrap();
+        seed(&data_dir, &todo);
+
+        let socket_path = data_dir.join("pomorksd.sock");
+        let daemon = DaemonProcess {
+            child: Command::new(env!("CARGO_BIN_EXE_pomorksd"))
+                .arg("--data-dir")
+                .arg(&data_dir)
+                .arg("--socket")
+                .arg(&socket_path)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .unwrap(),
+            data_dir,
+        };
+
+        (daemon, socket_path, todo)
+    }
+
+    fn connect(socket_path: &Path) -> Client {
+        for _ in 0..100 {
+            if let Ok(client) = Client::connect(socket_path) {
+                return client;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        panic!("daemon did not come up.");
+    }
+
+    #[test]
+    fn test_wire_format() {
+        assert_eq!(
+            serde_json::to_string(&Request::Focus {
+                id: Some("abcdefghij".to_string())
+            })
+            .unwrap(),
+            r#"{"cmd":"focus","id":"abcdefghij"}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Request>(r#"{"cmd":"skip"}"#).unwrap(),
+            Request::Skip
+        );
+
+        let status = DaemonStatus {
+            state: "BREAK".to_string(),
+            running: false,
+            paused: false,
+            remaining_seconds: 300,
+            focus: None,
+            title: String::new(),
+            phase: TaskDealing::default(),
+        };
+        let event = Response::Event(Event::Paused {
+            status: status.clone(),
+        });
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(json.starts_with(r#"{"type":"event","event":"paused","status":{"state":"BREAK""#));
+        assert_eq!(serde_json::from_str::<Response>(&json).unwrap(), event);
+        assert_eq!(
+            serde_json::to_string(&Response::Error {
+                message: "no phase is running.".to_string()
+            })
+            .unwrap(),
+            r#"{"type":"error","message":"no phase is running."}"#
+        );
+    }
+
+    #[test]
+    fn test_full_cycle_through_socket() {
+        let (mut daemon, socket_path, todo) = spawn_daemon("cycle", |_, _| {});
+        let data_dir = daemon.data_dir.clone();
+
+        let (status, mut events) = connect(&socket_path).subscribe().unwrap();
+        assert_eq!(status.state, "WORK_1");
+        assert!(!status.running);
+
+        let mut client = connect(&socket_path);
+        let status = client
+            .request(&Request::Focus {
+                id: Some(todo.id.clone()),
+            })
+            .unwrap();
+        assert_eq!(status.title, "write");
+        let todo_list: TodoList =
+            serde_json::from_str(&fs::read_to_string(data_dir.join("task.json")).unwrap()).unwrap();
+        assert_eq!(
+            todo_list.get_todo(&todo.id).unwrap().column(),
+            TodoStatus::Doing
+        );
+        assert!(client
+            .request(&Request::Focus {
+                id: Some("not_exist".to_string()),
+            })
+            .is_err());
+
+        assert!(client.request(&Request::Start).unwrap().running);
+        assert!(client.request(&Request::Start).is_err());
+        let status = client.request(&Request::Extend { seconds: 60 }).unwrap();
+        assert_eq!(status.phase.extended_seconds, 60);
+
+        let status = client.request(&Request::Pause).unwrap();
+        assert!(status.paused);
+        assert!(!status.running);
+        assert!(client.request(&Request::Pause).is_err());
+
+        assert!(client.request(&Request::Start).unwrap().running);
+        assert_eq!(client.request(&Request::Skip).unwrap().state, "BREAK");
+        client.request(&Request::Start).unwrap();
+        assert_eq!(client.request(&Request::Skip).unwrap().state, "WORK_2");
+        assert!(client.request(&Request::Finish).is_err());
+        assert_eq!(client.request(&Request::Prev).unwrap().state, "BREAK");
+
+        assert!(matches!(
+            events.next_event().unwrap(),
+            Event::FocusChanged { .. }
+        ));
+        assert!(matches!(
+            events.next_event().unwrap(),
+            Event::PhaseStarted { .. }
+        ));
+        assert!(matches!(events.next_event().unwrap(), Event::Paused { .. }));
+        assert!(matches!(
+            events.next_event().unwrap(),
+            Event::PhaseStarted { .. }
+        ));
+        match events.next_event().unwrap() {
+            Event::PhaseFinished {
+                finished,
+                skipped,
+                status,
+                ..
+            } => {
+                assert_eq!(finished, State::WORK(1));
+                assert!(skipped);
+                assert_eq!(status.state, "BREAK");
+            }
+            event => panic!("unexpected event: {:?}", event),
+        }
+        assert!(matches!(
+            events.next_event().unwrap(),
+            Event::PhaseStarted { .. }
+        ));
+        assert!(matches!(
+            events.next_event().unwrap(),
+            Event::PhaseFinished { .. }
+        ));
+
+        let task_logs: Vec<TaskLogJson> =
+            serde_json::from_str(&fs::read_to_string(data_dir.join("task_log.json")).unwrap())
+                .unwrap();
+        assert_eq!(task_logs.len(), 2);
+        assert_eq!(task_logs[0].id, todo.id);
+        assert!(task_logs[0].skipped);
+        assert_eq!(task_logs[1].kind, SessionKind::Break);
+
+        client.request(&Request::Shutdown).unwrap();
+        assert!(daemon.child.wait().unwrap().success());
+        assert!(!socket_path.exists());
+    }
+
+    #[test]
+    fn test_archived_focus_is_dropped() {
+        let (_daemon, socket_path, _) = spawn_daemon("archived", |data_dir, _| {
+            let task_dealing = TaskDealing {
+                id: Some("archived00".to_string()),
+                date: Some(Local::now()),
+                state: Some(State::WORK(1)),
+                ..TaskDealing::default()
+            };
+            fs::write(
+                data_dir.join("dealing_task.json"),
+                serde_json::to_string(&task_dealing).unwrap(),
+            )
+            .unwrap();
+        });
+
+        let status = connect(&socket_path).request(&Request::Status).unwrap();
+        assert!(status.running);
+        assert_eq!(status.focus, None);
+    }
+
+    #[test]
+    fn test_session_left_long_ago_is_not_credited() {
+        let (_daemon, socket_path, todo) = spawn_daemon("recovery", |data_dir, todo| {
+            let task_dealing = TaskDealing {
+                id: Some(todo.id.clone()),
+                date: Some(Local::now() - chrono::Duration::hours(3)),
+                state: Some(State::WORK(1)),
+                ..TaskDealing::default()
+            };
+            fs::write(
+                data_dir.join("dealing_task.json"),
+                serde_json::to_string(&task_dealing).unwrap(),
+            )
+            .unwrap();
+        });
+
+        let status = connect(&socket_path).request(&Request::Status).unwrap();
+        assert!(!status.running);
+        assert_eq!(status.state, "WORK_1");
+        assert_eq!(status.focus, Some(todo.id));
+
+        // a tick later nothing was credited either.
+        thread::sleep(Duration::from_millis(500));
+        let data_dir = socket_path.parent().unwrap();
+        assert!(!data_dir.join("task_log.json").exists());
+        let recovery_log = fs::read_to_string(data_dir.join("recovery_log.json")).unwrap();
+        assert!(recovery_log.contains(r#""action":"Discard""#));
+    }
+
+    #[test]
+    fn test_phase_ends_on_its_own() {
+        // a work phase close to its end, as a previous run of the daemon left it.
+        let limit = State::get_limit_time(&State::WORK(1)) as i64;
+        let started = Local::now() - chrono::Duration::seconds(limit - 3);
+        let (_daemon, socket_path, todo) = spawn_daemon("ticker", |data_dir, todo| {
+            let task_dealing = TaskDealing {
+                id: Some(todo.id.clone()),
+                date: Some(started),
+                state: Some(State::WORK(1)),
+                ..TaskDealing::default()
+            };
+            fs::write(
+                data_dir.join("dealing_task.json"),
+                serde_json::to_string(&task_dealing).unwrap(),
+            )
+            .unwrap();
+        });
+        // waits for the daemon to listen.
+        connect(&socket_path);
+
+        let mut remote = Remote::connect(&socket_path).unwrap();
+        let events = remote.events().unwrap();
+        let status = remote.request(&Request::Status).unwrap();
+        assert!(status.running);
+        let engine = status.engine(&TimerConfig::default(), Local::now());
+        assert_eq!(engine.start_time(), Some(started));
+        assert!(engine.remaining_seconds(Local::now()) <= 3);
+
+        match events.recv_timeout(Duration::from_secs(5)).unwrap() {
+            Event::PhaseFinished {
+                finished,
+                skipped,
+                status,
+                ..
+            } => {
+                assert_eq!(finished, State::WORK(1));
+                assert!(!skipped);
+                assert_eq!(status.state, "BREAK");
+                assert!(!status.running);
+            }
+            event => panic!("unexpected event: {:?}", event),
+        }
+
+        let data_dir = socket_path.parent().unwrap();
+        let task_logs: Vec<TaskLogJson> =
+            serde_json::from_str(&fs::read_to_string(data_dir.join("task_log.json")).unwrap())
+                .unwrap();
+        assert_eq!(task_logs.len(), 1);
+        assert_eq!(task_logs[0].id, todo.id);
+        assert!(task_logs[0].is_completed_work());
+        let todo_list: TodoList =
+            serde_json::from_str(&fs::read_to_string(data_dir.join("task.json")).unwrap()).unwrap();
+        assert_eq!(todo_list.get_todo(&todo.id).unwrap().executed_count, 1);
+    }
+}
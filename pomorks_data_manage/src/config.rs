@@ -0,0 +1,230 @@
+use crate::todo::TodoStatus;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+
+pub const CONFIG_FILE: &str = "config.toml";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Config {
+    pub recovery: RecoveryConfig,
+    pub timer: TimerConfig,
+    pub notifier: NotifierConfig,
+    pub autosave: AutosaveConfig,
+    pub hooks: HooksConfig,
+    pub webhook: WebhookConfig,
+    pub keys: KeysConfig,
+    pub board: BoardConfig,
+    /// timer settings `:profile <name>` switches to, e.g. `[profiles.deep]`.
+    pub profiles: BTreeMap<String, TimerConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct RecoveryConfig {
+    /// minutes after the limit in which an interrupted session is still credited by default.
+    pub grace_minutes: usize,
+}
+
+impl Default for RecoveryConfig {
+    fn default() -> Self {
+        RecoveryConfig { grace_minutes: 5 }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct TimerConfig {
+    /// keep counting past the limit until the phase is finished by hand.
+    pub overtime: bool,
+    pub extend_minutes: usize,
+    pub auto_start_next: bool,
+}
+
+impl Default for TimerConfig {
+    fn default() -> Self {
+        TimerConfig {
+            overtime: false,
+            extend_minutes: 5,
+            auto_start_next: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct AutosaveConfig {
+    /// seconds without a change before pending todos are written.
+    pub debounce_seconds: u64,
+}
+
+impl Default for AutosaveConfig {
+    fn default() -> Self {
+        AutosaveConfig {
+            debounce_seconds: 2,
+        }
+    }
+}
+
+/// executables run on timer and todo events, see `hooks`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// seconds before a hook that is still running is killed.
+    pub timeout_seconds: u64,
+    pub work_start: Vec<String>,
+    pub work_end: Vec<String>,
+    pub break_start: Vec<String>,
+    pub break_end: Vec<String>,
+    pub todo_finished: Vec<String>,
+    pub todo_archived: Vec<String>,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        HooksConfig {
+            timeout_seconds: 10,
+            work_start: vec![],
+            work_end: vec![],
+            break_start: vec![],
+            break_end: vec![],
+            todo_finished: vec![],
+            todo_archived: vec![],
+        }
+    }
+}
+
+/// HTTP POSTs sent when a session is logged, see `webhook`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct WebhookConfig {
+    pub urls: Vec<String>,
+    /// signs the body with HMAC-SHA256 when not empty.
+    pub secret: String,
+    /// write the requests to webhook_dry_run.jsonl instead of sending them.
+    pub dry_run: bool,
+    pub timeout_seconds: u64,
+    pub max_attempts: u32,
+    /// delay before the first retry, doubled on every further one.
+    pub retry_seconds: i64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        WebhookConfig {
+            urls: vec![],
+            secret: String::new(),
+            dry_run: false,
+            timeout_seconds: 10,
+            max_attempts: 5,
+            retry_seconds: 30,
+        }
+    }
+}
+
+/// TUI key bindings, e.g. `quit = ["q", "ctrl+c"]` under `[keys.bindings]`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct KeysConfig {
+    /// "vim" or "arrows".
+    pub preset: String,
+    /// action name to keys, replacing the preset's keys for that action.
+    pub bindings: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for KeysConfig {
+    fn default() -> Self {
+        KeysConfig {
+            preset: "vim".to_string(),
+            bindings: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifierKind {
+    Dbus,
+    Terminal,
+    Command,
+    Toast,
+    None,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct NotifierConfig {
+    pub kind: NotifierKind,
+    /// terminal notifier sends OSC 9 instead of a plain bell.
+    pub osc9: bool,
+    pub command: String,
+    pub args: Vec<String>,
+}
+
+impl Default for NotifierConfig {
+    fn default() -> Self {
+        NotifierConfig {
+            kind: if cfg!(target_os = "windows") {
+                NotifierKind::Toast
+            } else {
+                NotifierKind::Dbus
+            },
+            osc9: false,
+            command: "notify-send".to_string(),
+            args: vec!["Pomodoro-rs".to_string(), "{message}".to_string()],
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> Result<Config> {
+        match fs::read_to_string(CONFIG_FILE) {
+            Ok(config_toml) => {
+                let config: Config = toml::from_str(&config_toml)?;
+                config.board.validate()?;
+                Ok(config)
+            }
+            Err(_) => Ok(Config::default()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct BoardConfig {
+    /// most cards a column should hold, by column name, e.g. `doing = 1`.
+    pub wip_limits: BTreeMap<String, usize>,
+}
+
+impl BoardConfig {
+    pub fn wip_limit(&self, status: TodoStatus) -> Option<usize> {
+        self.wip_limits.get(status.name()).copied()
+    }
+
+    /// A limit on a column that doesn't exist, e.g. a misspelt one, would never apply.
+    pub fn validate(&self) -> Result<()> {
+        let names: Vec<&str> = TodoStatus::ALL.iter().map(|status| status.name()).collect();
+        match self
+            .wip_limits
+            .keys()
+            .find(|name| !names.contains(&name.as_str()))
+        {
+            Some(name) => Err(anyhow!(
+                "unknown column in board.wip_limits: {} (one of {})",
+                name,
+                names.join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for BoardConfig {
+    fn default() -> Self {
+        BoardConfig {
+            wip_limits: [("doing".to_string(), 1)].into_iter().collect(),
+        }
+    }
+}
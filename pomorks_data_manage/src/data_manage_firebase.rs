@@ -1,6 +1,7 @@
 use crate::data_manage_trait::{DataManage, TaskDealing, TaskLogJson, DATE_FORMAT};
+use crate::recovery::RecoveryLogJson;
 use crate::todo::*;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use chrono::prelude::*;
 use firerust::FirebaseClient;
 use serde::{Deserialize, Serialize};
@@ -54,10 +55,7 @@ impl DataManage for DataManageFirebase {
             .get()
             .expect("can't get todo_list from firebase");
 
-        let todo_list: TodoList = match serde_json::from_value(todo_list_json) {
-            Ok(todo_list) => todo_list,
-            Err(_) => TodoList::new(),
-        };
+        let todo_list: TodoList = serde_json::from_value(todo_list_json).unwrap_or_default();
 
         Ok(Some(todo_list))
     }
@@ -67,7 +65,7 @@ impl DataManage for DataManageFirebase {
 
         for archived in archived_todo_list.iter() {
             let client = FirebaseInfo::get_client()?;
-            let serialized = serde_json::to_value(&archived)?;
+            let serialized = serde_json::to_value(archived)?;
             client
                 .reference("/archive")
                 .set_unique(&serialized)
@@ -77,22 +75,11 @@ impl DataManage for DataManageFirebase {
         Ok(())
     }
 
-    fn write_task_dealing(
-        &self,
-        id: &str,
-        start_time: &DateTime<Local>,
-        state: &State,
-    ) -> Result<()> {
+    fn write_task_dealing(&self, task_dealing: &TaskDealing) -> Result<()> {
         println!("write_deal");
         let client = FirebaseInfo::get_client()?;
 
-        let task_dealing = TaskDealing {
-            id: Some(id.to_string()),
-            date: Some(*start_time),
-            state: Some(state.clone()),
-        };
-
-        let serialized = serde_json::to_value(&task_dealing)?;
+        let serialized = serde_json::to_value(task_dealing)?;
 
         client
             .reference("/task_dealing")
@@ -111,29 +98,14 @@ impl DataManage for DataManageFirebase {
             .get()
             .expect("can't get task_dealing from firebase");
 
-        let task_dealing = match serde_json::from_value(task_dealing_json) {
-            Ok(task_dealing) => task_dealing,
-            Err(_) => TaskDealing {
-                id: None,
-                date: None,
-                state: None,
-            },
-        };
-
-        Ok(task_dealing)
+        Ok(serde_json::from_value(task_dealing_json).unwrap_or_default())
     }
 
     fn delete_task_dealing(&self) -> Result<()> {
         println!("delete_log");
         let client = FirebaseInfo::get_client()?;
 
-        let task_dealing = TaskDealing {
-            id: None,
-            date: None,
-            state: None,
-        };
-
-        let serialized = serde_json::to_value(&task_dealing)?;
+        let serialized = serde_json::to_value(TaskDealing::default())?;
 
         client
             .reference("/task_dealing")
@@ -143,15 +115,9 @@ impl DataManage for DataManageFirebase {
         Ok(())
     }
 
-    fn add_task_log(&self, id: &str, date: &DateTime<Local>) -> Result<()> {
+    fn add_task_log(&self, task_log: &TaskLogJson) -> Result<()> {
         println!("add_log");
         let client = FirebaseInfo::get_client()?;
-        let date = date.format(DATE_FORMAT).to_string();
-
-        let task_log = TaskLogJson {
-            id: id.to_string(),
-            date,
-        };
 
         let serialized = serde_json::to_value(task_log)?;
 
@@ -188,7 +154,7 @@ impl DataManage for DataManageFirebase {
                     .datetime_from_str("1800/02/02 00:00:00+09:00", DATE_FORMAT)
                     .unwrap(),
             };
-            if date_each.day() == date.day() {
+            if date_each.day() == date.day() && log.is_completed_work() {
                 acc + 1
             } else {
                 acc
@@ -202,18 +168,91 @@ impl DataManage for DataManageFirebase {
         println!("get_log_all");
         let client = FirebaseInfo::get_client()?;
 
-        let task_log_json: Value = client
-            .reference("/task_log")
-            .get()
-            .expect("can't get task_log from firebase");
+        Ok(get_task_logs_with_key(&client)
+            .into_iter()
+            .map(|(_, task_log)| task_log)
+            .collect())
+    }
 
-        match serde_json::from_value::<HashMap<String, TaskLogJson>>(task_log_json) {
-            Ok(vec_task_log_with_id) => Ok(vec_task_log_with_id
-                .iter()
-                .map(|task_log_with_id| task_log_with_id.1.clone())
-                .collect()),
-            // TODO:名前おかしい
-            Err(_) => Ok(vec![]),
-        }
+    fn reassign_task_log(&self, log_id: &str, id: &str) -> Result<()> {
+        println!("reassign_log");
+        let client = FirebaseInfo::get_client()?;
+
+        let (key, task_log) = get_task_logs_with_key(&client)
+            .into_iter()
+            .find(|(_, task_log)| task_log.log_id == log_id)
+            .ok_or_else(|| anyhow!("selected task log is not exist."))?;
+
+        let serialized = serde_json::to_value(TaskLogJson {
+            id: id.to_string(),
+            ..task_log
+        })?;
+
+        client
+            .reference(format!("/task_log/{}", key))
+            .set(serialized)
+            .expect("can't update task_log where firebase");
+
+        Ok(())
+    }
+
+    fn delete_task_log(&self, log_id: &str) -> Result<()> {
+        println!("delete_log");
+        let client = FirebaseInfo::get_client()?;
+
+        let (key, _) = get_task_logs_with_key(&client)
+            .into_iter()
+            .find(|(_, task_log)| task_log.log_id == log_id)
+            .ok_or_else(|| anyhow!("selected task log is not exist."))?;
+
+        client
+            .reference(format!("/task_log/{}", key))
+            .set(Value::Null)
+            .expect("can't delete task_log where firebase");
+
+        Ok(())
+    }
+
+    fn add_recovery_log(&self, recovery_log: &RecoveryLogJson) -> Result<()> {
+        println!("add_recovery_log");
+        let client = FirebaseInfo::get_client()?;
+
+        let serialized = serde_json::to_value(recovery_log)?;
+
+        client
+            .reference("/recovery_log")
+            .set_unique(serialized)
+            .expect("can't update recovery_log where firebase");
+
+        Ok(())
+    }
+
+    // logs without a log_id are identified by their key, so nothing is rewritten.
+    fn migrate(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+// logs pushed before log_id existed are identified by their firebase key.
+fn get_task_logs_with_key(client: &FirebaseClient) -> Vec<(String, TaskLogJson)> {
+    let task_log_json: Value = client
+        .reference("/task_log")
+        .get()
+        .expect("can't get task_log from firebase");
+
+    match serde_json::from_value::<HashMap<String, TaskLogJson>>(task_log_json) {
+        Ok(vec_task_log_with_id) => vec_task_log_with_id
+            .into_iter()
+            .map(|(key, task_log)| {
+                let log_id = if task_log.log_id.is_empty() {
+                    key.clone()
+                } else {
+                    task_log.log_id.clone()
+                };
+                (key, TaskLogJson { log_id, ..task_log })
+            })
+            .collect(),
+        // TODO:名前おかしい
+        Err(_) => vec![],
     }
 }
@@ -1,10 +1,11 @@
 use crate::data_manage_trait::{DataManage, TaskDealing, TaskLogJson, DATE_FORMAT};
+use crate::recovery::RecoveryLogJson;
 use crate::todo::*;
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use chrono::prelude::*;
 use std::fs;
 use std::fs::File;
-use std::io::Write;
+use std::io::{ErrorKind, Write};
 
 pub struct DataManageJson {}
 
@@ -24,10 +25,7 @@ impl DataManage for DataManageJson {
             Ok(file) => file,
             Err(_) => File::create("task.json").context("can't create file.")?,
         };
-        let todo_list: TodoList = match serde_json::from_reader(todo_list_json) {
-            Ok(todo_list) => todo_list,
-            Err(_) => TodoList::new(),
-        };
+        let todo_list: TodoList = serde_json::from_reader(todo_list_json).unwrap_or_default();
 
         Ok(Some(todo_list))
     }
@@ -37,10 +35,8 @@ impl DataManage for DataManageJson {
             Ok(file) => file,
             Err(_) => File::create("archive.json")?,
         };
-        let current_archive = match serde_json::from_reader(current_archive_json) {
-            Ok(archive) => archive,
-            Err(_) => Vec::<TodoItem>::new(),
-        };
+        let current_archive: Vec<TodoItem> =
+            serde_json::from_reader(current_archive_json).unwrap_or_default();
 
         archived_todo_list.extend(current_archive);
         let serialized = serde_json::to_string(&archived_todo_list)?;
@@ -52,19 +48,8 @@ impl DataManage for DataManageJson {
         Ok(())
     }
 
-    fn write_task_dealing(
-        &self,
-        id: &str,
-        start_time: &DateTime<Local>,
-        state: &State,
-    ) -> Result<()> {
-        let task_dealing = TaskDealing {
-            id: Some(id.to_string()),
-            date: Some(*start_time),
-            state: Some(state.clone()),
-        };
-
-        let serialized = serde_json::to_string(&task_dealing)?;
+    fn write_task_dealing(&self, task_dealing: &TaskDealing) -> Result<()> {
+        let serialized = serde_json::to_string(task_dealing)?;
 
         let mut file = File::create("dealing_task.json")?;
         write!(file, "{}", serialized)?;
@@ -78,55 +63,31 @@ impl DataManage for DataManageJson {
             Ok(file) => file,
             Err(_) => File::create("dealing_task.json").context("can't create file.")?,
         };
-        let task_dealing = match serde_json::from_reader(task_dealing_json) {
-            Ok(task_dealing) => task_dealing,
-            Err(_) => TaskDealing {
-                id: None,
-                date: None,
-                state: None,
-            },
-        };
-
-        Ok(TaskDealing {
-            date: task_dealing.date,
-            id: task_dealing.id,
-            state: task_dealing.state,
-        })
+        Ok(serde_json::from_reader(task_dealing_json).unwrap_or_default())
     }
 
     fn delete_task_dealing(&self) -> Result<()> {
-        fs::remove_file("dealing_task.json")?;
-        Ok(())
+        match fs::remove_file("dealing_task.json") {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
     }
 
-    fn add_task_log(&self, id: &str, date: &DateTime<Local>) -> Result<()> {
+    fn add_task_log(&self, task_log: &TaskLogJson) -> Result<()> {
         let task_log_json = match File::open("task_log.json") {
             Ok(file) => file,
             Err(_) => File::create("task_log.json").context("can't create file.")?,
         };
-        let mut task_log: Vec<TaskLogJson> = match serde_json::from_reader(task_log_json) {
-            Ok(task_log) => task_log,
-            Err(_) => Vec::new(),
-        };
-
-        let date = date.format(DATE_FORMAT).to_string();
-
-        task_log.push(TaskLogJson {
-            id: id.to_string(),
-            date,
-        });
+        let mut task_logs: Vec<TaskLogJson> =
+            serde_json::from_reader(task_log_json).unwrap_or_default();
 
-        let serialized = serde_json::to_string(&task_log)?;
-        let mut file = File::create("task_log.json")?;
-        write!(file, "{}", serialized)?;
-        file.flush()?;
+        task_logs.push(task_log.clone());
 
-        Ok(())
+        write_task_logs(&task_logs)
     }
 
     fn get_executed_count_by_day(&self, date: &DateTime<Local>) -> Result<i64> {
-        let task_log_json = File::open("task_log.json")?;
-        let task_log: Vec<TaskLogJson> = serde_json::from_reader(task_log_json)?;
+        let task_log = read_task_logs()?;
 
         let count = task_log.iter().fold(0, |acc, log| {
             let date_each = match Local.datetime_from_str(&log.date, DATE_FORMAT) {
@@ -135,7 +96,7 @@ impl DataManage for DataManageJson {
                     .datetime_from_str("1800/02/02 00:00:00+09:00", DATE_FORMAT)
                     .unwrap(),
             };
-            if date_each.day() == date.day() {
+            if date_each.day() == date.day() && log.is_completed_work() {
                 acc + 1
             } else {
                 acc
@@ -146,7 +107,79 @@ impl DataManage for DataManageJson {
     }
 
     fn get_log_all(&self) -> Result<Vec<TaskLogJson>> {
-        let task_log_json = File::open("task_log.json")?;
-        Ok(serde_json::from_reader(task_log_json)?)
+        read_task_logs()
+    }
+
+    fn reassign_task_log(&self, log_id: &str, id: &str) -> Result<()> {
+        let mut task_logs = self.get_log_all()?;
+
+        match task_logs.iter_mut().find(|log| log.log_id == log_id) {
+            Some(log) => log.id = id.to_string(),
+            None => return Err(anyhow!("selected task log is not exist.")),
+        }
+
+        write_task_logs(&task_logs)
+    }
+
+    fn delete_task_log(&self, log_id: &str) -> Result<()> {
+        let mut task_logs = self.get_log_all()?;
+
+        let len_before = task_logs.len();
+        task_logs.retain(|log| log.log_id != log_id);
+        if task_logs.len() == len_before {
+            return Err(anyhow!("selected task log is not exist."));
+        }
+
+        write_task_logs(&task_logs)
+    }
+
+    fn add_recovery_log(&self, recovery_log: &RecoveryLogJson) -> Result<()> {
+        let recovery_log_json = match File::open("recovery_log.json") {
+            Ok(file) => file,
+            Err(_) => File::create("recovery_log.json").context("can't create file.")?,
+        };
+        let mut recovery_logs: Vec<RecoveryLogJson> =
+            serde_json::from_reader(recovery_log_json).unwrap_or_default();
+
+        recovery_logs.push(recovery_log.clone());
+
+        let serialized = serde_json::to_string(&recovery_logs)?;
+        let mut file = File::create("recovery_log.json")?;
+        write!(file, "{}", serialized)?;
+        file.flush()?;
+
+        Ok(())
+    }
+
+    // logs written before log_id existed get one, saved so it stays the same on every read.
+    fn migrate(&self) -> Result<()> {
+        let mut task_logs = read_task_logs()?;
+        if task_logs.iter().all(|log| !log.log_id.is_empty()) {
+            return Ok(());
+        }
+        task_logs
+            .iter_mut()
+            .filter(|log| log.log_id.is_empty())
+            .for_each(|log| log.log_id = generate_id());
+
+        write_task_logs(&task_logs)
+    }
+}
+
+// no log file yet means no log.
+fn read_task_logs() -> Result<Vec<TaskLogJson>> {
+    match File::open("task_log.json") {
+        Ok(file) => Ok(serde_json::from_reader(file)?),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
     }
 }
+
+fn write_task_logs(task_logs: &[TaskLogJson]) -> Result<()> {
+    let serialized = serde_json::to_string(task_logs)?;
+    let mut file = File::create("task_log.json")?;
+    write!(file, "{}", serialized)?;
+    file.flush()?;
+
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use crate::recovery::RecoveryLogJson;
 use crate::todo::*;
 use anyhow::Result;
 use chrono::prelude::*;
@@ -5,16 +6,97 @@ use enum_iterator::{all, Sequence};
 use serde::{Deserialize, Serialize};
 
 pub const DATE_FORMAT: &str = "%Y/%m/%d %H:%M:%S%Z";
+pub const UNASSIGNED_ID: &str = "";
+
+// logs written before the kind was recorded are all work sessions.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SessionKind {
+    #[default]
+    Work,
+    Break,
+    Lunch,
+}
+
+impl SessionKind {
+    pub fn from_state(state: &State) -> Self {
+        match state {
+            State::WORK(_) => SessionKind::Work,
+            State::BREAK(_) => SessionKind::Break,
+            State::LUNCH(_) => SessionKind::Lunch,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TaskLogJson {
+    #[serde(default)]
+    pub log_id: String,
     pub id: String,
     pub date: String,
+    #[serde(default)]
+    pub kind: SessionKind,
+    #[serde(default)]
+    pub planned_seconds: i64,
+    #[serde(default)]
+    pub actual_seconds: i64,
+    #[serde(default)]
+    pub skipped: bool,
+    #[serde(default)]
+    pub overtime_seconds: i64,
+}
+
+impl TaskLogJson {
+    pub fn new(
+        id: &str,
+        date: &DateTime<Local>,
+        state: &State,
+        actual_seconds: i64,
+        skipped: bool,
+    ) -> Self {
+        TaskLogJson {
+            log_id: generate_id(),
+            id: id.to_string(),
+            date: date.format(DATE_FORMAT).to_string(),
+            kind: SessionKind::from_state(state),
+            planned_seconds: State::get_limit_time(state) as i64,
+            actual_seconds,
+            skipped,
+            overtime_seconds: (actual_seconds - State::get_limit_time(state) as i64).max(0),
+        }
+    }
+
+    pub fn extended(self, extended_seconds: i64) -> Self {
+        let planned_seconds = self.planned_seconds + extended_seconds;
+        TaskLogJson {
+            planned_seconds,
+            overtime_seconds: (self.actual_seconds - planned_seconds).max(0),
+            ..self
+        }
+    }
+
+    pub fn get_date(&self) -> Option<DateTime<Local>> {
+        Local.datetime_from_str(&self.date, DATE_FORMAT).ok()
+    }
+
+    pub fn is_completed_work(&self) -> bool {
+        self.kind == SessionKind::Work && !self.skipped
+    }
+
+    pub fn is_unassigned(&self) -> bool {
+        self.is_completed_work() && self.id == UNASSIGNED_ID
+    }
 }
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
 pub struct TaskDealing {
     pub id: Option<String>,
     pub date: Option<DateTime<Local>>,
     pub state: Option<State>,
+    /// added to the limit of the phase by extending it.
+    #[serde(default)]
+    pub extended_seconds: usize,
+    /// elapsed time of a paused phase, which has no `date` while paused.
+    #[serde(default)]
+    pub paused_seconds: Option<i64>,
 }
 
 #[derive(Debug, Eq, PartialEq, Sequence, Clone, Copy)]
@@ -38,6 +120,11 @@ impl TypeDataManager {
             .find(|type_manager| type_manager.name() == name)
     }
 
+    // for command line flags, e.g. `--backend json`.
+    pub fn from_name_ignore_case(name: &str) -> Option<Self> {
+        all::<TypeDataManager>().find(|type_manager| type_manager.name().eq_ignore_ascii_case(name))
+    }
+
     pub fn get_all_type_name_and_index() -> Vec<(usize, String)> {
         let vec_type = all::<TypeDataManager>();
         vec_type
@@ -52,15 +139,26 @@ pub trait DataManage {
     fn write_all_todo(&self, todo_list: TodoList) -> Result<()>;
     fn read_all_todo(&self) -> Result<Option<TodoList>>;
     fn archive_todo(&self, archived_todo_list: Vec<TodoItem>) -> Result<()>;
-    fn write_task_dealing(
-        &self,
-        id: &str,
-        start_time: &DateTime<Local>,
-        state: &State,
-    ) -> Result<()>;
+    fn write_task_dealing(&self, task_dealing: &TaskDealing) -> Result<()>;
     fn read_task_dealing(&self) -> Result<TaskDealing>;
     fn delete_task_dealing(&self) -> Result<()>;
-    fn add_task_log(&self, id: &str, date: &DateTime<Local>) -> Result<()>;
+    fn add_task_log(&self, task_log: &TaskLogJson) -> Result<()>;
     fn get_executed_count_by_day(&self, date: &DateTime<Local>) -> Result<i64>;
     fn get_log_all(&self) -> Result<Vec<TaskLogJson>>;
+    fn reassign_task_log(&self, log_id: &str, id: &str) -> Result<()>;
+    fn delete_task_log(&self, log_id: &str) -> Result<()>;
+    fn add_recovery_log(&self, recovery_log: &RecoveryLogJson) -> Result<()>;
+    /// Brings data written by older versions up to date. Run once at startup, before reading.
+    fn migrate(&self) -> Result<()>;
+
+    /// Deletes `todo` from `todo_list`; its pomodoros still happened, so their logs go back
+    /// to the unassigned tab.
+    fn delete_todo(&self, todo_list: &mut TodoList, todo: &TodoItem) -> Result<()> {
+        todo_list.delete_todo(todo)?;
+        for log in self.get_log_all()?.iter().filter(|log| log.id == todo.id) {
+            self.reassign_task_log(&log.log_id, UNASSIGNED_ID)?;
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,197 @@
+use crate::config::HooksConfig;
+use crate::data_manage_trait::DATE_FORMAT;
+use crate::todo::*;
+use anyhow::{anyhow, Result};
+use chrono::prelude::*;
+use serde::Serialize;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    WorkStart,
+    WorkEnd,
+    BreakStart,
+    BreakEnd,
+    TodoFinished,
+    TodoArchived,
+}
+
+impl HookEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookEvent::WorkStart => "work_start",
+            HookEvent::WorkEnd => "work_end",
+            HookEvent::BreakStart => "break_start",
+            HookEvent::BreakEnd => "break_end",
+            HookEvent::TodoFinished => "todo_finished",
+            HookEvent::TodoArchived => "todo_archived",
+        }
+    }
+
+    // lunch is a break as far as hooks are concerned.
+    pub fn from_phase(state: &State, is_start: bool) -> Self {
+        match (state, is_start) {
+            (State::WORK(_), true) => HookEvent::WorkStart,
+            (State::WORK(_), false) => HookEvent::WorkEnd,
+            (_, true) => HookEvent::BreakStart,
+            (_, false) => HookEvent::BreakEnd,
+        }
+    }
+}
+
+/// Sent to a hook as JSON on stdin.
+#[derive(Serialize, Debug, Clone)]
+pub struct HookPayload {
+    pub event: HookEvent,
+    pub state: String,
+    pub todos: Vec<TodoItem>,
+    pub date: String,
+}
+
+impl HookPayload {
+    pub fn new(event: HookEvent, state: &State, todos: Vec<TodoItem>) -> Self {
+        HookPayload {
+            event,
+            state: State::get_state_name(state),
+            todos,
+            date: Local::now().format(DATE_FORMAT).to_string(),
+        }
+    }
+
+    pub fn get_envs(&self) -> Vec<(&'static str, String)> {
+        let todo = self.todos.first();
+        vec![
+            ("POMORKS_EVENT", self.event.name().to_string()),
+            ("POMORKS_STATE", self.state.clone()),
+            ("POMORKS_DATE", self.date.clone()),
+            ("POMORKS_TODO_COUNT", self.todos.len().to_string()),
+            (
+                "POMORKS_TODO_ID",
+                todo.map(|todo| todo.id.clone()).unwrap_or_default(),
+            ),
+            (
+                "POMORKS_TODO_TITLE",
+                todo.map(|todo| todo.title.clone()).unwrap_or_default(),
+            ),
+            (
+                "POMORKS_TODO_TAG",
+                todo.map(|todo| todo.tag.clone()).unwrap_or_default(),
+            ),
+            (
+                "POMORKS_TODO_PROJECT",
+                todo.map(|todo| todo.project.clone()).unwrap_or_default(),
+            ),
+        ]
+    }
+}
+
+/// Runs the configured hooks of an event in the background.
+#[derive(Default)]
+pub struct HookRunner {
+    config: HooksConfig,
+    errors: Arc<Mutex<Vec<String>>>,
+    // hooks that may still run, for `wait`.
+    running: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl HookRunner {
+    pub fn new(config: HooksConfig) -> Self {
+        HookRunner {
+            config,
+            errors: Arc::new(Mutex::new(vec![])),
+            running: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn fire(&self, payload: HookPayload) {
+        let timeout = Duration::from_secs(self.config.timeout_seconds);
+        for command in self.get_commands(payload.event) {
+            let command = command.clone();
+            let payload = payload.clone();
+            let errors = self.errors.clone();
+            let hook = thread::spawn(move || {
+                if let Err(e) = run_hook(&command, &payload, timeout) {
+                    if let Ok(mut errors) = errors.lock() {
+                        errors.push(format!("hook {} failed: {}", command, e));
+                    }
+                }
+            });
+            if let Ok(mut running) = self.running.lock() {
+                running.retain(|hook| !hook.is_finished());
+                running.push(hook);
+            }
+        }
+    }
+
+    /// Waits for the hooks fired so far, e.g. before a command run from the shell exits.
+    pub fn wait(&self) {
+        let running: Vec<JoinHandle<()>> = match self.running.lock() {
+            Ok(mut running) => running.drain(..).collect(),
+            Err(_) => vec![],
+        };
+        for hook in running {
+            let _ = hook.join();
+        }
+    }
+
+    /// Failures of hooks that finished since the last call.
+    pub fn take_errors(&self) -> Vec<String> {
+        match self.errors.lock() {
+            Ok(mut errors) => errors.drain(..).collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    fn get_commands(&self, event: HookEvent) -> &[String] {
+        match event {
+            HookEvent::WorkStart => &self.config.work_start,
+            HookEvent::WorkEnd => &self.config.work_end,
+            HookEvent::BreakStart => &self.config.break_start,
+            HookEvent::BreakEnd => &self.config.break_end,
+            HookEvent::TodoFinished => &self.config.todo_finished,
+            HookEvent::TodoArchived => &self.config.todo_archived,
+        }
+    }
+}
+
+/// Runs one hook and waits for it, killing it after `timeout`.
+pub fn run_hook(command: &str, payload: &HookPayload, timeout: Duration) -> Result<()> {
+    let mut child = Command::new(command)
+        .envs(payload.get_envs())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // written aside, so a hook that never reads a payload larger than the pipe still times out.
+    // killing it closes the pipe, which ends the write.
+    if let Some(mut stdin) = child.stdin.take() {
+        let json = serde_json::to_string(payload)?;
+        thread::spawn(move || {
+            // a hook that doesn't read stdin may have closed it already.
+            let _ = stdin.write_all(json.as_bytes());
+        });
+    }
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return if status.success() {
+                Ok(())
+            } else {
+                Err(anyhow!("{}", status))
+            };
+        }
+        if Instant::now() >= deadline {
+            child.kill()?;
+            child.wait()?;
+            return Err(anyhow!("timed out after {:?}", timeout));
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+}
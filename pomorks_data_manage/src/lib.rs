@@ -1,5 +1,13 @@
 #![feature(hash_drain_filter)]
+pub mod config;
 pub mod data_manage_firebase;
 pub mod data_manage_json;
 pub mod data_manage_trait;
+pub mod hooks;
+pub mod notifications;
+pub mod recovery;
+pub mod statistics;
+pub mod status;
 pub mod todo;
+pub mod view;
+pub mod webhook;
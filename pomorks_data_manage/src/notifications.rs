@@ -0,0 +1,313 @@
+use crate::config::{NotifierConfig, NotifierKind};
+use crate::todo::State;
+use anyhow::{anyhow, Result};
+use std::io::{stdout, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const APP_NAME: &str = "Pomodoro-rs";
+
+pub trait Notifier {
+    fn notify(&self, state: &State) -> Result<()>;
+
+    /// Failures of notifications sent in the background since the last call.
+    fn take_errors(&self) -> Vec<String> {
+        vec![]
+    }
+}
+
+pub fn get_notifier(config: &NotifierConfig) -> Box<dyn Notifier + Send> {
+    match config.kind {
+        NotifierKind::Dbus => Box::new(DbusNotifier::new(GdbusSession {})),
+        NotifierKind::Terminal => Box::new(TerminalNotifier { osc9: config.osc9 }),
+        NotifierKind::Command => Box::new(CommandNotifier {
+            command: config.command.clone(),
+            args: config.args.clone(),
+        }),
+        #[cfg(target_os = "windows")]
+        NotifierKind::Toast => Box::new(ToastNotifier {}),
+        #[cfg(not(target_os = "windows"))]
+        NotifierKind::Toast => Box::new(TerminalNotifier { osc9: config.osc9 }),
+        NotifierKind::None => Box::new(NoneNotifier {}),
+    }
+}
+
+fn get_message(state: &State) -> String {
+    State::get_state_name(state) + " is finish."
+}
+
+pub struct NoneNotifier {}
+
+impl Notifier for NoneNotifier {
+    fn notify(&self, _state: &State) -> Result<()> {
+        Ok(())
+    }
+}
+
+// org.freedesktop.Notifications.Notify arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    pub app_name: String,
+    pub replaces_id: u32,
+    pub summary: String,
+    pub body: String,
+    pub expire_timeout: i32,
+}
+
+pub trait NotificationBus {
+    fn call_notify(&self, notification: &Notification) -> Result<u32>;
+}
+
+/// Calls the bus from a thread of its own, so a slow bus holds up neither the timer nor the TUI.
+pub struct DbusNotifier {
+    sender: Sender<Notification>,
+    errors: Arc<Mutex<Vec<String>>>,
+}
+
+impl DbusNotifier {
+    pub fn new<B: NotificationBus + Send + 'static>(bus: B) -> Self {
+        let (sender, receiver) = mpsc::channel::<Notification>();
+        let errors = Arc::new(Mutex::new(vec![]));
+        let thread_errors = errors.clone();
+        // ends once the notifier is dropped.
+        thread::spawn(move || {
+            for notification in receiver {
+                if let Err(e) = bus.call_notify(&notification) {
+                    if let Ok(mut errors) = thread_errors.lock() {
+                        errors.push(e.to_string());
+                    }
+                }
+            }
+        });
+
+        DbusNotifier { sender, errors }
+    }
+}
+
+impl Notifier for DbusNotifier {
+    fn notify(&self, state: &State) -> Result<()> {
+        self.sender
+            .send(Notification {
+                app_name: APP_NAME.to_string(),
+                replaces_id: 0,
+                summary: APP_NAME.to_string(),
+                body: get_message(state),
+                expire_timeout: 5000,
+            })
+            .map_err(|_| anyhow!("can't send notification: the bus thread has stopped."))
+    }
+
+    fn take_errors(&self) -> Vec<String> {
+        match self.errors.lock() {
+            Ok(mut errors) => errors.drain(..).collect(),
+            Err(_) => vec![],
+        }
+    }
+}
+
+/// Session bus reached through `gdbus`, which ships with glib on most Linux desktops.
+pub struct GdbusSession {}
+
+impl NotificationBus for GdbusSession {
+    fn call_notify(&self, notification: &Notification) -> Result<u32> {
+        let output = Command::new("gdbus")
+            .args([
+                "call",
+                "--session",
+                "--dest",
+                "org.freedesktop.Notifications",
+                "--object-path",
+                "/org/freedesktop/Notifications",
+                "--method",
+                "org.freedesktop.Notifications.Notify",
+            ])
+            .arg(to_gvariant_string(&notification.app_name))
+            .arg(notification.replaces_id.to_string())
+            .arg(to_gvariant_string(""))
+            .arg(to_gvariant_string(&notification.summary))
+            .arg(to_gvariant_string(&notification.body))
+            .arg("@as []")
+            .arg("@a{sv} {}")
+            .arg(notification.expire_timeout.to_string())
+            .output()?;
+
+        if !output.status.success() {
+            return Err(anyhow!(
+                "can't send notification: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        parse_notification_id(&String::from_utf8_lossy(&output.stdout))
+    }
+}
+
+fn to_gvariant_string(str: &str) -> String {
+    format!("'{}'", str.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+// gdbus replies with "(uint32 12,)".
+fn parse_notification_id(reply: &str) -> Result<u32> {
+    reply
+        .trim()
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .trim_end_matches(',')
+        .trim_start_matches("uint32")
+        .trim()
+        .parse()
+        .map_err(|_| anyhow!("unexpected reply from notification server: {}", reply))
+}
+
+pub struct TerminalNotifier {
+    pub osc9: bool,
+}
+
+impl TerminalNotifier {
+    fn write_notification(&self, out: &mut impl Write, state: &State) -> Result<()> {
+        if self.osc9 {
+            // OSC 9 is shown as a desktop notification by iTerm2, Windows Terminal, kitty, etc.
+            write!(out, "\x1b]9;{}: {}\x07", APP_NAME, get_message(state))?;
+        } else {
+            write!(out, "\x07")?;
+        }
+        out.flush()?;
+
+        Ok(())
+    }
+}
+
+impl Notifier for TerminalNotifier {
+    fn notify(&self, state: &State) -> Result<()> {
+        self.write_notification(&mut stdout(), state)
+    }
+}
+
+/// Runs a user command. `{state}` and `{message}` in the arguments are replaced.
+/// It runs on its own, so a slow command neither holds up the timer nor writes over the TUI.
+pub struct CommandNotifier {
+    pub command: String,
+    pub args: Vec<String>,
+}
+
+impl Notifier for CommandNotifier {
+    fn notify(&self, state: &State) -> Result<()> {
+        let state_name = State::get_state_name(state);
+        let message = get_message(state);
+
+        let mut child = Command::new(&self.command)
+            .args(self.args.iter().map(|arg| {
+                arg.replace("{state}", &state_name)
+                    .replace("{message}", &message)
+            }))
+            .env("POMORKS_STATE", &state_name)
+            .env("POMORKS_MESSAGE", &message)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| anyhow!("can't run notification command {}: {}", self.command, e))?;
+        thread::spawn(move || child.wait());
+
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub struct ToastNotifier {}
+
+#[cfg(target_os = "windows")]
+impl Notifier for ToastNotifier {
+    fn notify(&self, state: &State) -> Result<()> {
+        use std::{path::PathBuf, str::FromStr};
+        use winrt_notification::{Duration, Sound, Toast};
+
+        Toast::new(Toast::POWERSHELL_APP_ID)
+            .title(APP_NAME)
+            .text1(&get_message(state))
+            .sound(Some(Sound::SMS))
+            // TODO!:imageが表示されない問題
+            .image(&PathBuf::from_str("./image/tomato.png")?, "Tomato")
+            .duration(Duration::Short)
+            .show()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    // stands in for the session bus and keeps what it was asked to show; it refuses lunch.
+    struct StandInSessionBus {
+        received: Arc<Mutex<Vec<Notification>>>,
+    }
+
+    impl NotificationBus for StandInSessionBus {
+        fn call_notify(&self, notification: &Notification) -> Result<u32> {
+            if notification.body.starts_with("LUNCH") {
+                return Err(anyhow!("can't send notification: no lunch"));
+            }
+            let mut received = self.received.lock().unwrap();
+            received.push(notification.clone());
+            Ok(received.len() as u32)
+        }
+    }
+
+    #[test]
+    fn test_dbus_notifier() {
+        let received = Arc::new(Mutex::new(vec![]));
+        let notifier = DbusNotifier::new(StandInSessionBus {
+            received: received.clone(),
+        });
+
+        notifier.notify(&State::WORK(2)).unwrap();
+        notifier.notify(&State::LUNCH(4)).unwrap();
+        notifier.notify(&State::BREAK(2)).unwrap();
+
+        let started = Instant::now();
+        while received.lock().unwrap().len() < 2 && started.elapsed() < Duration::from_secs(5) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].app_name, APP_NAME);
+        assert_eq!(received[0].body, "WORK_2 is finish.");
+        assert_eq!(received[1].body, "BREAK is finish.");
+        // calls are made in order, so the failure is in once the last one has arrived.
+        assert_eq!(
+            notifier.take_errors(),
+            vec!["can't send notification: no lunch"]
+        );
+        assert!(notifier.take_errors().is_empty());
+    }
+
+    #[test]
+    fn test_gdbus_helpers() {
+        assert_eq!(to_gvariant_string("it's"), "'it\\'s'");
+        assert_eq!(parse_notification_id("(uint32 12,)\n").unwrap(), 12);
+        assert!(parse_notification_id("Error").is_err());
+    }
+
+    #[test]
+    fn test_terminal_notifier() {
+        let mut out: Vec<u8> = vec![];
+        TerminalNotifier { osc9: true }
+            .write_notification(&mut out, &State::LUNCH(4))
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\x1b]9;Pomodoro-rs: LUNCH is finish.\x07"
+        );
+
+        let mut out: Vec<u8> = vec![];
+        TerminalNotifier { osc9: false }
+            .write_notification(&mut out, &State::LUNCH(4))
+            .unwrap();
+        assert_eq!(out, b"\x07");
+    }
+}
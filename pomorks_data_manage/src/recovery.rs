@@ -0,0 +1,150 @@
+use crate::data_manage_trait::{DataManage, TaskDealing, TaskLogJson, DATE_FORMAT};
+use crate::todo::*;
+use anyhow::Result;
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryAction {
+    Credit,
+    Discard,
+    Resume,
+}
+
+impl RecoveryAction {
+    pub fn name(&self) -> String {
+        match self {
+            RecoveryAction::Credit => "credit".to_string(),
+            RecoveryAction::Discard => "discard".to_string(),
+            RecoveryAction::Resume => "resume".to_string(),
+        }
+    }
+
+    pub fn from_input(input: &str) -> Option<Self> {
+        match input.trim() {
+            "c" | "credit" => Some(RecoveryAction::Credit),
+            "d" | "discard" => Some(RecoveryAction::Discard),
+            "r" | "resume" => Some(RecoveryAction::Resume),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecoveryLogJson {
+    pub id: String,
+    pub state: String,
+    pub start_date: String,
+    pub recovered_date: String,
+    pub action: RecoveryAction,
+}
+
+/// A session which was still running in `TaskDealing` when the app started.
+#[derive(Debug, Clone)]
+pub struct InFlightSession {
+    pub id: String,
+    pub start_time: DateTime<Local>,
+    pub state: State,
+    pub elapsed: i64,
+    pub limit: i64,
+}
+
+impl InFlightSession {
+    pub fn from_task_dealing(task_dealing: &TaskDealing, now: &DateTime<Local>) -> Option<Self> {
+        match (&task_dealing.date, &task_dealing.state) {
+            (Some(start_time), Some(state)) => Some(InFlightSession {
+                id: task_dealing.id.clone().unwrap_or_default(),
+                start_time: *start_time,
+                state: state.clone(),
+                elapsed: (*now - *start_time).num_seconds(),
+                limit: (State::get_limit_time(state) + task_dealing.extended_seconds) as i64,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.elapsed >= self.limit
+    }
+
+    /// When the phase ran out, which is when a credited session is logged.
+    pub fn end_time(&self) -> DateTime<Local> {
+        self.start_time + chrono::Duration::seconds(self.limit)
+    }
+
+    pub fn remaining(&self) -> i64 {
+        if self.is_expired() {
+            0
+        } else {
+            self.limit - self.elapsed
+        }
+    }
+
+    pub fn available_actions(&self) -> Vec<RecoveryAction> {
+        if self.is_expired() {
+            vec![RecoveryAction::Credit, RecoveryAction::Discard]
+        } else {
+            vec![
+                RecoveryAction::Resume,
+                RecoveryAction::Credit,
+                RecoveryAction::Discard,
+            ]
+        }
+    }
+
+    pub fn default_action(&self, grace_seconds: i64) -> RecoveryAction {
+        if !self.is_expired() {
+            RecoveryAction::Resume
+        } else if self.elapsed - self.limit <= grace_seconds {
+            RecoveryAction::Credit
+        } else {
+            RecoveryAction::Discard
+        }
+    }
+
+    /// Carries `action` out and logs it. A credited session is logged and counted for its
+    /// todo; unless resumed, `task_dealing` is left with the phase to go on with, not started.
+    pub fn recover(
+        &self,
+        action: RecoveryAction,
+        task_dealing: &mut TaskDealing,
+        todo_list: &mut TodoList,
+        data_manager: &dyn DataManage,
+        now: &DateTime<Local>,
+    ) -> Result<()> {
+        let state = match action {
+            RecoveryAction::Credit => {
+                if let State::WORK(_) = self.state {
+                    todo_list.increment_executed_count(&self.id);
+                }
+                data_manager.add_task_log(
+                    &TaskLogJson::new(&self.id, &self.end_time(), &self.state, self.limit, false)
+                        .extended(task_dealing.extended_seconds as i64),
+                )?;
+                Some(State::get_next_state(&self.state))
+            }
+            RecoveryAction::Discard => Some(self.state.clone()),
+            RecoveryAction::Resume => None,
+        };
+        if let Some(state) = state {
+            *task_dealing = TaskDealing {
+                id: task_dealing.id.take(),
+                state: Some(state),
+                ..TaskDealing::default()
+            };
+            data_manager.delete_task_dealing()?;
+        }
+
+        data_manager.add_recovery_log(&self.to_log(action, now))
+    }
+
+    pub fn to_log(&self, action: RecoveryAction, now: &DateTime<Local>) -> RecoveryLogJson {
+        RecoveryLogJson {
+            id: self.id.clone(),
+            state: State::get_state_name(&self.state),
+            start_date: self.start_time.format(DATE_FORMAT).to_string(),
+            recovered_date: now.format(DATE_FORMAT).to_string(),
+            action,
+        }
+    }
+}
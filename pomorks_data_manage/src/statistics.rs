@@ -0,0 +1,38 @@
+use crate::data_manage_trait::{SessionKind, TaskLogJson};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SessionSummary {
+    pub work_count: usize,
+    pub break_count: usize,
+    pub work_seconds: i64,
+    pub break_seconds: i64,
+    pub skipped_work_count: usize,
+    pub skipped_break_count: usize,
+}
+
+impl SessionSummary {
+    pub fn from_logs<'a>(task_logs: impl Iterator<Item = &'a TaskLogJson>) -> Self {
+        task_logs.fold(SessionSummary::default(), |mut summary, log| {
+            match (log.kind, log.skipped) {
+                (SessionKind::Work, false) => summary.work_count += 1,
+                (SessionKind::Work, true) => summary.skipped_work_count += 1,
+                (_, false) => summary.break_count += 1,
+                (_, true) => summary.skipped_break_count += 1,
+            }
+            match log.kind {
+                SessionKind::Work => summary.work_seconds += log.actual_seconds,
+                _ => summary.break_seconds += log.actual_seconds,
+            }
+            summary
+        })
+    }
+
+    // break time per work time. `None` while no work has been logged.
+    pub fn break_ratio(&self) -> Option<f64> {
+        if self.work_seconds == 0 {
+            None
+        } else {
+            Some(self.break_seconds as f64 / self.work_seconds as f64)
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::todo::*;
+use anyhow::Result;
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+
+pub const STATUS_CACHE_FILE: &str = "status_cache.json";
+pub const DEFAULT_STATUS_FORMAT: &str = "{state} {time} {title} ({count})";
+/// How long `status` trusts the cache before it asks the backend again.
+pub const STATUS_CACHE_MAX_AGE_SECONDS: i64 = 60;
+
+/// What `status` needs, kept in a local file so it can be shown without asking the backend.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StatusSnapshot {
+    pub state: State,
+    pub start_time: Option<DateTime<Local>>,
+    pub extended_seconds: usize,
+    /// time already spent in a paused phase.
+    #[serde(default)]
+    pub paused_seconds: Option<i64>,
+    pub id: Option<String>,
+    pub title: String,
+    pub todays_count: i64,
+    pub written: DateTime<Local>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct StatusLine {
+    pub state: String,
+    pub running: bool,
+    pub remaining_seconds: i64,
+    pub title: String,
+    pub todays_count: i64,
+}
+
+impl StatusSnapshot {
+    pub fn read_cache() -> Result<Option<StatusSnapshot>> {
+        match fs::read_to_string(STATUS_CACHE_FILE) {
+            Ok(cache) => Ok(Some(serde_json::from_str(&cache)?)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // written aside and renamed into place, so a reader never sees half of it.
+    pub fn write_cache(&self) -> Result<()> {
+        let temp = format!("{}.{}.tmp", STATUS_CACHE_FILE, std::process::id());
+        fs::write(&temp, serde_json::to_string(self)?)?;
+        fs::rename(&temp, STATUS_CACHE_FILE)?;
+
+        Ok(())
+    }
+
+    /// Whether the cache may be out of date: it is old, or its phase has ended since it was written.
+    pub fn is_stale(&self, now: &DateTime<Local>) -> bool {
+        let age = (*now - self.written).num_seconds();
+        let ended = match self.start_time {
+            Some(start) => {
+                let limit = (State::get_limit_time(&self.state) + self.extended_seconds) as i64;
+                let end = start + chrono::Duration::seconds(limit);
+                self.written < end && end <= *now
+            }
+            None => false,
+        };
+
+        ended || !(0..STATUS_CACHE_MAX_AGE_SECONDS).contains(&age)
+    }
+
+    // remaining time goes below zero only when the timer keeps counting in overtime.
+    pub fn to_status_line(&self, now: &DateTime<Local>, overtime: bool) -> StatusLine {
+        let limit = (State::get_limit_time(&self.state) + self.extended_seconds) as i64;
+        let remaining_seconds = match self.start_time {
+            Some(start) if overtime => limit - (*now - start).num_seconds(),
+            Some(start) => (limit - (*now - start).num_seconds()).max(0),
+            None => limit - self.paused_seconds.unwrap_or(0),
+        };
+
+        StatusLine {
+            state: State::get_state_name(&self.state),
+            running: self.start_time.is_some(),
+            remaining_seconds,
+            title: self.title.clone(),
+            // the count was taken on another day.
+            todays_count: if self.written.date() == now.date() {
+                self.todays_count
+            } else {
+                0
+            },
+        }
+    }
+}
+
+impl StatusLine {
+    /// `{state}`, `{remaining}`, `{time}`, `{title}`, `{count}` and `{running}` are replaced.
+    pub fn format(&self, format: &str) -> String {
+        let sign = if self.remaining_seconds < 0 { "-" } else { "" };
+        let time = format!(
+            "{}{:02}:{:02}",
+            sign,
+            self.remaining_seconds.abs() / 60,
+            self.remaining_seconds.abs() % 60
+        );
+
+        format
+            .replace("{state}", &self.state)
+            .replace("{remaining}", &self.remaining_seconds.to_string())
+            .replace("{time}", &time)
+            .replace("{title}", &self.title)
+            .replace("{count}", &self.todays_count.to_string())
+            .replace("{running}", &self.running.to_string())
+    }
+}
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::str::FromStr;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TodoList {
     todo_list: HashMap<String, TodoItem>,
 }
@@ -40,11 +40,47 @@ impl TodoList {
         Ok(())
     }
 
+    pub fn get_todo(&self, id: &str) -> Option<TodoItem> {
+        self.todo_list.get(id).cloned()
+    }
+
     pub fn get_vec_of_todo(&self) -> Vec<TodoItem> {
-        self.todo_list
-            .iter()
-            .map(|(_, todo)| todo.clone())
-            .collect()
+        self.todo_list.values().cloned().collect()
+    }
+
+    pub fn increment_executed_count(&mut self, id: &str) {
+        if let Some(todo) = self.todo_list.get_mut(id) {
+            todo.executed_count += 1;
+        }
+    }
+
+    pub fn decrement_executed_count(&mut self, id: &str) {
+        if let Some(todo) = self.todo_list.get_mut(id) {
+            todo.executed_count = todo.executed_count.saturating_sub(1);
+        }
+    }
+
+    /// Keeps the board in step with the focus: the focused todo sits in doing, and the one
+    /// focused before goes back to today. Returns whether a todo moved.
+    pub fn move_focus(&mut self, previous: Option<&str>, focus: Option<&str>) -> bool {
+        let mut moved = false;
+        if let Some(todo) = previous
+            .filter(|previous| Some(*previous) != focus)
+            .and_then(|previous| self.todo_list.get_mut(previous))
+            .filter(|todo| todo.column() == TodoStatus::Doing)
+        {
+            todo.move_to(TodoStatus::Today);
+            moved = true;
+        }
+        if let Some(todo) = focus
+            .and_then(|focus| self.todo_list.get_mut(focus))
+            .filter(|todo| todo.column() < TodoStatus::Doing)
+        {
+            todo.move_to(TodoStatus::Doing);
+            moved = true;
+        }
+
+        moved
     }
 
     pub fn drain_finished_todo(&mut self) -> Vec<TodoItem> {
@@ -61,6 +97,36 @@ impl Default for TodoList {
     }
 }
 
+/// The column of a todo on the board.
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub enum TodoStatus {
+    #[default]
+    Backlog,
+    Today,
+    Doing,
+    Done,
+}
+
+impl TodoStatus {
+    pub const ALL: [TodoStatus; 4] = [
+        TodoStatus::Backlog,
+        TodoStatus::Today,
+        TodoStatus::Doing,
+        TodoStatus::Done,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TodoStatus::Backlog => "backlog",
+            TodoStatus::Today => "today",
+            TodoStatus::Doing => "doing",
+            TodoStatus::Done => "done",
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct TodoItem {
@@ -72,6 +138,9 @@ pub struct TodoItem {
     pub executed_count: usize,
     pub finished: bool,
     pub detail: String,
+    // the finished flag stays the one truth for done, so todos saved before the board load as they were.
+    #[serde(default)]
+    pub status: TodoStatus,
 }
 
 impl TodoItem {
@@ -93,6 +162,32 @@ impl TodoItem {
             executed_count,
             finished: false,
             detail,
+            status: TodoStatus::Backlog,
+        }
+    }
+
+    /// A finished todo is done whatever its status says; one reopened from done goes back to the backlog.
+    pub fn column(&self) -> TodoStatus {
+        match (self.finished, self.status) {
+            (true, _) => TodoStatus::Done,
+            (false, TodoStatus::Done) => TodoStatus::Backlog,
+            (false, status) => status,
+        }
+    }
+
+    pub fn move_to(&mut self, status: TodoStatus) {
+        self.status = status;
+        self.finished = status == TodoStatus::Done;
+    }
+
+    /// Finishing moves the todo to done; reopening it sends it back to the backlog.
+    pub fn set_finished(&mut self, finished: bool) {
+        if finished != self.finished {
+            self.move_to(if finished {
+                TodoStatus::Done
+            } else {
+                TodoStatus::Backlog
+            });
         }
     }
 }
@@ -106,16 +201,8 @@ impl FromStr for TodoItem {
             return Err(anyhow!("Todo String Parse Error."));
         }
 
-        let mut id: Vec<char> = vec![];
-        for _num in 1..11 {
-            let rand_num = rand::thread_rng().gen_range(97..123);
-            if let Some(rand_num) = std::char::from_u32(rand_num) {
-                id.push(rand_num);
-            }
-        }
-
         Ok(TodoItem {
-            id: id.iter().collect(),
+            id: generate_id(),
             title: spl[0].to_string(),
             tag: spl[1].to_string(),
             project: spl[2].to_string(),
@@ -123,17 +210,30 @@ impl FromStr for TodoItem {
             executed_count: 0,
             finished: false,
             detail: String::new(),
+            status: TodoStatus::Backlog,
         })
     }
 }
 
+pub fn generate_id() -> String {
+    let mut id: Vec<char> = vec![];
+    for _num in 1..11 {
+        let rand_num = rand::thread_rng().gen_range(97..123);
+        if let Some(rand_num) = std::char::from_u32(rand_num) {
+            id.push(rand_num);
+        }
+    }
+
+    id.iter().collect()
+}
+
 #[cfg(debug_assertions)]
 pub const ONE_MINUTE: usize = 1;
 #[cfg(not(debug_assertions))]
 pub const ONE_MINUTE: usize = 60;
 type WorkCount = usize;
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum State {
     WORK(WorkCount),
     BREAK(WorkCount),
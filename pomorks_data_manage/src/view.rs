@@ -0,0 +1,244 @@
+use crate::data_manage_trait::TaskLogJson;
+use crate::todo::TodoItem;
+use anyhow::Result;
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{ErrorKind, Write};
+
+pub const LIST_VIEW_FILE: &str = "list_view.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortMode {
+    /// unfinished first, those already worked on before the untouched ones.
+    #[default]
+    Priority,
+    /// most pomodoros left to the estimate first.
+    Remaining,
+    Project,
+    /// the todo with the latest pomodoro first.
+    Recent,
+}
+
+impl SortMode {
+    pub const ALL: [SortMode; 4] = [
+        SortMode::Priority,
+        SortMode::Remaining,
+        SortMode::Project,
+        SortMode::Recent,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortMode::Priority => "priority",
+            SortMode::Remaining => "remaining",
+            SortMode::Project => "project",
+            SortMode::Recent => "recent",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        SortMode::ALL
+            .iter()
+            .copied()
+            .find(|mode| mode.name() == name)
+    }
+
+    pub fn next(&self) -> Self {
+        let ind = SortMode::ALL
+            .iter()
+            .position(|mode| mode == self)
+            .unwrap_or(0);
+        SortMode::ALL[(ind + 1) % SortMode::ALL.len()]
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    #[default]
+    None,
+    Project,
+    Tag,
+}
+
+impl GroupBy {
+    pub fn next(&self) -> Self {
+        match self {
+            GroupBy::None => GroupBy::Project,
+            GroupBy::Project => GroupBy::Tag,
+            GroupBy::Tag => GroupBy::None,
+        }
+    }
+}
+
+/// The todos sharing a project or a tag, in the order of the list.
+#[derive(Debug, Clone)]
+pub struct TodoGroup {
+    pub name: String,
+    pub todos: Vec<TodoItem>,
+}
+
+impl TodoGroup {
+    pub fn estimate_count(&self) -> usize {
+        self.todos.iter().map(|todo| todo.estimate_count).sum()
+    }
+
+    pub fn executed_count(&self) -> usize {
+        self.todos.iter().map(|todo| todo.executed_count).sum()
+    }
+
+    pub fn unfinished_count(&self) -> usize {
+        self.todos.iter().filter(|todo| !todo.finished).count()
+    }
+}
+
+/// How the todo list is narrowed and ordered. Kept in a local file between sessions.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ListView {
+    /// matched against title, tag, project and detail, ignoring case.
+    pub search: String,
+    pub hide_finished: bool,
+    pub tag: Option<String>,
+    pub project: Option<String>,
+    /// only todos that took more pomodoros than estimated.
+    pub over_estimate: bool,
+    pub sort: SortMode,
+    pub group: GroupBy,
+}
+
+impl ListView {
+    pub fn read() -> Result<ListView> {
+        match fs::read_to_string(LIST_VIEW_FILE) {
+            Ok(view) => Ok(serde_json::from_str(&view)?),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(ListView::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn write(&self) -> Result<()> {
+        let serialized = serde_json::to_string(self)?;
+        let mut file = File::create(LIST_VIEW_FILE)?;
+        write!(file, "{}", serialized)?;
+
+        Ok(())
+    }
+
+    pub fn is_match(&self, todo: &TodoItem) -> bool {
+        let search = self.search.to_lowercase();
+        let search_match = search.is_empty()
+            || [&todo.title, &todo.tag, &todo.project, &todo.detail]
+                .iter()
+                .any(|text| text.to_lowercase().contains(&search));
+
+        search_match
+            && !(self.hide_finished && todo.finished)
+            && self.tag.iter().all(|tag| &todo.tag == tag)
+            && self.project.iter().all(|project| &todo.project == project)
+            && (!self.over_estimate || todo.executed_count > todo.estimate_count)
+    }
+
+    /// The matching todos in the order of `sort`. Ties are broken by title.
+    pub fn apply(&self, todos: Vec<TodoItem>, task_logs: &[TaskLogJson]) -> Vec<TodoItem> {
+        let mut todos: Vec<TodoItem> = todos
+            .into_iter()
+            .filter(|todo| self.is_match(todo))
+            .collect();
+        todos.sort_by(|a, b| a.title.cmp(&b.title));
+
+        match self.sort {
+            SortMode::Priority => {
+                todos.sort_by_key(|todo| (todo.finished, todo.executed_count == 0));
+            }
+            SortMode::Remaining => {
+                todos.sort_by_key(|todo| {
+                    Reverse(todo.estimate_count.saturating_sub(todo.executed_count))
+                });
+            }
+            SortMode::Project => todos.sort_by(|a, b| a.project.cmp(&b.project)),
+            SortMode::Recent => {
+                let mut last_worked: HashMap<&str, DateTime<Local>> = HashMap::new();
+                for log in task_logs {
+                    if let Some(date) = log.get_date() {
+                        let last = last_worked.entry(&log.id).or_insert(date);
+                        *last = (*last).max(date);
+                    }
+                }
+                // never worked on is `None`, which sorts last when reversed.
+                todos.sort_by_key(|todo| Reverse(last_worked.get(todo.id.as_str()).copied()));
+            }
+        }
+        todos
+    }
+
+    /// Groups by name; `todos` is expected in the order `apply` gave it.
+    pub fn group(&self, todos: Vec<TodoItem>) -> Vec<TodoGroup> {
+        let mut groups: Vec<TodoGroup> = vec![];
+        for todo in todos {
+            let name = match self.group {
+                GroupBy::None => "",
+                GroupBy::Project => &todo.project,
+                GroupBy::Tag => &todo.tag,
+            }
+            .to_string();
+            match groups.iter_mut().find(|group| group.name == name) {
+                Some(group) => group.todos.push(todo),
+                None => groups.push(TodoGroup {
+                    name,
+                    todos: vec![todo],
+                }),
+            }
+        }
+        groups.sort_by(|a, b| a.name.cmp(&b.name));
+        groups
+    }
+
+    pub fn is_filtered(&self) -> bool {
+        !self.search.is_empty()
+            || self.hide_finished
+            || self.tag.is_some()
+            || self.project.is_some()
+            || self.over_estimate
+    }
+
+    /// Keeps the sort and the grouping.
+    pub fn clear_filter(&mut self) {
+        *self = ListView {
+            sort: self.sort,
+            group: self.group,
+            ..ListView::default()
+        };
+    }
+
+    /// e.g. `"doc" #doc unfinished by recent per project`.
+    pub fn describe(&self) -> String {
+        let mut parts = vec![];
+        if !self.search.is_empty() {
+            parts.push(format!("\"{}\"", self.search));
+        }
+        if let Some(tag) = &self.tag {
+            parts.push(format!("#{}", tag));
+        }
+        if let Some(project) = &self.project {
+            parts.push(format!("@{}", project));
+        }
+        if self.hide_finished {
+            parts.push("unfinished".to_string());
+        }
+        if self.over_estimate {
+            parts.push("over estimate".to_string());
+        }
+
+        parts.push(format!("by {}", self.sort.name()));
+        match self.group {
+            GroupBy::None => {}
+            GroupBy::Project => parts.push("per project".to_string()),
+            GroupBy::Tag => parts.push("per tag".to_string()),
+        }
+        parts.join(" ")
+    }
+}
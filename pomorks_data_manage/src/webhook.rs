@@ -0,0 +1,310 @@
+use crate::config::WebhookConfig;
+use crate::data_manage_trait::TaskLogJson;
+use crate::todo::*;
+use anyhow::{anyhow, Result};
+use chrono::prelude::*;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+pub const WEBHOOK_QUEUE_FILE: &str = "webhook_queue.json";
+pub const WEBHOOK_DRY_RUN_FILE: &str = "webhook_dry_run.jsonl";
+pub const SIGNATURE_HEADER: &str = "X-Pomorks-Signature";
+/// Longest wait between two tries of a delivery, however often it failed.
+pub const MAX_RETRY_SECONDS: i64 = 24 * 60 * 60;
+
+/// Body of the POST sent when a session is logged.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WebhookPayload {
+    pub event: String,
+    pub todo: Option<WebhookTodo>,
+    pub project: String,
+    pub tags: Vec<String>,
+    pub state: String,
+    pub duration_seconds: i64,
+    pub planned_seconds: i64,
+    pub skipped: bool,
+    pub date: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WebhookTodo {
+    pub id: String,
+    pub title: String,
+    pub estimate_count: usize,
+    pub executed_count: usize,
+}
+
+impl WebhookPayload {
+    pub fn from_session(log: &TaskLogJson, state: &State, todo: Option<&TodoItem>) -> Self {
+        WebhookPayload {
+            event: "session_completed".to_string(),
+            todo: todo.map(|todo| WebhookTodo {
+                id: todo.id.clone(),
+                title: todo.title.clone(),
+                estimate_count: todo.estimate_count,
+                executed_count: todo.executed_count,
+            }),
+            project: todo.map(|todo| todo.project.clone()).unwrap_or_default(),
+            tags: todo
+                .iter()
+                .filter(|todo| !todo.tag.is_empty())
+                .map(|todo| todo.tag.clone())
+                .collect(),
+            state: State::get_state_name(state),
+            duration_seconds: log.actual_seconds,
+            planned_seconds: log.planned_seconds,
+            skipped: log.skipped,
+            date: log.date.clone(),
+        }
+    }
+}
+
+/// `sha256=<hex>` of the HMAC-SHA256 of `body`, sent in `SIGNATURE_HEADER`.
+pub fn sign(secret: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any size");
+    mac.update(body.as_bytes());
+
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Wait before the next try after `attempts` failed ones: doubled each time, up to a day.
+pub fn retry_delay(retry_seconds: i64, attempts: u32) -> i64 {
+    retry_seconds
+        .saturating_mul(2_i64.saturating_pow(attempts.saturating_sub(1)))
+        .min(MAX_RETRY_SECONDS)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct QueuedDelivery {
+    pub url: String,
+    pub body: String,
+    pub attempts: u32,
+    /// unix time of the next try.
+    pub next_attempt: i64,
+}
+
+#[derive(Serialize)]
+struct DryRunEntry<'a> {
+    url: &'a str,
+    signature: Option<String>,
+    body: &'a str,
+}
+
+/// Posts session payloads to the configured URLs.
+/// Failed deliveries wait in a queue file and are retried with backoff.
+#[derive(Clone, Default)]
+pub struct WebhookNotifier {
+    config: WebhookConfig,
+    dir: PathBuf,
+    // one delivery run at a time, so the queue file isn't written twice.
+    lock: Arc<Mutex<()>>,
+    errors: Arc<Mutex<Vec<String>>>,
+    // unix time the first queued delivery is due, as of the last run.
+    next_retry: Arc<Mutex<Option<i64>>>,
+}
+
+impl WebhookNotifier {
+    pub fn new(config: WebhookConfig) -> Self {
+        WebhookNotifier {
+            config,
+            ..WebhookNotifier::default()
+        }
+    }
+
+    /// Keeps the queue and dry-run files in `dir` instead of the working directory.
+    pub fn in_dir(self, dir: &Path) -> Self {
+        WebhookNotifier {
+            dir: dir.to_path_buf(),
+            ..self
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.config.urls.is_empty()
+    }
+
+    /// Delivers in the background; failures show up in `take_errors`.
+    pub fn session_completed(&self, payload: WebhookPayload) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        self.in_background(move |notifier| notifier.deliver(&payload));
+    }
+
+    /// Sends what an earlier run left in the queue, e.g. on startup.
+    pub fn retry_in_background(&self) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        self.in_background(|notifier| notifier.retry_pending());
+    }
+
+    /// Retries in the background once the queue has a delivery due; called on every tick.
+    pub fn retry_if_due(&self, now: DateTime<Local>) {
+        let due = match self.next_retry.lock() {
+            Ok(mut next_retry) if matches!(*next_retry, Some(at) if at <= now.timestamp()) => {
+                // the run sets it again for whatever is still pending.
+                next_retry.take().is_some()
+            }
+            _ => false,
+        };
+        if due {
+            self.retry_in_background();
+        }
+    }
+
+    pub fn next_retry(&self) -> Option<i64> {
+        self.next_retry
+            .lock()
+            .ok()
+            .and_then(|next_retry| *next_retry)
+    }
+
+    pub fn take_errors(&self) -> Vec<String> {
+        match self.errors.lock() {
+            Ok(mut errors) => errors.drain(..).collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    /// Queues the payload for every URL and sends everything that is due.
+    pub fn deliver(&self, payload: &WebhookPayload) -> Result<()> {
+        let _guard = self
+            .lock
+            .lock()
+            .map_err(|_| anyhow!("webhook queue is poisoned."))?;
+
+        let body = serde_json::to_string(payload)?;
+        let mut queue = self.read_queue()?;
+        let now = Local::now().timestamp();
+        queue.extend(self.config.urls.iter().map(|url| QueuedDelivery {
+            url: url.clone(),
+            body: body.clone(),
+            attempts: 0,
+            next_attempt: now,
+        }));
+
+        self.send_due(queue)
+    }
+
+    /// Sends the queued deliveries whose retry time has come.
+    pub fn retry_pending(&self) -> Result<()> {
+        let _guard = self
+            .lock
+            .lock()
+            .map_err(|_| anyhow!("webhook queue is poisoned."))?;
+
+        let queue = self.read_queue()?;
+        self.send_due(queue)
+    }
+
+    pub fn read_queue(&self) -> Result<Vec<QueuedDelivery>> {
+        match fs::read_to_string(self.dir.join(WEBHOOK_QUEUE_FILE)) {
+            Ok(queue_json) => Ok(serde_json::from_str(&queue_json)?),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(vec![]),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn in_background<F>(&self, deliver: F)
+    where
+        F: FnOnce(&WebhookNotifier) -> Result<()> + Send + 'static,
+    {
+        let notifier = self.clone();
+        thread::spawn(move || {
+            if let Err(e) = deliver(&notifier) {
+                if let Ok(mut errors) = notifier.errors.lock() {
+                    errors.push(format!("webhook failed: {}", e));
+                }
+            }
+        });
+    }
+
+    fn write_queue(&self, queue: &[QueuedDelivery]) -> Result<()> {
+        let path = self.dir.join(WEBHOOK_QUEUE_FILE);
+        if queue.is_empty() {
+            return match fs::remove_file(path) {
+                Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            };
+        }
+
+        Ok(fs::write(path, serde_json::to_string(queue)?)?)
+    }
+
+    fn send_due(&self, queue: Vec<QueuedDelivery>) -> Result<()> {
+        let now = Local::now().timestamp();
+        let mut pending = vec![];
+        let mut errors = vec![];
+
+        for mut delivery in queue {
+            if delivery.next_attempt > now {
+                pending.push(delivery);
+                continue;
+            }
+            if let Err(e) = self.send(&delivery) {
+                delivery.attempts += 1;
+                if delivery.attempts >= self.config.max_attempts {
+                    errors.push(format!("{}: {}, giving up", delivery.url, e));
+                    continue;
+                }
+                errors.push(format!("{}: {}", delivery.url, e));
+                delivery.next_attempt =
+                    now.saturating_add(retry_delay(self.config.retry_seconds, delivery.attempts));
+                pending.push(delivery);
+            }
+        }
+        self.write_queue(&pending)?;
+        if let Ok(mut next_retry) = self.next_retry.lock() {
+            *next_retry = pending.iter().map(|delivery| delivery.next_attempt).min();
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!(errors.join(", ")))
+        }
+    }
+
+    fn send(&self, delivery: &QueuedDelivery) -> Result<()> {
+        let signature = if self.config.secret.is_empty() {
+            None
+        } else {
+            Some(sign(&self.config.secret, &delivery.body))
+        };
+
+        if self.config.dry_run {
+            let mut dry_run_log = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.dir.join(WEBHOOK_DRY_RUN_FILE))?;
+            let entry = DryRunEntry {
+                url: &delivery.url,
+                signature,
+                body: &delivery.body,
+            };
+            writeln!(dry_run_log, "{}", serde_json::to_string(&entry)?)?;
+            return Ok(());
+        }
+
+        let mut request = ureq::post(&delivery.url)
+            .timeout(Duration::from_secs(self.config.timeout_seconds))
+            .set("Content-Type", "application/json");
+        if let Some(signature) = &signature {
+            request = request.set(SIGNATURE_HEADER, signature);
+        }
+        request.send_string(&delivery.body)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+#[cfg(test)]
+mod tests {
+    use pomorks_data_manage::config::*;
+
+    #[test]
+    fn test_wip_limits_of_unknown_columns_are_rejected() {
+        let config: Config = toml::from_str("[board.wip_limits]\ndoing = 2\ntoday = 5\n").unwrap();
+        assert!(config.board.validate().is_ok());
+
+        let config: Config = toml::from_str("[board.wip_limits]\ndoign = 2\n").unwrap();
+        let error = config.board.validate().unwrap_err();
+        assert!(error.to_string().contains("doign"));
+    }
+
+    #[test]
+    fn test_profiles() {
+        let config: Config =
+            toml::from_str("[timer]\nextend_minutes = 3\n\n[profiles.deep]\novertime = true\n")
+                .unwrap();
+        assert_eq!(config.timer.extend_minutes, 3);
+
+        let deep = &config.profiles["deep"];
+        assert!(deep.overtime);
+        // unset keys fall back to the defaults, not to `[timer]`.
+        assert_eq!(deep.extend_minutes, TimerConfig::default().extend_minutes);
+    }
+}
@@ -0,0 +1,32 @@
+// the json backend works in the current dir, so this binary changes it once for all its tests.
+#[cfg(test)]
+mod tests {
+    use pomorks_data_manage::data_manage_json::DataManageJson;
+    use pomorks_data_manage::data_manage_trait::DataManage;
+    use std::{env, fs, process};
+
+    #[test]
+    fn test_migrate_gives_old_logs_a_stable_id() {
+        let dir = env::temp_dir().join(format!("pomorks-test-json-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        env::set_current_dir(&dir).unwrap();
+        let old_logs = r#"[{"id":"abc","date":"2022/06/03 10:00:00+09:00"},{"log_id":"kept","id":"","date":"2022/06/03 11:00:00+09:00"}]"#;
+        fs::write("task_log.json", old_logs).unwrap();
+        let data_manager = DataManageJson {};
+
+        // reading leaves the file alone.
+        assert_eq!(data_manager.get_log_all().unwrap()[0].log_id, "");
+        assert_eq!(fs::read_to_string("task_log.json").unwrap(), old_logs);
+
+        data_manager.migrate().unwrap();
+        let migrated = data_manager.get_log_all().unwrap();
+        assert!(!migrated[0].log_id.is_empty());
+        assert_eq!(migrated[1].log_id, "kept");
+        assert_eq!(
+            data_manager.get_log_all().unwrap()[0].log_id,
+            migrated[0].log_id
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
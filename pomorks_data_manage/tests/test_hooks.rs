@@ -0,0 +1,189 @@
+#[cfg(test)]
+mod tests {
+    use pomorks_data_manage::config::HooksConfig;
+    use pomorks_data_manage::hooks::*;
+    use pomorks_data_manage::todo::*;
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    #[test]
+    fn test_phase_events() {
+        assert_eq!(
+            HookEvent::from_phase(&State::WORK(3), true),
+            HookEvent::WorkStart
+        );
+        assert_eq!(
+            HookEvent::from_phase(&State::WORK(4), false),
+            HookEvent::WorkEnd
+        );
+        assert_eq!(
+            HookEvent::from_phase(&State::LUNCH(4), true),
+            HookEvent::BreakStart
+        );
+        assert_eq!(
+            HookEvent::from_phase(&State::BREAK(1), false),
+            HookEvent::BreakEnd
+        );
+    }
+
+    #[test]
+    fn test_payload_envs() {
+        let todo = TodoItem::from_str("write doc pomorks 2").unwrap();
+        let payload = HookPayload::new(HookEvent::WorkStart, &State::WORK(1), vec![todo.clone()]);
+        let envs = payload.get_envs();
+        let env = |key: &str| {
+            envs.iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| value.clone())
+                .unwrap()
+        };
+
+        assert_eq!(env("POMORKS_EVENT"), "work_start");
+        assert_eq!(env("POMORKS_STATE"), "WORK_1");
+        assert_eq!(env("POMORKS_TODO_ID"), todo.id);
+        assert_eq!(env("POMORKS_TODO_TITLE"), "write");
+        assert_eq!(env("POMORKS_TODO_PROJECT"), "pomorks");
+        assert_eq!(env("POMORKS_TODO_COUNT"), "1");
+
+        let payload = HookPayload::new(HookEvent::BreakEnd, &State::BREAK(1), vec![]);
+        assert!(serde_json::to_string(&payload)
+            .unwrap()
+            .starts_with(r#"{"event":"break_end","state":"BREAK","todos":[]"#));
+    }
+
+    #[cfg(unix)]
+    mod scripts {
+        use super::*;
+        use std::os::unix::fs::PermissionsExt;
+        use std::path::PathBuf;
+        use std::time::Instant;
+        use std::{env, fs, thread};
+
+        // removes the scripts even when an assertion fails.
+        struct ScriptDir(PathBuf);
+
+        impl ScriptDir {
+            fn new(name: &str) -> Self {
+                let dir =
+                    env::temp_dir().join(format!("pomorks-hooks-{}-{}", name, std::process::id()));
+                let _ = fs::remove_dir_all(&dir);
+                fs::create_dir_all(&dir).unwrap();
+                ScriptDir(dir)
+            }
+
+            fn script(&self, name: &str, body: &str) -> String {
+                let path = self.0.join(name);
+                fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+                fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+                path.to_string_lossy().to_string()
+            }
+        }
+
+        impl Drop for ScriptDir {
+            fn drop(&mut self) {
+                let _ = fs::remove_dir_all(&self.0);
+            }
+        }
+
+        fn payload() -> HookPayload {
+            HookPayload::new(
+                HookEvent::TodoFinished,
+                &State::WORK(2),
+                vec![TodoItem::from_str("write doc pomorks 2").unwrap()],
+            )
+        }
+
+        #[test]
+        fn test_run_hook_passes_envs_and_stdin() {
+            let dir = ScriptDir::new("stdin");
+            let out = dir.0.join("out");
+            let hook = dir.script(
+                "hook",
+                &format!(
+                    "echo \"$POMORKS_EVENT $POMORKS_TODO_TITLE\" > {0}\ncat >> {0}",
+                    out.display()
+                ),
+            );
+
+            run_hook(&hook, &payload(), Duration::from_secs(5)).unwrap();
+
+            let out = fs::read_to_string(out).unwrap();
+            let (envs, json) = out.split_once('\n').unwrap();
+            assert_eq!(envs, "todo_finished write");
+            let json: serde_json::Value = serde_json::from_str(json).unwrap();
+            assert_eq!(json["event"], "todo_finished");
+            assert_eq!(json["todos"][0]["Title"], "write");
+        }
+
+        #[test]
+        fn test_run_hook_reports_failure_and_timeout() {
+            let dir = ScriptDir::new("failure");
+            let failing = dir.script("failing", "exit 3");
+            assert!(run_hook(&failing, &payload(), Duration::from_secs(5)).is_err());
+
+            let slow = dir.script("slow", "exec sleep 10");
+            let started = Instant::now();
+            let result = run_hook(&slow, &payload(), Duration::from_millis(200));
+            assert!(result.unwrap_err().to_string().contains("timed out"));
+            assert!(started.elapsed() < Duration::from_secs(5));
+        }
+
+        #[test]
+        fn test_timeout_covers_a_payload_left_unread() {
+            let dir = ScriptDir::new("unread");
+            let slow = dir.script("slow", "exec sleep 10");
+            let mut todo = TodoItem::from_str("write doc pomorks 2").unwrap();
+            // more than a pipe holds.
+            todo.detail = "x".repeat(1 << 20);
+            let payload = HookPayload::new(HookEvent::TodoFinished, &State::WORK(2), vec![todo]);
+
+            let started = Instant::now();
+            let result = run_hook(&slow, &payload, Duration::from_millis(200));
+            assert!(result.unwrap_err().to_string().contains("timed out"));
+            assert!(started.elapsed() < Duration::from_secs(5));
+        }
+
+        #[test]
+        fn test_fire_does_not_wait() {
+            let dir = ScriptDir::new("fire");
+            let slow = dir.script("slow", "exec sleep 10");
+            let runner = HookRunner::new(HooksConfig {
+                timeout_seconds: 1,
+                todo_finished: vec![slow, dir.0.join("not_exist").display().to_string()],
+                ..HooksConfig::default()
+            });
+
+            let started = Instant::now();
+            runner.fire(payload());
+            assert!(started.elapsed() < Duration::from_millis(500));
+
+            let mut errors = vec![];
+            while errors.len() < 2 && started.elapsed() < Duration::from_secs(5) {
+                errors.append(&mut runner.take_errors());
+                thread::sleep(Duration::from_millis(50));
+            }
+            assert_eq!(errors.len(), 2);
+            assert!(errors.iter().any(|error| error.contains("timed out")));
+        }
+
+        #[test]
+        fn test_wait_for_fired_hooks() {
+            let dir = ScriptDir::new("wait");
+            let out = dir.0.join("out");
+            let hook = dir.script(
+                "hook",
+                &format!("sleep 0.3\necho \"$POMORKS_EVENT\" > {}", out.display()),
+            );
+            let runner = HookRunner::new(HooksConfig {
+                todo_finished: vec![hook],
+                ..HooksConfig::default()
+            });
+
+            runner.fire(payload());
+            runner.wait();
+
+            assert_eq!(fs::read_to_string(out).unwrap(), "todo_finished\n");
+            assert!(runner.take_errors().is_empty());
+        }
+    }
+}
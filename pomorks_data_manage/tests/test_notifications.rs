@@ -0,0 +1,361 @@
+#[cfg(all(test, unix))]
+mod tests {
+    use pomorks_data_manage::notifications::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::os::unix::fs::MetadataExt;
+    use std::os::unix::net::UnixStream;
+    use std::path::{Path, PathBuf};
+    use std::process::{Child, Command, Stdio};
+    use std::sync::mpsc::{self, Receiver};
+    use std::time::Duration;
+    use std::{env, fs, thread};
+
+    const NOTIFICATIONS: &str = "org.freedesktop.Notifications";
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    // a private `dbus-daemon --session`, stopped and removed even when an assertion fails.
+    struct SessionBus {
+        daemon: Child,
+        dir: PathBuf,
+    }
+
+    impl SessionBus {
+        fn start(name: &str) -> Option<Self> {
+            let dir = env::temp_dir().join(format!("pomorks-dbus-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            let mut daemon = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .arg(format!("--address=unix:path={}", dir.join("bus").display()))
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()?;
+
+            // the address is printed once the bus listens.
+            let mut address = String::new();
+            BufReader::new(daemon.stdout.take().unwrap())
+                .read_line(&mut address)
+                .unwrap();
+            Some(SessionBus { daemon, dir })
+        }
+
+        fn address(&self) -> String {
+            format!("unix:path={}", self.socket().display())
+        }
+
+        fn socket(&self) -> PathBuf {
+            self.dir.join("bus")
+        }
+    }
+
+    impl Drop for SessionBus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    // just enough of the D-Bus wire format for a notification server, little endian only.
+    #[derive(Default)]
+    struct Writer(Vec<u8>);
+
+    impl Writer {
+        fn align(&mut self, n: usize) {
+            self.0.resize(self.0.len().div_ceil(n) * n, 0);
+        }
+
+        fn u32(&mut self, value: u32) {
+            self.align(4);
+            self.0.extend_from_slice(&value.to_le_bytes());
+        }
+
+        fn string(&mut self, value: &str) {
+            self.u32(value.len() as u32);
+            self.0.extend_from_slice(value.as_bytes());
+            self.0.push(0);
+        }
+
+        fn signature(&mut self, value: &str) {
+            self.0.push(value.len() as u8);
+            self.0.extend_from_slice(value.as_bytes());
+            self.0.push(0);
+        }
+    }
+
+    struct Reader<'a> {
+        buf: &'a [u8],
+        pos: usize,
+    }
+
+    impl Reader<'_> {
+        fn align(&mut self, n: usize) {
+            self.pos = self.pos.div_ceil(n) * n;
+        }
+
+        fn u32(&mut self) -> u32 {
+            self.align(4);
+            let value = u32::from_le_bytes(self.buf[self.pos..self.pos + 4].try_into().unwrap());
+            self.pos += 4;
+            value
+        }
+
+        fn string(&mut self) -> String {
+            let len = self.u32() as usize;
+            let value = String::from_utf8(self.buf[self.pos..self.pos + len].to_vec()).unwrap();
+            self.pos += len + 1;
+            value
+        }
+
+        fn signature(&mut self) -> String {
+            let len = self.buf[self.pos] as usize;
+            let value = String::from_utf8(self.buf[self.pos + 1..self.pos + 1 + len].to_vec());
+            self.pos += len + 2;
+            value.unwrap()
+        }
+
+        // the length of an array, then past it; `element_align` applies even when it is empty.
+        fn skip_array(&mut self, element_align: usize) {
+            let len = self.u32() as usize;
+            self.align(element_align);
+            self.pos += len;
+        }
+    }
+
+    enum Field<'a> {
+        Str(u8, &'a str, &'a str),
+        U32(u8, u32),
+    }
+
+    struct Message {
+        kind: u8,
+        member: String,
+        sender: String,
+        serial: u32,
+        reply_serial: u32,
+        body: Vec<u8>,
+    }
+
+    fn write_message(
+        stream: &mut UnixStream,
+        kind: u8,
+        serial: u32,
+        fields: &[Field],
+        body: &[u8],
+    ) {
+        let mut message = Writer::default();
+        message.0.extend_from_slice(&[b'l', kind, 0, 1]);
+        message.u32(body.len() as u32);
+        message.u32(serial);
+
+        let mut array = Writer(vec![0; 16]);
+        for field in fields {
+            array.align(8);
+            match field {
+                Field::Str(code, signature, value) => {
+                    array.0.push(*code);
+                    array.signature(signature);
+                    match *signature {
+                        "g" => array.signature(value),
+                        _ => array.string(value),
+                    }
+                }
+                Field::U32(code, value) => {
+                    array.0.push(*code);
+                    array.signature("u");
+                    array.u32(*value);
+                }
+            }
+        }
+        message.u32(array.0.len() as u32 - 16);
+        message.0.extend_from_slice(&array.0[16..]);
+        message.align(8);
+        message.0.extend_from_slice(body);
+
+        stream.write_all(&message.0).unwrap();
+    }
+
+    fn read_message(stream: &mut UnixStream) -> Message {
+        let mut fixed = [0; 16];
+        stream.read_exact(&mut fixed).unwrap();
+        assert_eq!(fixed[0], b'l');
+        let mut reader = Reader {
+            buf: &fixed,
+            pos: 4,
+        };
+        let body_len = reader.u32() as usize;
+        let serial = reader.u32();
+        let fields_len = reader.u32() as usize;
+
+        let mut rest = vec![0; fields_len.div_ceil(8) * 8 + body_len];
+        stream.read_exact(&mut rest).unwrap();
+        let mut message = Message {
+            kind: fixed[1],
+            member: String::new(),
+            sender: String::new(),
+            serial,
+            reply_serial: 0,
+            body: rest[rest.len() - body_len..].to_vec(),
+        };
+
+        // the fields start at offset 16, so their alignment is the same within `rest`.
+        let mut reader = Reader {
+            buf: &rest[..fields_len],
+            pos: 0,
+        };
+        while reader.pos < fields_len {
+            reader.align(8);
+            let code = reader.buf[reader.pos];
+            reader.pos += 1;
+            match reader.signature().as_str() {
+                "u" => {
+                    let value = reader.u32();
+                    if code == 5 {
+                        message.reply_serial = value;
+                    }
+                }
+                "g" => {
+                    reader.signature();
+                }
+                _ => {
+                    let value = reader.string();
+                    match code {
+                        3 => message.member = value,
+                        7 => message.sender = value,
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        message
+    }
+
+    fn call_bus(stream: &mut UnixStream, serial: u32, member: &str, signature: &str, body: &[u8]) {
+        let mut fields = vec![
+            Field::Str(1, "o", "/org/freedesktop/DBus"),
+            Field::Str(2, "s", "org.freedesktop.DBus"),
+            Field::Str(3, "s", member),
+            Field::Str(6, "s", "org.freedesktop.DBus"),
+        ];
+        if !signature.is_empty() {
+            fields.push(Field::Str(8, "g", signature));
+        }
+        write_message(stream, 1, serial, &fields, body);
+    }
+
+    // owns the notifications name, answers the first Notify with 42 and hands its arguments over.
+    fn serve_notifications(socket: &Path) -> (Receiver<()>, Receiver<Notification>) {
+        let (ready_tx, ready) = mpsc::channel();
+        let (tx, received) = mpsc::channel();
+        let mut stream = UnixStream::connect(socket).unwrap();
+        stream.set_read_timeout(Some(TIMEOUT)).unwrap();
+
+        thread::spawn(move || {
+            let uid = fs::metadata("/proc/self").unwrap().uid().to_string();
+            write!(stream, "\0AUTH EXTERNAL {}\r\n", hex::encode(uid)).unwrap();
+            let mut line = String::new();
+            BufReader::new(stream.try_clone().unwrap())
+                .read_line(&mut line)
+                .unwrap();
+            assert!(line.starts_with("OK"), "{}", line);
+            write!(stream, "BEGIN\r\n").unwrap();
+
+            call_bus(&mut stream, 1, "Hello", "", &[]);
+            let mut body = Writer::default();
+            body.string(NOTIFICATIONS);
+            body.u32(0);
+            call_bus(&mut stream, 2, "RequestName", "su", &body.0);
+
+            loop {
+                let message = read_message(&mut stream);
+                if message.kind == 2 && message.reply_serial == 2 {
+                    ready_tx.send(()).unwrap();
+                }
+                if message.kind != 1 {
+                    continue;
+                }
+                // gdbus introspects first; without an answer it would wait for its timeout.
+                if message.member != "Notify" {
+                    write_message(
+                        &mut stream,
+                        3,
+                        3,
+                        &[
+                            Field::Str(4, "s", "org.freedesktop.DBus.Error.UnknownMethod"),
+                            Field::U32(5, message.serial),
+                            Field::Str(6, "s", &message.sender),
+                        ],
+                        &[],
+                    );
+                    continue;
+                }
+
+                let mut body = Reader {
+                    buf: &message.body,
+                    pos: 0,
+                };
+                let app_name = body.string();
+                let replaces_id = body.u32();
+                let _icon = body.string();
+                let summary = body.string();
+                let text = body.string();
+                body.skip_array(4);
+                body.skip_array(8);
+                let expire_timeout = body.u32() as i32;
+
+                let mut reply = Writer::default();
+                reply.u32(42);
+                write_message(
+                    &mut stream,
+                    2,
+                    4,
+                    &[
+                        Field::U32(5, message.serial),
+                        Field::Str(6, "s", &message.sender),
+                        Field::Str(8, "g", "u"),
+                    ],
+                    &reply.0,
+                );
+                tx.send(Notification {
+                    app_name,
+                    replaces_id,
+                    summary,
+                    body: text,
+                    expire_timeout,
+                })
+                .unwrap();
+                return;
+            }
+        });
+
+        (ready, received)
+    }
+
+    #[test]
+    fn test_gdbus_session_on_a_real_bus() {
+        let bus = match SessionBus::start("notify") {
+            Some(bus) => bus,
+            None => {
+                eprintln!("dbus-daemon is not installed, skipped.");
+                return;
+            }
+        };
+        // gdbus finds the session bus here; nothing else in this test binary uses it.
+        env::set_var("DBUS_SESSION_BUS_ADDRESS", bus.address());
+
+        let (ready, received) = serve_notifications(&bus.socket());
+        ready.recv_timeout(TIMEOUT).unwrap();
+
+        let notification = Notification {
+            app_name: "Pomodoro-rs".to_string(),
+            replaces_id: 0,
+            summary: "Pomodoro-rs".to_string(),
+            body: "it's WORK_1 \\ finish.".to_string(),
+            expire_timeout: 5000,
+        };
+        assert_eq!(GdbusSession {}.call_notify(&notification).unwrap(), 42);
+        assert_eq!(received.recv_timeout(TIMEOUT).unwrap(), notification);
+    }
+}
@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{prelude::*, Duration};
+    use pomorks_data_manage::data_manage_trait::TaskDealing;
+    use pomorks_data_manage::recovery::*;
+    use pomorks_data_manage::todo::*;
+
+    fn task_dealing_started_at(start_time: DateTime<Local>, state: State) -> TaskDealing {
+        TaskDealing {
+            id: Some("id".to_string()),
+            date: Some(start_time),
+            state: Some(state),
+            extended_seconds: 0,
+            paused_seconds: None,
+        }
+    }
+
+    #[test]
+    fn test_no_session_without_start_time() {
+        let task_dealing = TaskDealing {
+            id: Some("id".to_string()),
+            date: None,
+            state: Some(State::WORK(1)),
+            extended_seconds: 0,
+            paused_seconds: Some(60),
+        };
+
+        assert!(InFlightSession::from_task_dealing(&task_dealing, &Local::now()).is_none());
+    }
+
+    #[test]
+    fn test_running_session_is_resumed() {
+        let now = Local::now();
+        let limit = State::get_limit_time(&State::WORK(1)) as i64;
+        let task_dealing =
+            task_dealing_started_at(now - Duration::seconds(limit - 3), State::WORK(1));
+
+        let session = InFlightSession::from_task_dealing(&task_dealing, &now).unwrap();
+
+        assert!(!session.is_expired());
+        assert_eq!(session.remaining(), 3);
+        assert_eq!(session.default_action(0), RecoveryAction::Resume);
+        assert!(session
+            .available_actions()
+            .contains(&RecoveryAction::Resume));
+    }
+
+    #[test]
+    fn test_expired_session_within_grace_is_credited() {
+        let now = Local::now();
+        let limit = State::get_limit_time(&State::WORK(2)) as i64;
+        let task_dealing =
+            task_dealing_started_at(now - Duration::seconds(limit + 10), State::WORK(2));
+
+        let session = InFlightSession::from_task_dealing(&task_dealing, &now).unwrap();
+
+        assert!(session.is_expired());
+        assert_eq!(session.remaining(), 0);
+        assert_eq!(session.default_action(10), RecoveryAction::Credit);
+        assert_eq!(session.default_action(9), RecoveryAction::Discard);
+        assert_eq!(session.end_time(), now - Duration::seconds(10));
+        assert!(!session
+            .available_actions()
+            .contains(&RecoveryAction::Resume));
+    }
+
+    #[test]
+    fn test_extension_is_part_of_the_limit() {
+        let now = Local::now();
+        let limit = State::get_limit_time(&State::WORK(1)) as i64;
+        let task_dealing = TaskDealing {
+            extended_seconds: 300,
+            ..task_dealing_started_at(now - Duration::seconds(limit + 10), State::WORK(1))
+        };
+
+        let session = InFlightSession::from_task_dealing(&task_dealing, &now).unwrap();
+
+        assert!(!session.is_expired());
+        assert_eq!(session.remaining(), 290);
+        assert_eq!(session.default_action(0), RecoveryAction::Resume);
+    }
+
+    #[test]
+    fn test_grace_counts_from_the_extended_limit() {
+        let now = Local::now();
+        let limit = State::get_limit_time(&State::WORK(1)) as i64;
+        let extended = |elapsed: i64| TaskDealing {
+            extended_seconds: 300,
+            ..task_dealing_started_at(now - Duration::seconds(elapsed), State::WORK(1))
+        };
+
+        // past the plain limit by more than the grace, but within it of the extended one.
+        let session =
+            InFlightSession::from_task_dealing(&extended(limit + 300 + 30), &now).unwrap();
+        assert!(session.is_expired());
+        assert_eq!(session.default_action(60), RecoveryAction::Credit);
+        assert_eq!(session.end_time(), now - Duration::seconds(30));
+
+        let session =
+            InFlightSession::from_task_dealing(&extended(limit + 300 + 90), &now).unwrap();
+        assert_eq!(session.default_action(60), RecoveryAction::Discard);
+    }
+
+    #[test]
+    fn test_recovery_action_from_input() {
+        assert_eq!(
+            RecoveryAction::from_input("c\n"),
+            Some(RecoveryAction::Credit)
+        );
+        assert_eq!(
+            RecoveryAction::from_input("discard"),
+            Some(RecoveryAction::Discard)
+        );
+        assert_eq!(RecoveryAction::from_input("x"), None);
+    }
+}
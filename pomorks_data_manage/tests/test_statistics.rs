@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests {
+    use chrono::prelude::*;
+    use pomorks_data_manage::data_manage_trait::{SessionKind, TaskLogJson, UNASSIGNED_ID};
+    use pomorks_data_manage::statistics::SessionSummary;
+    use pomorks_data_manage::todo::*;
+
+    #[test]
+    fn test_read_log_without_kind() {
+        let task_log: TaskLogJson =
+            serde_json::from_str(r#"{"id":"abc","date":"2022/06/03 10:00:00+09:00"}"#).unwrap();
+
+        assert_eq!(task_log.kind, SessionKind::Work);
+        assert!(!task_log.skipped);
+        assert!(task_log.is_completed_work());
+    }
+
+    #[test]
+    fn test_session_summary() {
+        let now = Local::now();
+        let task_logs = [
+            TaskLogJson::new("a", &now, &State::WORK(1), 1500, false),
+            TaskLogJson::new("", &now, &State::BREAK(1), 300, false),
+            TaskLogJson::new("a", &now, &State::WORK(2), 1500, false),
+            TaskLogJson::new("", &now, &State::BREAK(2), 0, true),
+            TaskLogJson::new("", &now, &State::LUNCH(4), 1800, false),
+            TaskLogJson::new("b", &now, &State::WORK(3), 600, true),
+        ];
+
+        let summary = SessionSummary::from_logs(task_logs.iter());
+
+        assert_eq!(summary.work_count, 2);
+        assert_eq!(summary.break_count, 2);
+        assert_eq!(summary.skipped_work_count, 1);
+        assert_eq!(summary.skipped_break_count, 1);
+        assert_eq!(summary.work_seconds, 3600);
+        assert_eq!(summary.break_seconds, 2100);
+        assert_eq!(summary.break_ratio(), Some(2100.0 / 3600.0));
+        assert_eq!(
+            task_logs
+                .iter()
+                .filter(|log| log.is_completed_work())
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_overtime_of_log() {
+        let now = Local::now();
+        let limit = State::get_limit_time(&State::WORK(1)) as i64;
+
+        let in_time = TaskLogJson::new("a", &now, &State::WORK(1), limit - 1, false);
+        assert_eq!(in_time.overtime_seconds, 0);
+
+        let overtime = TaskLogJson::new("a", &now, &State::WORK(1), limit + 30, false);
+        assert_eq!(overtime.overtime_seconds, 30);
+
+        let extended = overtime.extended(20);
+        assert_eq!(extended.planned_seconds, limit + 20);
+        assert_eq!(extended.overtime_seconds, 10);
+    }
+
+    #[test]
+    fn test_unassigned_log() {
+        let now = Local::now();
+        let unassigned = TaskLogJson::new(UNASSIGNED_ID, &now, &State::WORK(1), 1500, false);
+        let skipped = TaskLogJson::new(UNASSIGNED_ID, &now, &State::WORK(1), 10, true);
+        let breaking = TaskLogJson::new(UNASSIGNED_ID, &now, &State::BREAK(1), 300, false);
+        let assigned = TaskLogJson::new("a", &now, &State::WORK(1), 1500, false);
+
+        assert!(unassigned.is_unassigned());
+        assert!(!skipped.is_unassigned());
+        assert!(!breaking.is_unassigned());
+        assert!(!assigned.is_unassigned());
+        assert_ne!(unassigned.log_id, assigned.log_id);
+    }
+
+    #[test]
+    fn test_break_ratio_without_work() {
+        assert_eq!(SessionSummary::default().break_ratio(), None);
+    }
+}
@@ -0,0 +1,134 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{prelude::*, Duration};
+    use pomorks_data_manage::status::*;
+    use pomorks_data_manage::todo::*;
+    use std::{env, fs};
+
+    fn snapshot_started_at(start_time: Option<DateTime<Local>>) -> StatusSnapshot {
+        StatusSnapshot {
+            state: State::WORK(2),
+            start_time,
+            extended_seconds: 0,
+            paused_seconds: None,
+            id: Some("id".to_string()),
+            title: "write".to_string(),
+            todays_count: 3,
+            written: Local::now(),
+        }
+    }
+
+    #[test]
+    fn test_status_line_of_stopped_phase() {
+        let now = Local::now();
+        let status_line = snapshot_started_at(None).to_status_line(&now, false);
+
+        assert_eq!(status_line.state, "WORK_2");
+        assert!(!status_line.running);
+        assert_eq!(
+            status_line.remaining_seconds,
+            State::get_limit_time(&State::WORK(2)) as i64
+        );
+        assert_eq!(status_line.todays_count, 3);
+    }
+
+    #[test]
+    fn test_status_line_of_paused_phase() {
+        let now = Local::now();
+        let limit = State::get_limit_time(&State::WORK(2)) as i64;
+        let snapshot = StatusSnapshot {
+            paused_seconds: Some(10),
+            ..snapshot_started_at(None)
+        };
+        let status_line = snapshot.to_status_line(&now, false);
+
+        assert!(!status_line.running);
+        assert_eq!(status_line.remaining_seconds, limit - 10);
+    }
+
+    #[test]
+    fn test_status_line_past_the_limit() {
+        let now = Local::now();
+        let limit = State::get_limit_time(&State::WORK(2)) as i64;
+        let snapshot = snapshot_started_at(Some(now - Duration::seconds(limit + 10)));
+
+        assert_eq!(snapshot.to_status_line(&now, false).remaining_seconds, 0);
+        assert_eq!(snapshot.to_status_line(&now, true).remaining_seconds, -10);
+
+        let extended = StatusSnapshot {
+            extended_seconds: 30,
+            ..snapshot
+        };
+        assert_eq!(extended.to_status_line(&now, false).remaining_seconds, 20);
+    }
+
+    #[test]
+    fn test_stale_cache() {
+        let now = Local::now();
+        let limit = State::get_limit_time(&State::WORK(2)) as i64;
+        let written_at = |start_time, written| StatusSnapshot {
+            written,
+            ..snapshot_started_at(start_time)
+        };
+
+        assert!(!written_at(None, now).is_stale(&now));
+        assert!(
+            written_at(None, now - Duration::seconds(STATUS_CACHE_MAX_AGE_SECONDS)).is_stale(&now)
+        );
+        assert!(written_at(None, now + Duration::seconds(5)).is_stale(&now));
+
+        // the phase ended after the cache was written, so whoever ran it has moved on.
+        let start = now - Duration::seconds(limit + 1);
+        assert!(written_at(Some(start), now - Duration::seconds(2)).is_stale(&now));
+        assert!(!written_at(Some(start), now).is_stale(&now));
+    }
+
+    #[test]
+    fn test_write_cache_leaves_no_temp_file() {
+        let dir = env::temp_dir().join(format!("pomorks-status-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        env::set_current_dir(&dir).unwrap();
+
+        let snapshot = snapshot_started_at(None);
+        snapshot.write_cache().unwrap();
+        snapshot.write_cache().unwrap();
+        assert_eq!(StatusSnapshot::read_cache().unwrap(), Some(snapshot));
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_count_of_another_day_is_reset() {
+        let now = Local::now();
+        let snapshot = StatusSnapshot {
+            written: now - Duration::days(1),
+            ..snapshot_started_at(None)
+        };
+
+        assert_eq!(snapshot.to_status_line(&now, false).todays_count, 0);
+    }
+
+    #[test]
+    fn test_format_status_line() {
+        let status_line = StatusLine {
+            state: "WORK_2".to_string(),
+            running: true,
+            remaining_seconds: 754,
+            title: "write".to_string(),
+            todays_count: 3,
+        };
+
+        assert_eq!(
+            status_line.format(DEFAULT_STATUS_FORMAT),
+            "WORK_2 12:34 write (3)"
+        );
+        assert_eq!(status_line.format("{remaining}s {running}"), "754s true");
+
+        let overtime = StatusLine {
+            remaining_seconds: -65,
+            ..status_line
+        };
+        assert_eq!(overtime.format("{time}"), "-01:05");
+    }
+}
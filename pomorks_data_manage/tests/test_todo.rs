@@ -33,17 +33,28 @@ mod tests {
         assert_eq!(list.get_vec_of_todo().len(), 1);
     }
 
+    #[test]
+    fn test_get_todo() {
+        let mut list = TodoList::new();
+
+        let todo = TodoItem::from_str("test test test 1").unwrap();
+        list.add_todo(todo.clone()).unwrap();
+
+        assert_eq!(list.get_todo(&todo.id).unwrap().title, "test");
+        assert!(list.get_todo("not_exist").is_none());
+    }
+
     #[test]
     fn test_delete_todo() {
         let mut list = TodoList::new();
 
-        let todo = TodoItem::from_str(&"test test test 1".to_string()).unwrap();
+        let todo = TodoItem::from_str("test test test 1").unwrap();
         list.add_todo(todo.clone()).unwrap();
         list.delete_todo(&todo).unwrap();
 
         assert_eq!(list.get_vec_of_todo().len(), 0);
 
-        let todo2 = TodoItem::from_str(&"test test test 1".to_string()).unwrap();
+        let todo2 = TodoItem::from_str("test test test 1").unwrap();
         list.add_todo(todo2).unwrap();
         let result = list.delete_todo(&todo.clone()); // no exist.
 
@@ -54,7 +65,7 @@ mod tests {
     fn test_insert_todo() {
         let mut list = TodoList::new();
 
-        let mut todo = TodoItem::from_str(&"test test test 1".to_string()).unwrap();
+        let mut todo = TodoItem::from_str("test test test 1").unwrap();
         list.add_todo(todo.clone()).unwrap();
 
         todo.estimate_count += 1;
@@ -64,6 +75,26 @@ mod tests {
         assert_eq!(list.get_vec_of_todo()[0].estimate_count, 2);
     }
 
+    #[test]
+    fn test_change_executed_count() {
+        let mut list = TodoList::new();
+
+        let todo = TodoItem::from_str("test test test 1").unwrap();
+        list.add_todo(todo.clone()).unwrap();
+
+        list.increment_executed_count(&todo.id);
+        list.increment_executed_count(&todo.id);
+        list.decrement_executed_count(&todo.id);
+        assert_eq!(list.get_vec_of_todo()[0].executed_count, 1);
+
+        list.decrement_executed_count(&todo.id);
+        list.decrement_executed_count(&todo.id); // never below zero.
+        assert_eq!(list.get_vec_of_todo()[0].executed_count, 0);
+
+        list.increment_executed_count("not exist");
+        assert_eq!(list.get_vec_of_todo()[0].executed_count, 0);
+    }
+
     #[test]
     fn test_drain_finished_todo() {
         let mut list = TodoList::new();
@@ -79,6 +110,7 @@ mod tests {
                 executed_count: 0,
                 detail: "none".to_string(),
                 finished: *finish,
+                status: TodoStatus::Backlog,
             })
             .unwrap();
         });
@@ -88,4 +120,76 @@ mod tests {
         assert_eq!(list_drained.len(), 4);
         assert_eq!(list.get_vec_of_todo().len(), 3);
     }
+
+    #[test]
+    fn test_board_column() {
+        let mut todo = TodoItem::from_str("test test test 1").unwrap();
+        assert_eq!(todo.column(), TodoStatus::Backlog);
+
+        todo.move_to(TodoStatus::Doing);
+        assert_eq!(todo.column(), TodoStatus::Doing);
+        todo.finished = true;
+        assert_eq!(todo.column(), TodoStatus::Done);
+        todo.finished = false;
+        assert_eq!(todo.column(), TodoStatus::Doing);
+
+        todo.move_to(TodoStatus::Done);
+        assert!(todo.finished);
+        todo.finished = false;
+        assert_eq!(todo.column(), TodoStatus::Backlog);
+
+        // saved before the board existed.
+        let todo: TodoItem = serde_json::from_str(
+            r#"{"Id":"a","Title":"t","Tag":"g","Project":"p","EstimateCount":1,"ExecutedCount":0,"Finished":true,"Detail":""}"#,
+        )
+        .unwrap();
+        assert_eq!(todo.status, TodoStatus::Backlog);
+        assert_eq!(todo.column(), TodoStatus::Done);
+    }
+
+    #[test]
+    fn test_set_finished() {
+        let mut todo = TodoItem::from_str("test test test 1").unwrap();
+        todo.move_to(TodoStatus::Doing);
+
+        todo.set_finished(true);
+        assert!(todo.finished);
+        assert_eq!(todo.status, TodoStatus::Done);
+
+        // finishing again keeps it where it is.
+        todo.set_finished(true);
+        assert_eq!(todo.status, TodoStatus::Done);
+
+        todo.set_finished(false);
+        assert!(!todo.finished);
+        assert_eq!(todo.status, TodoStatus::Backlog);
+        assert_eq!(todo.column(), TodoStatus::Backlog);
+    }
+
+    #[test]
+    fn test_move_focus() {
+        let first = TodoItem::from_str("first test test 1").unwrap();
+        let second = TodoItem::from_str("second test test 1").unwrap();
+        let mut finished = TodoItem::from_str("finished test test 1").unwrap();
+        finished.move_to(TodoStatus::Done);
+        let mut list = TodoList::new();
+        for todo in [&first, &second, &finished] {
+            list.add_todo(todo.clone()).unwrap();
+        }
+        let column = |list: &TodoList, todo: &TodoItem| list.get_todo(&todo.id).unwrap().column();
+
+        assert!(list.move_focus(None, Some(&first.id)));
+        assert_eq!(column(&list, &first), TodoStatus::Doing);
+        assert!(!list.move_focus(Some(&first.id), Some(&first.id)));
+
+        assert!(list.move_focus(Some(&first.id), Some(&second.id)));
+        assert_eq!(column(&list, &first), TodoStatus::Today);
+        assert_eq!(column(&list, &second), TodoStatus::Doing);
+
+        // a finished todo stays done either way.
+        assert!(list.move_focus(Some(&second.id), Some(&finished.id)));
+        assert_eq!(column(&list, &finished), TodoStatus::Done);
+        assert!(!list.move_focus(Some(&finished.id), None));
+        assert_eq!(column(&list, &finished), TodoStatus::Done);
+    }
 }
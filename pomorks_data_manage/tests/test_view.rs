@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{prelude::*, Duration};
+    use pomorks_data_manage::data_manage_trait::TaskLogJson;
+    use pomorks_data_manage::todo::*;
+    use pomorks_data_manage::view::*;
+    use std::str::FromStr;
+
+    fn todo(line: &str, executed_count: usize, finished: bool) -> TodoItem {
+        TodoItem {
+            executed_count,
+            finished,
+            ..TodoItem::from_str(line).unwrap()
+        }
+    }
+
+    fn titles(todos: &[TodoItem]) -> Vec<&str> {
+        todos.iter().map(|todo| todo.title.as_str()).collect()
+    }
+
+    fn todos() -> Vec<TodoItem> {
+        vec![
+            todo("review code pomorks 2", 3, false),
+            todo("write doc pomorks 4", 1, false),
+            todo("plan meeting team 1", 0, false),
+            todo("fix code team 2", 2, true),
+        ]
+    }
+
+    #[test]
+    fn test_filter() {
+        let mut view = ListView {
+            search: "CODE".to_string(),
+            ..ListView::default()
+        };
+        assert_eq!(titles(&view.apply(todos(), &[])), vec!["review", "fix"]);
+
+        view.hide_finished = true;
+        assert_eq!(titles(&view.apply(todos(), &[])), vec!["review"]);
+        assert_eq!(view.describe(), "\"CODE\" unfinished by priority");
+
+        view.clear_filter();
+        view.project = Some("team".to_string());
+        assert_eq!(titles(&view.apply(todos(), &[])), vec!["plan", "fix"]);
+
+        let view = ListView {
+            over_estimate: true,
+            ..ListView::default()
+        };
+        assert_eq!(titles(&view.apply(todos(), &[])), vec!["review"]);
+        assert!(view.is_filtered());
+        assert!(!ListView::default().is_filtered());
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut view = ListView::default();
+        assert_eq!(
+            titles(&view.apply(todos(), &[])),
+            vec!["review", "write", "plan", "fix"]
+        );
+
+        view.sort = SortMode::Remaining;
+        assert_eq!(
+            titles(&view.apply(todos(), &[])),
+            vec!["write", "plan", "fix", "review"]
+        );
+
+        view.sort = SortMode::Project;
+        assert_eq!(
+            titles(&view.apply(todos(), &[])),
+            vec!["review", "write", "fix", "plan"]
+        );
+
+        let todos = todos();
+        let now = Local::now();
+        let task_logs = [
+            TaskLogJson::new(
+                &todos[0].id,
+                &(now - Duration::days(1)),
+                &State::WORK(1),
+                1500,
+                false,
+            ),
+            TaskLogJson::new(&todos[3].id, &now, &State::WORK(1), 1500, false),
+        ];
+        view.sort = view.sort.next();
+        assert_eq!(view.sort, SortMode::Recent);
+        assert_eq!(
+            titles(&view.apply(todos, &task_logs)),
+            vec!["fix", "review", "plan", "write"]
+        );
+        assert_eq!(view.sort.next(), SortMode::Priority);
+    }
+
+    #[test]
+    fn test_group() {
+        let mut view = ListView {
+            group: GroupBy::Project,
+            ..ListView::default()
+        };
+        let groups = view.group(view.apply(todos(), &[]));
+        let names: Vec<&str> = groups.iter().map(|group| group.name.as_str()).collect();
+        assert_eq!(names, vec!["pomorks", "team"]);
+        assert_eq!(titles(&groups[0].todos), vec!["review", "write"]);
+        assert_eq!(groups[0].estimate_count(), 6);
+        assert_eq!(groups[0].executed_count(), 4);
+        assert_eq!(groups[1].unfinished_count(), 1);
+        assert_eq!(view.describe(), "by priority per project");
+
+        view.group = view.group.next();
+        assert_eq!(view.group(todos()).len(), 3);
+        assert_eq!(view.group.next(), GroupBy::None);
+    }
+
+    #[test]
+    fn test_read_partial_view() {
+        let view: ListView = serde_json::from_str(r#"{"sort":"recent"}"#).unwrap();
+        assert_eq!(view.sort, SortMode::Recent);
+        assert!(!view.is_filtered());
+    }
+}
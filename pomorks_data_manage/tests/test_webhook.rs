@@ -0,0 +1,251 @@
+#[cfg(test)]
+mod tests {
+    use chrono::prelude::*;
+    use pomorks_data_manage::config::WebhookConfig;
+    use pomorks_data_manage::data_manage_trait::TaskLogJson;
+    use pomorks_data_manage::todo::*;
+    use pomorks_data_manage::webhook::*;
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::path::PathBuf;
+    use std::str::FromStr;
+    use std::sync::mpsc::{self, Receiver};
+    use std::time::Duration;
+    use std::{env, fs, thread};
+
+    struct Received {
+        headers: HashMap<String, String>,
+        body: String,
+    }
+
+    // answers one request per status with that status and hands the request over.
+    fn receiver(statuses: Vec<u16>) -> (String, Receiver<Received>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            for status in statuses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut headers = HashMap::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(": ") {
+                        headers.insert(name.to_lowercase(), value.to_string());
+                    }
+                }
+                let length = headers["content-length"].parse().unwrap();
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+
+                let mut stream = stream;
+                write!(
+                    stream,
+                    "HTTP/1.1 {} STATUS\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                )
+                .unwrap();
+                let _ = tx.send(Received {
+                    headers,
+                    body: String::from_utf8(body).unwrap(),
+                });
+            }
+        });
+
+        (url, rx)
+    }
+
+    // removes the queue even when an assertion fails.
+    struct DataDir(PathBuf);
+
+    impl DataDir {
+        fn new(name: &str) -> Self {
+            let dir =
+                env::temp_dir().join(format!("pomorks-webhook-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            DataDir(dir)
+        }
+    }
+
+    impl Drop for DataDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn payload() -> WebhookPayload {
+        let todo = TodoItem::from_str("write doc pomorks 2").unwrap();
+        let log = TaskLogJson::new(&todo.id, &Local::now(), &State::WORK(1), 1490, false);
+        WebhookPayload::from_session(&log, &State::WORK(1), Some(&todo))
+    }
+
+    #[test]
+    fn test_sign() {
+        assert_eq!(
+            sign("key", "The quick brown fox jumps over the lazy dog"),
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[test]
+    fn test_payload_of_session() {
+        let payload = payload();
+        assert_eq!(payload.event, "session_completed");
+        assert_eq!(payload.todo.unwrap().title, "write");
+        assert_eq!(payload.project, "pomorks");
+        assert_eq!(payload.tags, vec!["doc".to_string()]);
+        assert_eq!(payload.state, "WORK_1");
+        assert_eq!(payload.duration_seconds, 1490);
+
+        let log = TaskLogJson::new("", &Local::now(), &State::BREAK(1), 300, true);
+        let payload = WebhookPayload::from_session(&log, &State::BREAK(1), None);
+        assert!(payload.todo.is_none());
+        assert!(payload.tags.is_empty());
+        assert!(payload.skipped);
+    }
+
+    #[test]
+    fn test_deliver_signed_post() {
+        let dir = DataDir::new("deliver");
+        let (url, received) = receiver(vec![200]);
+        let notifier = WebhookNotifier::new(WebhookConfig {
+            urls: vec![url],
+            secret: "secret".to_string(),
+            ..WebhookConfig::default()
+        })
+        .in_dir(&dir.0);
+
+        let payload = payload();
+        notifier.deliver(&payload).unwrap();
+
+        let request = received.recv().unwrap();
+        assert_eq!(
+            serde_json::from_str::<WebhookPayload>(&request.body).unwrap(),
+            payload
+        );
+        assert_eq!(
+            request.headers[&SIGNATURE_HEADER.to_lowercase()],
+            sign("secret", &request.body)
+        );
+        assert!(notifier.read_queue().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_failed_delivery_is_queued_and_retried() {
+        let dir = DataDir::new("retry");
+        let (url, received) = receiver(vec![500, 200]);
+        let notifier = WebhookNotifier::new(WebhookConfig {
+            urls: vec![url],
+            retry_seconds: 0,
+            ..WebhookConfig::default()
+        })
+        .in_dir(&dir.0);
+
+        assert!(notifier.deliver(&payload()).is_err());
+        let queue = notifier.read_queue().unwrap();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].attempts, 1);
+        assert!(dir.0.join(WEBHOOK_QUEUE_FILE).exists());
+
+        // a new notifier picks the queue up from the file.
+        let notifier = WebhookNotifier::new(WebhookConfig {
+            urls: vec![queue[0].url.clone()],
+            retry_seconds: 0,
+            ..WebhookConfig::default()
+        })
+        .in_dir(&dir.0);
+        notifier.retry_pending().unwrap();
+        assert!(notifier.read_queue().unwrap().is_empty());
+        assert!(!received
+            .recv()
+            .unwrap()
+            .headers
+            .contains_key("x-pomorks-signature"));
+        assert_eq!(received.recv().unwrap().body, queue[0].body);
+    }
+
+    #[test]
+    fn test_tick_retries_once_due() {
+        let dir = DataDir::new("tick");
+        let (url, received) = receiver(vec![500, 200]);
+        let notifier = WebhookNotifier::new(WebhookConfig {
+            urls: vec![url],
+            retry_seconds: 0,
+            ..WebhookConfig::default()
+        })
+        .in_dir(&dir.0);
+
+        assert!(notifier.deliver(&payload()).is_err());
+        let due = notifier.next_retry().unwrap();
+        notifier.retry_if_due(Local.timestamp(due - 1, 0));
+        assert!(notifier.next_retry().is_some());
+
+        notifier.retry_if_due(Local.timestamp(due, 0));
+        received.recv().unwrap();
+        received.recv().unwrap();
+        for _ in 0..100 {
+            if notifier.read_queue().unwrap().is_empty() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(notifier.read_queue().unwrap().is_empty());
+        assert_eq!(notifier.next_retry(), None);
+    }
+
+    #[test]
+    fn test_retry_delay_is_capped() {
+        assert_eq!(retry_delay(30, 1), 30);
+        assert_eq!(retry_delay(30, 3), 120);
+        assert_eq!(retry_delay(30, 100), MAX_RETRY_SECONDS);
+        assert_eq!(retry_delay(i64::MAX, 2), MAX_RETRY_SECONDS);
+    }
+
+    #[test]
+    fn test_delivery_is_dropped_after_max_attempts() {
+        let dir = DataDir::new("drop");
+        let (url, _received) = receiver(vec![500, 500]);
+        let notifier = WebhookNotifier::new(WebhookConfig {
+            urls: vec![url],
+            retry_seconds: 0,
+            max_attempts: 2,
+            ..WebhookConfig::default()
+        })
+        .in_dir(&dir.0);
+
+        assert!(notifier.deliver(&payload()).is_err());
+        let error = notifier.retry_pending().unwrap_err();
+        assert!(error.to_string().contains("giving up"));
+        assert!(notifier.read_queue().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_dry_run_sends_nothing() {
+        let dir = DataDir::new("dry-run");
+        let notifier = WebhookNotifier::new(WebhookConfig {
+            // nothing listens here; a real request would fail.
+            urls: vec!["http://127.0.0.1:9/hook".to_string()],
+            secret: "secret".to_string(),
+            dry_run: true,
+            ..WebhookConfig::default()
+        })
+        .in_dir(&dir.0);
+
+        notifier.deliver(&payload()).unwrap();
+
+        let dry_run_log = fs::read_to_string(dir.0.join(WEBHOOK_DRY_RUN_FILE)).unwrap();
+        let entry: serde_json::Value = serde_json::from_str(dry_run_log.trim_end()).unwrap();
+        assert_eq!(entry["url"], "http://127.0.0.1:9/hook");
+        let body = entry["body"].as_str().unwrap();
+        assert_eq!(entry["signature"], sign("secret", body));
+        assert!(notifier.read_queue().unwrap().is_empty());
+    }
+}
@@ -3,22 +3,101 @@
     windows_subsystem = "windows"
 )]
 
-fn main() {
+mod timer;
+mod todos;
+
+use anyhow::{anyhow, Result};
+use pomorks_daemon::daemon::Daemon;
+use pomorks_daemon::remote::{default_socket_path, Remote};
+use pomorks_data_manage::config::Config;
+use pomorks_data_manage::data_manage_firebase::DataManageFirebase;
+use pomorks_data_manage::data_manage_json::DataManageJson;
+use pomorks_data_manage::data_manage_trait::{DataManage, TypeDataManager};
+use pomorks_data_manage::hooks::HookRunner;
+use pomorks_data_manage::notifications;
+use pomorks_data_manage::webhook::WebhookNotifier;
+use std::env;
+use std::sync::Mutex;
+use timer::{Timer, TimerBackend};
+use todos::Store;
+
+fn main() -> Result<()> {
+    let backend = parse_backend(env::args().skip(1))?;
+    let config = Config::load()?;
+    get_data_manager(backend).migrate()?;
+
+    let (timer_backend, events) = match Remote::connect(&default_socket_path()) {
+        Some(remote) => {
+            let events = remote.events()?;
+            (TimerBackend::Remote(remote), events)
+        }
+        None => {
+            let mut daemon = Daemon::new(
+                get_data_manager(backend),
+                notifications::get_notifier(&config.notifier),
+                HookRunner::new(config.hooks.clone()),
+                WebhookNotifier::new(config.webhook),
+                config.timer.clone(),
+                config.recovery,
+            )?;
+            let events = daemon.subscribe();
+            (TimerBackend::Local(daemon), events)
+        }
+    };
+    let timer = Timer::new(timer_backend, config.timer);
+    let store = Store {
+        data_manager: Mutex::new(get_data_manager(backend)),
+        hooks: HookRunner::new(config.hooks),
+    };
+
     tauri::Builder::default()
+        .manage(timer.clone())
+        .manage(store)
+        .setup(move |app| {
+            timer.run(app.handle(), events);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
-            simple_command,
-            command_with_message
+            timer::timer_status,
+            timer::start_timer,
+            timer::pause_timer,
+            timer::skip_phase,
+            timer::focus_todo,
+            todos::list_todos,
+            todos::add_todo,
+            todos::update_todo,
+            todos::finish_todo,
+            todos::delete_todo,
+            todos::archive_todos,
+            todos::stats
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .run(tauri::generate_context!())?;
+
+    Ok(())
 }
 
-#[tauri::command]
-fn simple_command() {
-    println!("I was Invoked from JS!");
+// `--backend <json|firebase>`, as pomorksd takes it.
+fn parse_backend(mut args: impl Iterator<Item = String>) -> Result<TypeDataManager> {
+    let mut backend = TypeDataManager::DataManageJson;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--backend" => {
+                let name = args
+                    .next()
+                    .ok_or_else(|| anyhow!("{} needs a value.", arg))?;
+                backend = TypeDataManager::from_name_ignore_case(&name)
+                    .ok_or_else(|| anyhow!("unknown backend: {}", name))?;
+            }
+            _ => return Err(anyhow!("unexpected argument: {}", arg)),
+        }
+    }
+
+    Ok(backend)
 }
 
-#[tauri::command]
-fn command_with_message(message: String) -> String {
-    format!("hello {}", message)
+fn get_data_manager(backend: TypeDataManager) -> Box<dyn DataManage + Send> {
+    match backend {
+        TypeDataManager::DataManageJson => Box::new(DataManageJson {}),
+        TypeDataManager::DataManageFirebase => Box::new(DataManageFirebase {}),
+    }
 }
@@ -0,0 +1,162 @@
+use chrono::prelude::*;
+use pomorks_daemon::daemon::Daemon;
+use pomorks_daemon::protocol::{DaemonStatus, Event, Request, Response};
+use pomorks_daemon::remote::Remote;
+use pomorks_data_manage::config::TimerConfig;
+use pomorks_data_manage::todo::State;
+use serde::Serialize;
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+const TICK_RATE: Duration = Duration::from_secs(1);
+
+/// Emitted every second with the `TimerStatus`.
+pub const TICK_EVENT: &str = "timer-tick";
+/// Emitted on phase changes with the daemon's `Event`, e.g. `phase_finished`.
+pub const PHASE_EVENT: &str = "timer-event";
+
+#[derive(Clone)]
+pub struct Timer {
+    backend: Arc<Mutex<TimerBackend>>,
+    timer_config: TimerConfig,
+}
+
+// without `pomorksd` the GUI runs the same timer itself, so sessions are logged alike.
+pub enum TimerBackend {
+    Local(Daemon),
+    Remote(Remote),
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TimerStatus {
+    /// "WORK", "BREAK" or "LUNCH".
+    pub kind: String,
+    /// e.g. "WORK_2", as the TUI shows it.
+    pub name: String,
+    pub running: bool,
+    pub paused: bool,
+    pub remaining_seconds: i64,
+    pub limit_seconds: i64,
+    pub focus: Option<String>,
+    pub title: String,
+}
+
+impl TimerStatus {
+    pub fn of(status: DaemonStatus, timer_config: &TimerConfig, now: DateTime<Local>) -> Self {
+        let engine = status.engine(timer_config, now);
+        TimerStatus {
+            kind: match engine.state() {
+                State::WORK(_) => "WORK",
+                State::BREAK(_) => "BREAK",
+                State::LUNCH(_) => "LUNCH",
+            }
+            .to_string(),
+            name: status.state,
+            running: status.running,
+            paused: status.paused,
+            remaining_seconds: engine.remaining_seconds(now),
+            limit_seconds: engine.limit_seconds(),
+            focus: status.focus,
+            title: status.title,
+        }
+    }
+}
+
+impl TimerBackend {
+    pub fn request(&mut self, request: Request) -> Result<DaemonStatus, String> {
+        match self {
+            TimerBackend::Local(daemon) => match daemon.handle(request) {
+                Response::Status(status) => Ok(status),
+                Response::Error { message } => Err(message),
+                Response::Event(event) => Err(format!("unexpected event: {:?}", event)),
+            },
+            TimerBackend::Remote(remote) => remote.request(&request).map_err(|e| e.to_string()),
+        }
+    }
+
+    pub fn state(&mut self) -> Result<State, String> {
+        let status = self.request(Request::Status)?;
+        Ok(status.phase.state.unwrap_or(State::WORK(1)))
+    }
+
+    // `pomorksd` ends its phases itself.
+    fn tick(&mut self) -> Result<DaemonStatus, String> {
+        if let TimerBackend::Local(daemon) = self {
+            if let Err(e) = daemon.tick() {
+                eprintln!("{}", e);
+            }
+        }
+        self.request(Request::Status)
+    }
+}
+
+impl Timer {
+    pub fn new(backend: TimerBackend, timer_config: TimerConfig) -> Self {
+        Timer {
+            backend: Arc::new(Mutex::new(backend)),
+            timer_config,
+        }
+    }
+
+    pub fn lock(&self) -> Result<MutexGuard<'_, TimerBackend>, String> {
+        self.backend.lock().map_err(|e| e.to_string())
+    }
+
+    fn request(&self, request: Request) -> Result<TimerStatus, String> {
+        let status = self.lock()?.request(request)?;
+        Ok(TimerStatus::of(status, &self.timer_config, Local::now()))
+    }
+
+    /// Ends phases in the background and reports the time left to the frontend.
+    pub fn run(&self, app: AppHandle, events: Receiver<Event>) {
+        let timer = self.clone();
+        let tick_app = app.clone();
+        thread::spawn(move || loop {
+            let status = timer.lock().and_then(|mut backend| backend.tick());
+            if let Ok(status) = status {
+                let status = TimerStatus::of(status, &timer.timer_config, Local::now());
+                let _ = tick_app.emit_all(TICK_EVENT, status);
+            }
+            thread::sleep(TICK_RATE);
+        });
+
+        thread::spawn(move || {
+            for event in events {
+                let _ = app.emit_all(PHASE_EVENT, event);
+            }
+        });
+    }
+}
+
+#[tauri::command]
+pub fn timer_status(timer: tauri::State<'_, Timer>) -> Result<TimerStatus, String> {
+    timer.request(Request::Status)
+}
+
+#[tauri::command]
+pub fn start_timer(timer: tauri::State<'_, Timer>) -> Result<TimerStatus, String> {
+    timer.request(Request::Start)
+}
+
+#[tauri::command]
+pub fn pause_timer(timer: tauri::State<'_, Timer>) -> Result<TimerStatus, String> {
+    timer.request(Request::Pause)
+}
+
+#[tauri::command]
+pub fn skip_phase(timer: tauri::State<'_, Timer>) -> Result<TimerStatus, String> {
+    timer.request(Request::Skip)
+}
+
+/// Work on the todo `id`, or on nothing when `id` is null.
+#[tauri::command]
+pub fn focus_todo(
+    timer: tauri::State<'_, Timer>,
+    id: Option<String>,
+) -> Result<TimerStatus, String> {
+    timer.request(Request::Focus { id })
+}
@@ -0,0 +1,244 @@
+use crate::timer::Timer;
+use chrono::{prelude::*, Duration};
+use pomorks_daemon::protocol::Request;
+use pomorks_data_manage::data_manage_trait::DataManage;
+use pomorks_data_manage::hooks::{HookEvent, HookPayload, HookRunner};
+use pomorks_data_manage::statistics::SessionSummary;
+use pomorks_data_manage::todo::{generate_id, State, TodoItem, TodoList};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+pub struct Store {
+    pub data_manager: Mutex<Box<dyn DataManage + Send>>,
+    pub hooks: HookRunner,
+}
+
+/// `TodoItem` as the frontend spells it.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Todo {
+    pub id: String,
+    pub title: String,
+    pub tag: String,
+    pub project: String,
+    pub estimate_count: usize,
+    pub executed_count: usize,
+    pub finished: bool,
+    pub detail: String,
+}
+
+impl From<TodoItem> for Todo {
+    fn from(todo: TodoItem) -> Self {
+        Todo {
+            id: todo.id,
+            title: todo.title,
+            tag: todo.tag,
+            project: todo.project,
+            estimate_count: todo.estimate_count,
+            executed_count: todo.executed_count,
+            finished: todo.finished,
+            detail: todo.detail,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    pub work_count: usize,
+    pub break_count: usize,
+    pub work_seconds: i64,
+    pub break_seconds: i64,
+    pub skipped_work_count: usize,
+    pub skipped_break_count: usize,
+    pub break_ratio: Option<f64>,
+}
+
+impl Store {
+    // the timer is locked throughout, so a pomodoro the GUI finishes can't overwrite the change.
+    fn update<T, F>(&self, timer: &Timer, f: F) -> Result<T, String>
+    where
+        F: FnOnce(
+            &mut TodoList,
+            &State,
+            &dyn DataManage,
+        ) -> anyhow::Result<(T, Option<HookPayload>)>,
+    {
+        let mut backend = timer.lock()?;
+        let state = backend.state()?;
+        let data_manager = self.data_manager.lock().map_err(|e| e.to_string())?;
+        let mut todo_list = read_todo_list(data_manager.as_ref())?;
+
+        let (result, payload) =
+            f(&mut todo_list, &state, data_manager.as_ref()).map_err(|e| e.to_string())?;
+        data_manager
+            .write_all_todo(todo_list)
+            .map_err(|e| e.to_string())?;
+        if let Some(payload) = payload {
+            self.hooks.fire(payload);
+        }
+
+        Ok(result)
+    }
+}
+
+fn read_todo_list(data_manager: &dyn DataManage) -> Result<TodoList, String> {
+    data_manager
+        .read_all_todo()
+        .map(Option::unwrap_or_default)
+        .map_err(|e| e.to_string())
+}
+
+fn get_todo(todo_list: &TodoList, id: &str) -> anyhow::Result<TodoItem> {
+    todo_list
+        .get_todo(id)
+        .ok_or_else(|| anyhow::anyhow!("todo {} is not exist.", id))
+}
+
+#[tauri::command]
+pub fn list_todos(store: tauri::State<'_, Store>) -> Result<Vec<Todo>, String> {
+    let data_manager = store.data_manager.lock().map_err(|e| e.to_string())?;
+    let mut todos = read_todo_list(data_manager.as_ref())?.get_vec_of_todo();
+    todos.sort_by(|a, b| a.title.cmp(&b.title));
+
+    Ok(todos.into_iter().map(Todo::from).collect())
+}
+
+#[tauri::command]
+pub fn add_todo(
+    store: tauri::State<'_, Store>,
+    timer: tauri::State<'_, Timer>,
+    title: String,
+    tag: String,
+    project: String,
+    estimate_count: usize,
+) -> Result<Todo, String> {
+    store.update(&timer, |todo_list, _, _| {
+        if title.trim().is_empty() {
+            return Err(anyhow::anyhow!("title is empty."));
+        }
+        let todo = TodoItem::new(
+            generate_id(),
+            title,
+            tag,
+            project,
+            estimate_count,
+            0,
+            String::new(),
+        );
+        todo_list.add_todo(todo.clone())?;
+
+        Ok((todo.into(), None))
+    })
+}
+
+/// Edits the fields of a todo; the executed count stays with the timer.
+#[tauri::command]
+pub fn update_todo(
+    store: tauri::State<'_, Store>,
+    timer: tauri::State<'_, Timer>,
+    todo: Todo,
+) -> Result<Todo, String> {
+    store.update(&timer, |todo_list, _, _| {
+        let mut updated = TodoItem {
+            title: todo.title,
+            tag: todo.tag,
+            project: todo.project,
+            estimate_count: todo.estimate_count,
+            detail: todo.detail,
+            ..get_todo(todo_list, &todo.id)?
+        };
+        updated.set_finished(todo.finished);
+        let todo = updated;
+        todo_list.insert_todo(todo.clone())?;
+
+        Ok((todo.into(), None))
+    })
+}
+
+/// Toggles the finished flag, as `f` does in the TUI.
+#[tauri::command]
+pub fn finish_todo(
+    store: tauri::State<'_, Store>,
+    timer: tauri::State<'_, Timer>,
+    id: String,
+) -> Result<Todo, String> {
+    store.update(&timer, |todo_list, state, _| {
+        let mut todo = get_todo(todo_list, &id)?;
+        todo.set_finished(!todo.finished);
+        todo_list.insert_todo(todo.clone())?;
+
+        let payload = todo
+            .finished
+            .then(|| HookPayload::new(HookEvent::TodoFinished, state, vec![todo.clone()]));
+        Ok((todo.into(), payload))
+    })
+}
+
+/// Deletes a todo as the TUI does; its logs go back to unassigned.
+#[tauri::command]
+pub fn delete_todo(
+    store: tauri::State<'_, Store>,
+    timer: tauri::State<'_, Timer>,
+    id: String,
+) -> Result<(), String> {
+    store.update(&timer, |todo_list, _, data_manager| {
+        let todo = get_todo(todo_list, &id)?;
+        data_manager.delete_todo(todo_list, &todo)?;
+
+        Ok(((), None))
+    })?;
+
+    // the running phase is no longer credited to it.
+    let mut backend = timer.lock()?;
+    if backend.request(Request::Status)?.focus.as_deref() == Some(id.as_str()) {
+        backend.request(Request::Focus { id: None })?;
+    }
+
+    Ok(())
+}
+
+/// Moves the finished todos to the archive and returns them.
+#[tauri::command]
+pub fn archive_todos(
+    store: tauri::State<'_, Store>,
+    timer: tauri::State<'_, Timer>,
+) -> Result<Vec<Todo>, String> {
+    store.update(&timer, |todo_list, state, data_manager| {
+        let finished_todo = todo_list.drain_finished_todo();
+        data_manager.archive_todo(finished_todo.clone())?;
+
+        let payload = (!finished_todo.is_empty())
+            .then(|| HookPayload::new(HookEvent::TodoArchived, state, finished_todo.clone()));
+        Ok((finished_todo.into_iter().map(Todo::from).collect(), payload))
+    })
+}
+
+/// Summary of today, or of this week from Monday.
+#[tauri::command]
+pub fn stats(store: tauri::State<'_, Store>, week: bool) -> Result<Stats, String> {
+    let today = Local::today();
+    let since = if week {
+        today - Duration::days(today.weekday().num_days_from_monday() as i64)
+    } else {
+        today
+    };
+
+    let data_manager = store.data_manager.lock().map_err(|e| e.to_string())?;
+    let task_logs = data_manager.get_log_all().map_err(|e| e.to_string())?;
+    let summary = SessionSummary::from_logs(task_logs.iter().filter(|log| {
+        log.get_date()
+            .iter()
+            .any(|date| date.date() >= since && date.date() <= today)
+    }));
+
+    Ok(Stats {
+        work_count: summary.work_count,
+        break_count: summary.break_count,
+        work_seconds: summary.work_seconds,
+        break_seconds: summary.break_seconds,
+        skipped_work_count: summary.skipped_work_count,
+        skipped_break_count: summary.skipped_break_count,
+        break_ratio: summary.break_ratio(),
+    })
+}
@@ -1,30 +1,41 @@
 import React from 'react';
 import './App.css';
 import { invoke } from '@tauri-apps/api/tauri';
+import { listen } from '@tauri-apps/api/event';
 import { PomodoroTimer } from './Timer';
 import { TodoList } from './TodoList';
 import { Todo } from './Todo';
 import { Grid } from '@mui/material';
 
 function App() {
-  //function executeCommands() {
-  //invoke('command_with_message', {message: 'some message' }).then(message=>{
-  //console.log('command_with_message', message)
-  //})
-  //}
-
-  const [targetTodo, setTargetTodo] = React.useState<Todo>();
   const [todos, setTodos] = React.useState<Todo[]>([]);
 
+  const refreshTodos = () => {
+    invoke<Todo[]>('list_todos').then(setTodos).catch(console.error);
+  };
+
+  React.useEffect(() => {
+    refreshTodos();
+    // a finished pomodoro changes the executed count.
+    const unlisten = listen('timer-event', refreshTodos);
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+
+  const focusTodo = (todo: Todo) => {
+    invoke('focus_todo', { id: todo.id }).catch(console.error);
+  };
+
   return (
     <div className="App">
       <header className="App-header">
         <Grid container>
           <Grid item xs={4}>
-            <PomodoroTimer todos={todos} setTodos={setTodos} targetTodo={targetTodo} />
+            <PomodoroTimer />
           </Grid>
           <Grid item xs={8}>
-            <TodoList todos={todos} setTodos={setTodos} emitTargetTodo={setTargetTodo} />
+            <TodoList todos={todos} refreshTodos={refreshTodos} emitTargetTodo={focusTodo} />
           </Grid>
         </Grid>
       </header>
@@ -1,77 +1,53 @@
 import React from 'react';
-import { Todo } from './Todo';
-import { useTimer } from 'use-timer';
-import { PomodoroState } from './pomodoroStatus';
-import { sendNotification } from '@tauri-apps/api/notification';
-import { getTimerSeconds } from './pomodoroStatus';
-import { getStringOfStatus } from './pomodoroStatus';
+import { invoke } from '@tauri-apps/api/tauri';
+import { listen } from '@tauri-apps/api/event';
+import { TimerStatus } from './pomodoroStatus';
 import { IconButton } from '@mui/material';
 import PlayCircleFilledWhiteIcon from '@mui/icons-material/PlayCircleFilledWhite';
 import PauseCircleIcon from '@mui/icons-material/PauseCircle';
 import StopCircleIcon from '@mui/icons-material/StopCircle';
 
-export function PomodoroTimer({
-  todos,
-  setTodos,
-  targetTodo,
-}: {
-  todos: Todo[];
-  setTodos: (todos: Todo[]) => void;
-  targetTodo: Todo | undefined;
-}) {
-  const [state, setState] = React.useState<PomodoroState>(new PomodoroState('WORK', 0));
+// the backend runs the timer and sends `timer-tick` every second; this only shows it.
+export function PomodoroTimer() {
+  const [timerStatus, setTimerStatus] = React.useState<TimerStatus>();
 
-  const { time, start, pause, reset, status, advanceTime } = useTimer({
-    initialTime: getTimerSeconds('BREAK'),
-    timerType: 'DECREMENTAL',
-    endTime: 0,
-    onTimeOver: () => {
-      pause();
-      sendNotification(getStringOfStatus(state.getState(), state.workCount) + ' is Finish.');
-      if (state.getState() === 'WORK' && targetTodo !== undefined) {
-        const deepCopy = todos.map((todo) => ({ ...todo }));
-        const newTodos = deepCopy.map((todo) => {
-          if (todo.id === targetTodo.id) {
-            todo.executedCount += 1;
-          }
-          return todo;
-        });
+  const request = (command: string) => {
+    invoke<TimerStatus>(command).then(setTimerStatus).catch(console.error);
+  };
 
-        setTodos(newTodos);
-      }
-      if (state.getState() === 'BREAK' || state.getState() === 'LUNCH') {
-        setState(state.getIncrementWorkCountedState());
-      }
-      setState(state.getNextState());
-      advanceTime(-getTimerSeconds(state.getState()));
-    },
-  });
+  React.useEffect(() => {
+    request('timer_status');
+    const unlisten = listen<TimerStatus>('timer-tick', (event) => setTimerStatus(event.payload));
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+
+  // negative while in overtime.
+  const remaining = timerStatus?.remainingSeconds ?? 0;
+  const time = Math.abs(remaining);
 
   // styleの指定は別ファイルでやりたい。
   return (
     <div style={{ textAlign: 'center' }}>
       <link rel="stylesheet" type="text/css" href="./Timer.css"></link>
-      <h1>{getStringOfStatus(state.getState(), state.workCount)} </h1>
-      <h1>{targetTodo === undefined ? 'free' : targetTodo.title} </h1>
+      <h1>{timerStatus?.name} </h1>
+      <h1>{timerStatus?.title || 'free'} </h1>
       <div style={{ fontSize: '100px' }}>
+        {remaining < 0 && <span>+</span>}
         <span>{Math.floor(time / 60)}</span>:<span>{time % 60}</span>
       </div>
-      <p>{status === 'RUNNING' ? 'Process...' : 'Done!'} </p>
-      <IconButton onClick={start}>
+      <p>{timerStatus?.running ? 'Process...' : timerStatus?.paused ? 'Paused' : 'Done!'} </p>
+      <IconButton onClick={() => request('start_timer')}>
         <PlayCircleFilledWhiteIcon
           className="Test"
           style={{ fontSize: '48px', color: 'gray' }}
         ></PlayCircleFilledWhiteIcon>
       </IconButton>
-      <IconButton onClick={pause}>
+      <IconButton onClick={() => request('pause_timer')}>
         <PauseCircleIcon style={{ fontSize: '48px', color: 'gray' }}></PauseCircleIcon>
       </IconButton>
-      <IconButton
-        onClick={() => {
-          setState(new PomodoroState('BREAK', 0));
-          reset();
-        }}
-      >
+      <IconButton onClick={() => request('skip_phase')}>
         <StopCircleIcon style={{ fontSize: '48px', color: 'gray' }}></StopCircleIcon>
       </IconButton>
     </div>
@@ -1,10 +1,11 @@
+// mirrors `Todo` in src-tauri/src/todos.rs.
 export type Todo = {
+  readonly id: string;
   title: string;
   tag: string;
   project: string;
-  readonly id: string;
-  checked: boolean;
-  removed: boolean;
   estimateCount: number;
   executedCount: number;
+  finished: boolean;
+  detail: string;
 };
@@ -1,6 +1,7 @@
 import React from 'react';
 import { useState } from 'react';
-import { Button, TextField } from '@mui/material';
+import { TextField } from '@mui/material';
+import { invoke } from '@tauri-apps/api/tauri';
 import AddIcon from '@mui/icons-material/Add';
 import { IconButton } from '@mui/material';
 import { Grid } from '@mui/material';
@@ -9,6 +10,7 @@ import BoltIcon from '@mui/icons-material/Bolt';
 import Box from '@mui/material/Box';
 import { DataGrid, GridSelectionModel, GridColDef } from '@mui/x-data-grid';
 import { Todo } from './Todo';
+import CheckCircleIcon from '@mui/icons-material/CheckCircle';
 import DeleteIcon from '@mui/icons-material/Delete';
 
 type Filter = 'all' | 'finished' | 'progress' | 'trash';
@@ -16,26 +18,34 @@ type Attribute = 'title' | 'tag' | 'project' | 'pomodoroCount';
 
 export function TodoList({
   todos,
-  setTodos,
+  refreshTodos,
   emitTargetTodo,
 }: {
   todos: Todo[];
-  setTodos: (todos: Todo[]) => void;
+  refreshTodos: () => void;
   emitTargetTodo: (targetTodo: Todo) => void;
 }) {
-  const DeleteButton = ({ rowId }: { rowId: string }) => {
-    const deleteRow = ({ rowId }: { rowId: string }) => {
-      const deepCopy = todos.map((todo) => ({ ...todo }));
-      const newTodos = deepCopy.filter((todo) => {
-        return todo.id !== rowId;
-      });
+  // every change goes to the backend, which then hands the list back.
+  const request = (command: string, args?: Record<string, unknown>) => {
+    invoke(command, args).then(refreshTodos).catch(console.error);
+  };
 
-      setTodos(newTodos);
-    };
+  const FinishButton = ({ rowId }: { rowId: string }) => {
+    return (
+      <div>
+        <IconButton onClick={() => request('finish_todo', { id: rowId })}>
+          <CheckCircleIcon
+            style={{ verticalAlign: 'middle', display: 'inline-flex' }}
+          ></CheckCircleIcon>
+        </IconButton>
+      </div>
+    );
+  };
 
+  const DeleteButton = ({ rowId }: { rowId: string }) => {
     return (
       <div>
-        <IconButton onClick={() => deleteRow({ rowId })}>
+        <IconButton onClick={() => request('delete_todo', { id: rowId })}>
           <DeleteIcon style={{ verticalAlign: 'middle', display: 'inline-flex' }}></DeleteIcon>
         </IconButton>
       </div>
@@ -59,6 +69,13 @@ export function TodoList({
       headerName: 'pomodoro',
       width: 90,
     },
+    {
+      field: 'finishButton',
+      headerName: 'finish',
+      sortable: false,
+      width: 90,
+      renderCell: (params) => <FinishButton rowId={params.id.toString()}></FinishButton>,
+    },
     {
       field: 'deleteButton',
       headerName: 'delete',
@@ -76,26 +93,20 @@ export function TodoList({
     executedCount: 0,
   });
   const [filter, setFilter] = useState<Filter>('all');
+  // the trash lives here until it is emptied; only then are the todos deleted.
+  const [removed, setRemoved] = useState<string[]>([]);
   const [pomodoroCount, setCount] = useState<number>(0);
   const [selectionModel, setSelectionModel] = React.useState<GridSelectionModel>([]);
 
   const handleOnSubmit = () => {
     if (!todo.title) return;
 
-    todo.estimateCount = pomodoroCount;
-
-    const newTodo: Todo = {
+    request('add_todo', {
       title: todo.title,
       tag: todo.tag,
       project: todo.project,
-      estimateCount: todo.estimateCount,
-      executedCount: todo.executedCount,
-      id: Math.random().toString(32).substring(2),
-      checked: false,
-      removed: false,
-    };
-
-    setTodos([newTodo, ...todos]);
+      estimateCount: pomodoroCount,
+    });
     setValues({ ...todo, title: '' });
   };
 
@@ -121,57 +132,34 @@ export function TodoList({
     }
   };
 
-  const handleOnEdit = (id: string, value: string) => {
-    const deepCopy = todos.map((todo) => ({ ...todo }));
-    const newTodos = deepCopy.map((todo) => {
-      if (todo.id === id) {
-        todo.title = value;
-      }
-      return todo;
-    });
-
-    setTodos(newTodos);
-  };
-
-  const handleOnCheck = (id: string, checked: boolean) => {
-    const deepCopy = todos.map((todo) => ({ ...todo }));
-    const newTodos = deepCopy.map((todo) => {
-      if (todo.id === id) {
-        todo.checked = !checked;
-      }
-      return todo;
-    });
+  const handleOnEdit = (target: Todo, value: string) => {
+    if (value === target.title) return;
 
-    setTodos(newTodos);
+    request('update_todo', { todo: { ...target, title: value } });
   };
 
-  const handleOnRemove = (id: string, removed: boolean) => {
-    const deepCopy = todos.map((todo) => ({ ...todo }));
-    const newTodos = deepCopy.map((todo) => {
-      if (todo.id === id) {
-        todo.removed = !removed;
-      }
-      return todo;
-    });
-
-    setTodos(newTodos);
+  const handleOnRemove = (id: string) => {
+    setRemoved(removed.includes(id) ? removed.filter((r) => r !== id) : [...removed, id]);
   };
 
   const handleOnEmpty = () => {
-    const newTodos = todos.filter((todo) => !todo.removed);
-    setTodos(newTodos);
+    Promise.all(removed.map((id) => invoke('delete_todo', { id })))
+      .then(() => setRemoved([]))
+      .then(refreshTodos)
+      .catch(console.error);
   };
 
   const filteredTodos = todos.filter((todo) => {
+    const isRemoved = removed.includes(todo.id);
     switch (filter) {
       case 'all':
-        return !todo.removed;
+        return !isRemoved;
       case 'finished':
-        return todo.checked && !todo.removed;
+        return todo.finished && !isRemoved;
       case 'progress':
-        return !todo.checked && !todo.removed;
+        return !todo.finished && !isRemoved;
       case 'trash':
-        return todo.removed;
+        return isRemoved;
       default:
         return todo;
     }
@@ -181,108 +169,108 @@ export function TodoList({
     <div>
       {filter === 'trash' ? (
         <button onClick={() => handleOnEmpty()}>Remove all.</button>
+      ) : filter === 'finished' ? (
+        <button onClick={() => request('archive_todos')}>Archive all.</button>
       ) : (
-        filter !== 'finished' && (
-          <form
-            onSubmit={(e) => {
-              e.preventDefault();
-              handleOnSubmit();
-            }}
-          >
-            <Grid container>
-              <Grid item xs={11}>
-                <TextField
-                  margin="normal"
-                  label="NewTask"
-                  value={todo.title}
-                  variant="standard"
-                  placeholder="Input New Task"
-                  inputProps={{ style: { fontSize: 10, color: 'white' } }}
-                  InputLabelProps={{ style: { color: 'gray' } }}
-                  onChange={(e) => handleOnChange(e, 'title')}
-                />
-                <TextField
-                  margin="normal"
-                  label="Tag"
-                  value={todo.tag}
-                  variant="standard"
-                  color="secondary"
-                  placeholder="Input New Task"
-                  inputProps={{ style: { fontSize: 10, color: 'white' } }}
-                  InputLabelProps={{ style: { color: 'gray' } }}
-                  onChange={(e) => handleOnChange(e, 'tag')}
-                />
-                <TextField
-                  margin="normal"
-                  label="Project"
-                  value={todo.project}
-                  variant="standard"
-                  color="warning"
-                  placeholder="Input New Task"
-                  inputProps={{ style: { fontSize: 10, color: 'white' } }}
-                  InputLabelProps={{ style: { color: 'gray' } }}
-                  onChange={(e) => handleOnChange(e, 'project')}
-                />
-                <IconButton onClick={handleOnSubmit}>
-                  <AddIcon style={{ verticalAlign: 'middle', display: 'inline-flex' }}></AddIcon>
-                </IconButton>
-                <TextField
-                  margin="normal"
-                  label="Pomodoro"
-                  value={pomodoroCount}
-                  size="small"
-                  variant="standard"
-                  color="warning"
-                  placeholder="Input Estimate Pomodoro"
-                  inputProps={{ style: { fontSize: 10, color: 'white' } }}
-                  InputLabelProps={{ style: { color: 'gray' } }}
-                  onChange={(e) => handleOnChange(e, 'pomodoroCount')}
-                />
-                <Rating
-                  name="pomodoro-count"
-                  value={pomodoroCount}
-                  max={10}
-                  precision={0.5}
-                  icon={<BoltIcon fontSize="inherit" />}
-                  emptyIcon={<BoltIcon fontSize="inherit" />}
-                  onChange={(e, newValue) => {
-                    if (newValue === null) return;
-                    setCount(newValue);
-                  }}
-                />
-              </Grid>
-            </Grid>
-            <Box sx={{ height: 400, width: '95%' }}>
-              <DataGrid
-                getRowId={(row) => row.id}
-                density="compact"
-                rows={todos.map((todo) => {
-                  return {
-                    id: todo.id,
-                    title: todo.title,
-                    tag: todo.tag,
-                    project: todo.project,
-                    pomodoro: String(todo.executedCount) + '/' + String(todo.estimateCount),
-                  };
-                })}
-                columns={columns}
-                pageSize={5}
-                rowsPerPageOptions={[5]}
-                autoPageSize
-                onSelectionModelChange={(newSelectionModel) => {
-                  setSelectionModel(newSelectionModel);
-                  const target = todos.find((todo) => {
-                    return todo.id === newSelectionModel[0];
-                  });
-                  if (target !== undefined) {
-                    emitTargetTodo(target);
-                  }
+        <form
+          onSubmit={(e) => {
+            e.preventDefault();
+            handleOnSubmit();
+          }}
+        >
+          <Grid container>
+            <Grid item xs={11}>
+              <TextField
+                margin="normal"
+                label="NewTask"
+                value={todo.title}
+                variant="standard"
+                placeholder="Input New Task"
+                inputProps={{ style: { fontSize: 10, color: 'white' } }}
+                InputLabelProps={{ style: { color: 'gray' } }}
+                onChange={(e) => handleOnChange(e, 'title')}
+              />
+              <TextField
+                margin="normal"
+                label="Tag"
+                value={todo.tag}
+                variant="standard"
+                color="secondary"
+                placeholder="Input New Task"
+                inputProps={{ style: { fontSize: 10, color: 'white' } }}
+                InputLabelProps={{ style: { color: 'gray' } }}
+                onChange={(e) => handleOnChange(e, 'tag')}
+              />
+              <TextField
+                margin="normal"
+                label="Project"
+                value={todo.project}
+                variant="standard"
+                color="warning"
+                placeholder="Input New Task"
+                inputProps={{ style: { fontSize: 10, color: 'white' } }}
+                InputLabelProps={{ style: { color: 'gray' } }}
+                onChange={(e) => handleOnChange(e, 'project')}
+              />
+              <IconButton onClick={handleOnSubmit}>
+                <AddIcon style={{ verticalAlign: 'middle', display: 'inline-flex' }}></AddIcon>
+              </IconButton>
+              <TextField
+                margin="normal"
+                label="Pomodoro"
+                value={pomodoroCount}
+                size="small"
+                variant="standard"
+                color="warning"
+                placeholder="Input Estimate Pomodoro"
+                inputProps={{ style: { fontSize: 10, color: 'white' } }}
+                InputLabelProps={{ style: { color: 'gray' } }}
+                onChange={(e) => handleOnChange(e, 'pomodoroCount')}
+              />
+              <Rating
+                name="pomodoro-count"
+                value={pomodoroCount}
+                max={10}
+                precision={0.5}
+                icon={<BoltIcon fontSize="inherit" />}
+                emptyIcon={<BoltIcon fontSize="inherit" />}
+                onChange={(e, newValue) => {
+                  if (newValue === null) return;
+                  setCount(newValue);
                 }}
-                selectionModel={selectionModel}
               />
-            </Box>
-          </form>
-        )
+            </Grid>
+          </Grid>
+          <Box sx={{ height: 400, width: '95%' }}>
+            <DataGrid
+              getRowId={(row) => row.id}
+              density="compact"
+              rows={todos.map((todo) => {
+                return {
+                  id: todo.id,
+                  title: todo.title,
+                  tag: todo.tag,
+                  project: todo.project,
+                  pomodoro: String(todo.executedCount) + '/' + String(todo.estimateCount),
+                };
+              })}
+              columns={columns}
+              pageSize={5}
+              rowsPerPageOptions={[5]}
+              autoPageSize
+              onSelectionModelChange={(newSelectionModel) => {
+                setSelectionModel(newSelectionModel);
+                const target = todos.find((todo) => {
+                  return todo.id === newSelectionModel[0];
+                });
+                if (target !== undefined) {
+                  emitTargetTodo(target);
+                }
+              }}
+              selectionModel={selectionModel}
+            />
+          </Box>
+        </form>
       )}
       <select defaultValue="all" onChange={(e) => setFilter(e.target.value as Filter)}>
         <option value="all">All Task</option>
@@ -296,20 +284,18 @@ export function TodoList({
             <li key={todo.id}>
               <input
                 type="checkbox"
-                disabled={todo.removed}
-                checked={todo.checked}
-                onChange={(e) => handleOnCheck(todo.id, todo.checked)}
+                disabled={removed.includes(todo.id)}
+                checked={todo.finished}
+                onChange={() => request('finish_todo', { id: todo.id })}
               />
               <input
                 type="text"
-                disabled={todo.checked || todo.removed}
-                value={todo.title}
-                onChange={(e) => {
-                  handleOnEdit(todo.id, e.target.value);
-                }}
+                disabled={todo.finished || removed.includes(todo.id)}
+                defaultValue={todo.title}
+                onBlur={(e) => handleOnEdit(todo, e.target.value)}
               />
-              <button onClick={() => handleOnRemove(todo.id, todo.removed)}>
-                {todo.removed ? '復元' : '削除'}
+              <button onClick={() => handleOnRemove(todo.id)}>
+                {removed.includes(todo.id) ? '復元' : '削除'}
               </button>
             </li>
           );
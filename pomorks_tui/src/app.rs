@@ -1,238 +1,973 @@
-use crate::notifications::send_notification;
+use crate::cli::{self, Command};
+use crate::command_line::{self, CommandLine, LineCommand, Palette};
+use crate::controller::{Controller, SaveStatus};
+use crate::help::HintContext;
+use crate::keymap::{Action, Keymap};
+use crate::mouse::{ClickTracker, HitAreas, Target};
 use crate::statefull_list::StatefulList;
+use crate::todo_form::TodoForm;
 use crate::tui::UpdateInfo;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use chrono::prelude::*;
-use pomorks_data_manage::data_manage_trait::TaskLogJson;
-use pomorks_data_manage::todo::{State, TodoItem, TodoList};
+use pomorks_core::engine::{Engine, EngineEvent};
+use pomorks_data_manage::config::{BoardConfig, TimerConfig};
+use pomorks_data_manage::data_manage_trait::{SessionKind, TaskLogJson, UNASSIGNED_ID};
+use pomorks_data_manage::notifications::Notifier;
+use pomorks_data_manage::todo::{State, TodoItem, TodoStatus, ONE_MINUTE};
+use pomorks_data_manage::view::{GroupBy, ListView, TodoGroup};
+use std::collections::HashSet;
+use std::path::Path;
 use std::str::FromStr;
+use std::time::Instant;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Tab {
     Main,
+    Board,
     Statistics,
+    Unassigned,
+    Log,
 }
 
 impl Tab {
+    pub const ALL: [Tab; 5] = [
+        Tab::Main,
+        Tab::Board,
+        Tab::Statistics,
+        Tab::Unassigned,
+        Tab::Log,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tab::Main => "Todo",
+            Tab::Board => "Board",
+            Tab::Statistics => "Statistics",
+            Tab::Unassigned => "Unassigned",
+            Tab::Log => "Log",
+        }
+    }
+
     pub fn get_next_tab(&self) -> Self {
         match self {
-            Tab::Main => Tab::Statistics,
-            Tab::Statistics => Tab::Main,
+            Tab::Main => Tab::Board,
+            Tab::Board => Tab::Statistics,
+            Tab::Statistics => Tab::Unassigned,
+            Tab::Unassigned => Tab::Log,
+            Tab::Log => Tab::Main,
         }
     }
 }
 
+/// What `y` deletes once the dialog confirms it.
+pub enum Deletion {
+    Todo(TodoItem),
+    Log(TaskLogJson),
+}
+
+/// A line of the todo list: a group header, or a todo of `App::todos`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListRow {
+    Group(usize),
+    Todo(usize),
+}
+
 pub struct App<'a> {
-    pub title: &'a str,
     pub should_quit: bool,
-    pub show_add_todo: bool,
     pub show_chart: bool,
+    pub show_help: bool,
+    pub command_line: Option<CommandLine>,
+    pub command_history: Vec<String>,
+    pub palette: Option<Palette>,
+    // names of the configured timer profiles, for completion.
+    pub profiles: Vec<String>,
     pub progress: f64,
-    pub start_time: Option<DateTime<Local>>,
-    pub limit_time: usize,
-    pub on_progress: bool,
-    pub state: &'a State,
-    pub enhanced_graphics: bool,
+    // the controller's timer as of the last sync.
+    pub engine: Engine,
+    // every todo; `todos` holds those the list view shows.
+    pub all_todos: Vec<TodoItem>,
     pub todos: StatefulList<TodoItem>,
+    pub list_view: ListView,
+    // while `/` is being typed into.
+    pub searching: bool,
+    // shown while the list view groups; `todos` then holds the todos of the open groups.
+    pub groups: Vec<TodoGroup>,
+    pub collapsed: HashSet<String>,
+    // the group whose header the cursor is on, instead of a todo.
+    pub selected_group: Option<String>,
+    // the first row the list shows, so the columns scroll together and clicks map to rows.
+    pub row_offset: usize,
+    // index into `TodoStatus::ALL`; the selected todo is the card when it is in that column.
+    pub board_column: usize,
+    pub board: BoardConfig,
     pub todo_focus: Option<TodoItem>,
-    pub new_todo_string: String,
+    // the add or edit popup, while it is open.
+    pub todo_form: Option<TodoForm>,
+    pub confirm_delete: Option<Deletion>,
+    // the todo whose detail opens in $EDITOR before the next draw.
+    pub detail_to_edit: Option<TodoItem>,
     pub status: String,
+    pub save_status: SaveStatus,
     pub todays_executed_count: i64,
     pub selected_tab: Tab,
-    pub task_log: &'a Vec<TaskLogJson>,
+    pub task_log: Vec<TaskLogJson>,
+    pub unassigned_logs: StatefulList<TaskLogJson>,
+    pub logs: StatefulList<TaskLogJson>,
+    pub show_add_log: bool,
+    pub new_log_string: String,
+    pub extend_minutes: usize,
+    pub keymap: Keymap,
+    pub hit_areas: HitAreas,
+    pub clicks: ClickTracker,
+    pub notifier: &'a dyn Notifier,
 }
 
-#[allow(clippy::too_many_arguments)]
 impl<'a> App<'a> {
     pub fn new(
-        title: &'a str,
-        enhanced_graphics: bool,
-        todo_list: &TodoList,
-        state: &'a State,
-        status: String,
-        id: &Option<String>,
-        start_time: &Option<DateTime<Local>>,
-        todays_executed_count: i64,
-        task_log: &'a Vec<TaskLogJson>,
+        controller: &Controller,
+        timer_config: &TimerConfig,
+        board_config: &BoardConfig,
+        keymap: Keymap,
+        notifier: &'a dyn Notifier,
     ) -> App<'a> {
-        App {
-            title,
+        let mut app = App {
             should_quit: false,
-            show_add_todo: false,
             show_chart: false,
+            show_help: false,
+            command_line: None,
+            command_history: vec![],
+            palette: None,
+            profiles: controller.profile_names(),
             progress: 0.0,
-            start_time: *start_time,
-            limit_time: State::get_limit_time(state),
-            on_progress: false,
-            state,
-            todos: StatefulList::with_items(todo_list.get_vec_of_todo()),
-            enhanced_graphics,
-            // TODO!:分かりにくすぎる...
-            todo_focus: if let Some(id) = id {
-                todo_list.get_vec_of_todo().iter().find_map(|todo| {
-                    if &todo.id == id {
-                        Some(todo.clone())
-                    } else {
-                        None
-                    }
-                })
-            } else {
-                None
-            },
-            new_todo_string: String::new(),
-            status,
-            todays_executed_count,
+            engine: controller.engine.clone(),
+            all_todos: vec![],
+            todos: StatefulList::with_items(vec![]),
+            list_view: ListView::default(),
+            searching: false,
+            groups: vec![],
+            collapsed: HashSet::new(),
+            selected_group: None,
+            row_offset: 0,
+            board_column: 0,
+            board: board_config.clone(),
+            todo_focus: None,
+            todo_form: None,
+            confirm_delete: None,
+            detail_to_edit: None,
+            status: String::new(),
+            save_status: SaveStatus::Saved,
+            todays_executed_count: 0,
             selected_tab: Tab::Main,
-            task_log,
+            task_log: vec![],
+            unassigned_logs: StatefulList::with_items(vec![]),
+            logs: StatefulList::with_items(vec![]),
+            show_add_log: false,
+            new_log_string: String::new(),
+            extend_minutes: timer_config.extend_minutes,
+            keymap,
+            hit_areas: HitAreas::default(),
+            clicks: ClickTracker::default(),
+            notifier,
+        };
+        app.sync(controller);
+
+        app.todo_focus = controller
+            .focus
+            .as_ref()
+            .and_then(|id| app.all_todos.iter().find(|todo| &todo.id == id).cloned());
+
+        app
+    }
+
+    // pulls the controller's data in, keeping the selected todo and the focus.
+    pub fn sync(&mut self, controller: &Controller) {
+        self.all_todos = controller.todo_list.get_vec_of_todo();
+        self.task_log = controller.task_log.clone();
+        self.refresh_todos();
+        self.todo_focus = self.todo_focus.as_ref().and_then(|focus| {
+            self.all_todos
+                .iter()
+                .find(|todo| todo.id == focus.id)
+                .cloned()
+        });
+
+        self.engine = controller.engine.clone();
+        if let Some(timer_config) = controller.timer_profile() {
+            self.extend_minutes = timer_config.extend_minutes;
+        }
+        self.todays_executed_count = controller.todays_executed_count;
+        self.save_status = controller.save_status.clone();
+
+        self.unassigned_logs.set_items(
+            self.task_log
+                .iter()
+                .filter(|log| log.is_unassigned())
+                .cloned()
+                .collect(),
+        );
+        self.logs.set_items({
+            let mut logs: Vec<TaskLogJson> = self
+                .task_log
+                .iter()
+                .filter(|log| log.kind == SessionKind::Work)
+                .cloned()
+                .collect();
+            logs.sort_by_key(|log| std::cmp::Reverse(log.get_date()));
+            logs
+        });
+    }
+
+    // applies the list view, keeping the selected todo while it is shown.
+    pub fn refresh_todos(&mut self) {
+        let selected_id = self
+            .todos
+            .state
+            .selected()
+            .map(|ind| self.todos.items[ind].id.clone());
+        let todos = self.list_view.apply(self.all_todos.clone(), &self.task_log);
+        if self.list_view.group == GroupBy::None {
+            self.groups = vec![];
+            self.selected_group = None;
+            self.todos.set_items(todos);
+        } else {
+            self.groups = self.list_view.group(todos);
+            let collapsed = &self.collapsed;
+            self.todos.set_items(
+                self.groups
+                    .iter()
+                    .filter(|group| !collapsed.contains(&group.name))
+                    .flat_map(|group| group.todos.iter().cloned())
+                    .collect(),
+            );
+            if self.selected_group.is_some() {
+                self.todos.state.select(None);
+            }
+        }
+        if let Some(id) = selected_id {
+            if let Some(ind) = self.todos.items.iter().position(|todo| todo.id == id) {
+                self.todos.state.select(Some(ind));
+            }
         }
     }
 
+    pub fn set_list_view(&mut self, list_view: ListView) {
+        self.list_view = list_view;
+        self.refresh_todos();
+        if let Err(e) = self.list_view.write() {
+            self.status = e.to_string();
+        }
+    }
+
+    // `None` when no todo is selected.
+    fn selected_todo(&self) -> Option<&TodoItem> {
+        self.todos
+            .state
+            .selected()
+            .map(|ind| &self.todos.items[ind])
+    }
+
+    fn on_filter_tag(&mut self) {
+        let tag = match &self.list_view.tag {
+            Some(_) => None,
+            None => self.selected_todo().map(|todo| todo.tag.clone()),
+        };
+        self.set_list_view(ListView {
+            tag,
+            ..self.list_view.clone()
+        });
+    }
+
+    fn on_filter_project(&mut self) {
+        let project = match &self.list_view.project {
+            Some(_) => None,
+            None => self.selected_todo().map(|todo| todo.project.clone()),
+        };
+        self.set_list_view(ListView {
+            project,
+            ..self.list_view.clone()
+        });
+    }
+
+    pub fn rows(&self) -> Vec<ListRow> {
+        if self.groups.is_empty() {
+            return (0..self.todos.items.len()).map(ListRow::Todo).collect();
+        }
+        let mut rows = vec![];
+        let mut todo_ind = 0;
+        for (group_ind, group) in self.groups.iter().enumerate() {
+            rows.push(ListRow::Group(group_ind));
+            if !self.collapsed.contains(&group.name) {
+                rows.extend((todo_ind..todo_ind + group.todos.len()).map(ListRow::Todo));
+                todo_ind += group.todos.len();
+            }
+        }
+        rows
+    }
+
+    pub fn selected_row(&self) -> Option<usize> {
+        let selected = match (&self.selected_group, self.todos.state.selected()) {
+            (Some(name), _) => {
+                ListRow::Group(self.groups.iter().position(|group| &group.name == name)?)
+            }
+            (None, Some(ind)) => ListRow::Todo(ind),
+            (None, None) => return None,
+        };
+        self.rows().iter().position(|row| *row == selected)
+    }
+
+    fn select_row(&mut self, row: ListRow) {
+        match row {
+            ListRow::Group(ind) => {
+                self.selected_group = Some(self.groups[ind].name.clone());
+                self.todos.state.select(None);
+            }
+            ListRow::Todo(ind) => {
+                self.selected_group = None;
+                self.todos.state.select(Some(ind));
+            }
+        }
+    }
+
+    // the log tabs list the todos without their headers.
+    fn is_grouped(&self) -> bool {
+        !self.groups.is_empty() && matches!(self.selected_tab, Tab::Main)
+    }
+
     pub fn on_up(&mut self) {
-        self.todos.previous();
+        if !self.is_grouped() {
+            return self.todos.previous();
+        }
+        let rows = self.rows();
+        let ind = match self.selected_row() {
+            Some(0) | None => rows.len() - 1,
+            Some(ind) => ind - 1,
+        };
+        self.select_row(rows[ind]);
     }
 
     pub fn on_down(&mut self) {
-        self.todos.next();
+        if !self.is_grouped() {
+            return self.todos.next();
+        }
+        let rows = self.rows();
+        let ind = match self.selected_row() {
+            Some(ind) if ind + 1 < rows.len() => ind + 1,
+            _ => 0,
+        };
+        self.select_row(rows[ind]);
+    }
+
+    // the group the cursor is in, on its header or on one of its todos.
+    fn current_group(&self) -> Option<usize> {
+        let selected = self.selected_row()?;
+        self.rows()[..=selected]
+            .iter()
+            .rev()
+            .find_map(|row| match row {
+                ListRow::Group(ind) => Some(*ind),
+                ListRow::Todo(_) => None,
+            })
     }
 
-    pub fn on_right(&mut self) {
-        //self.tabs.next();
+    fn on_jump_group(&mut self, forward: bool) {
+        if !self.is_grouped() {
+            return;
+        }
+        let len = self.groups.len();
+        let ind = match (self.current_group(), forward) {
+            (Some(ind), true) => (ind + 1) % len,
+            (Some(ind), false) if self.selected_group.is_none() => ind,
+            (Some(ind), false) => (ind + len - 1) % len,
+            (None, _) => 0,
+        };
+        self.select_row(ListRow::Group(ind));
     }
 
-    pub fn on_left(&mut self) {
-        //self.tabs.previous();
+    // collapsing from a todo leaves the cursor on its header.
+    fn on_toggle_group(&mut self) {
+        if !self.is_grouped() {
+            return;
+        }
+        if let Some(ind) = self.current_group() {
+            let name = self.groups[ind].name.clone();
+            if !self.collapsed.remove(&name) {
+                self.collapsed.insert(name.clone());
+            }
+            self.selected_group = Some(name);
+            self.refresh_todos();
+        }
     }
 
     pub fn on_enter(&mut self) -> Result<Option<UpdateInfo>> {
-        if self.show_add_todo {
-            self.show_add_todo = false;
+        match self.selected_tab {
+            Tab::Unassigned => return Ok(self.on_reassign_task_log(&self.unassigned_logs)),
+            Tab::Log if self.show_add_log => {
+                self.show_add_log = false;
+                return Ok(Some(UpdateInfo::AddTaskLog(self.get_new_task_log()?)));
+            }
+            Tab::Log => return Ok(self.on_reassign_task_log(&self.logs)),
+            Tab::Main if self.selected_group.is_some() => {
+                self.on_toggle_group();
+                return Ok(None);
+            }
+            _ => {}
+        }
 
-            Ok(Some(UpdateInfo::AddNewTodo(
-                TodoItem::from_str(&self.new_todo_string)?,
-                false,
-            )))
-        } else {
-            self.todo_focus = match self.todos.state.selected() {
-                Some(ind) => Some(self.todos.items[ind].clone()),
-                None => None,
-            };
-            Ok(None)
+        self.todo_focus = match self.todos.state.selected() {
+            Some(ind) => Some(self.todos.items[ind].clone()),
+            None => None,
+        };
+        Ok(None)
+    }
+
+    pub fn on_edit_todo(&mut self) {
+        if let Some(ind) = self.todos.state.selected() {
+            self.todo_form = Some(TodoForm::edit(&self.todos.items[ind]));
+        }
+    }
+
+    pub fn on_delete_todo(&mut self) {
+        self.confirm_delete = self
+            .todos
+            .state
+            .selected()
+            .map(|ind| Deletion::Todo(self.todos.items[ind].clone()));
+    }
+
+    pub fn on_confirm_delete(&mut self, confirmed: bool) -> Option<UpdateInfo> {
+        let deletion = self.confirm_delete.take()?;
+        if !confirmed {
+            return None;
+        }
+        match deletion {
+            Deletion::Todo(todo) => {
+                if self.todo_focus.as_ref().map(|focus| &focus.id) == Some(&todo.id) {
+                    self.todo_focus = None;
+                }
+                Some(UpdateInfo::DeleteTodo(todo))
+            }
+            Deletion::Log(log) => Some(UpdateInfo::DeleteTaskLog(log)),
         }
     }
 
-    pub fn on_delete(&mut self) {
-        if self.show_add_todo {
-            self.new_todo_string.pop();
+    // keeps the form open with the error when a field is invalid.
+    pub fn on_submit_form(&mut self) -> Option<UpdateInfo> {
+        let form = self.todo_form.as_mut()?;
+        match form.to_todo() {
+            Ok(todo) => {
+                let info = if form.is_editing() {
+                    UpdateInfo::EditTodo(todo)
+                } else {
+                    UpdateInfo::AddNewTodo(todo)
+                };
+                self.todo_form = None;
+                Some(info)
+            }
+            Err(e) => {
+                form.error = Some(e.to_string());
+                None
+            }
         }
     }
 
     pub fn on_change_tab(&mut self) {
-        self.selected_tab = Tab::get_next_tab(&self.selected_tab);
+        self.select_tab(Tab::get_next_tab(&self.selected_tab));
+    }
+
+    fn select_tab(&mut self, tab: Tab) {
+        self.selected_tab = tab;
+        if let (Tab::Board, Some(todo)) = (&self.selected_tab, self.selected_todo()) {
+            self.board_column = self.card_status(todo) as usize;
+        }
+    }
+
+    // the focused todo sits in doing until it is finished.
+    fn card_status(&self, todo: &TodoItem) -> TodoStatus {
+        let focused = self.todo_focus.as_ref().map(|focus| &focus.id) == Some(&todo.id);
+        if focused && !todo.finished {
+            TodoStatus::Doing
+        } else {
+            todo.column()
+        }
+    }
+
+    /// The cards of each column of `TodoStatus::ALL`, in the order of the list view.
+    pub fn board_columns(&self) -> Vec<Vec<TodoItem>> {
+        let mut columns = vec![vec![]; TodoStatus::ALL.len()];
+        for todo in self.list_view.apply(self.all_todos.clone(), &self.task_log) {
+            columns[self.card_status(&todo) as usize].push(todo);
+        }
+        columns
+    }
+
+    /// The row of the selected card in `board_column`.
+    pub fn board_card(&self, columns: &[Vec<TodoItem>]) -> Option<usize> {
+        let selected = self.selected_todo()?;
+        columns[self.board_column]
+            .iter()
+            .position(|todo| todo.id == selected.id)
+    }
+
+    // the card is the selected todo, so the todo actions act on it.
+    fn select_card(&mut self, card: Option<&TodoItem>) {
+        match card {
+            Some(todo) => self.show_todo(todo),
+            None => self.todos.state.select(None),
+        }
+    }
+
+    fn on_board_action(&mut self, action: Action) -> Result<Option<UpdateInfo>> {
+        let columns = self.board_columns();
+        let card = self.board_card(&columns);
+        let cards = &columns[self.board_column];
+        match action {
+            Action::Up | Action::TodoUp => {
+                let row = card.map_or(0, |row| row.saturating_sub(1));
+                self.select_card(cards.get(row));
+            }
+            Action::Down | Action::TodoDown => {
+                let row = card.map_or(0, |row| (row + 1).min(cards.len().saturating_sub(1)));
+                self.select_card(cards.get(row));
+            }
+            Action::ColumnLeft | Action::ColumnRight => {
+                self.board_column = match action {
+                    Action::ColumnLeft => self.board_column.saturating_sub(1),
+                    _ => (self.board_column + 1).min(TodoStatus::ALL.len() - 1),
+                };
+                let cards = &columns[self.board_column];
+                let row = card.unwrap_or(0).min(cards.len().saturating_sub(1));
+                self.select_card(cards.get(row));
+            }
+            Action::MoveLeft | Action::MoveRight => {
+                let todo = match card {
+                    Some(row) => cards[row].clone(),
+                    None => return Ok(None),
+                };
+                let column = match action {
+                    Action::MoveLeft => self.board_column.checked_sub(1),
+                    _ => Some(self.board_column + 1).filter(|ind| *ind < TodoStatus::ALL.len()),
+                };
+                let status = match column {
+                    Some(ind) => TodoStatus::ALL[ind],
+                    None => return Ok(None),
+                };
+                let focused = self.todo_focus.as_ref().map(|focus| &focus.id) == Some(&todo.id);
+                if focused && !todo.finished && status < TodoStatus::Doing {
+                    self.status = "the focused todo stays in doing.".to_string();
+                    return Ok(None);
+                }
+                self.board_column = status as usize;
+                return Ok(Some(UpdateInfo::MoveTodo(todo, status)));
+            }
+            action => return self.on_todo_action(action),
+        }
+
+        Ok(None)
     }
 
     pub fn on_next_state(&mut self) -> Result<Option<UpdateInfo>> {
-        if !self.on_progress {
-            Ok(Some(UpdateInfo::MoveNextState()))
+        if !self.engine.is_running() {
+            Ok(Some(UpdateInfo::SkipPhase()))
         } else {
             Ok(None)
         }
     }
 
     pub fn on_prev_state(&mut self) -> Result<Option<UpdateInfo>> {
-        if !self.on_progress {
+        if !self.engine.is_running() {
             Ok(Some(UpdateInfo::MovePrevState()))
         } else {
             Ok(None)
         }
     }
 
+    pub fn on_skip_break(&mut self) -> Result<Option<UpdateInfo>> {
+        match self.engine.state() {
+            State::WORK(_) => Ok(None),
+            _ => Ok(Some(UpdateInfo::SkipPhase())),
+        }
+    }
+
+    pub fn on_extend_phase(&mut self) -> Result<Option<UpdateInfo>> {
+        if self.engine.is_running() {
+            Ok(Some(UpdateInfo::ExtendPhase(
+                self.extend_minutes * ONE_MINUTE,
+            )))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn on_finish_phase(&mut self) -> Result<Option<UpdateInfo>> {
+        if self.engine.is_running() {
+            Ok(Some(UpdateInfo::FinishPhase()))
+        } else {
+            Ok(None)
+        }
+    }
+
     pub fn on_change_finish_flag(&mut self) -> Result<Option<UpdateInfo>> {
         match self.todos.state.selected() {
             Some(ind) => Ok(Some(UpdateInfo::ChangeFinishStatus(
                 self.todos.items[ind].clone(),
-                false,
             ))),
             None => Ok(None),
         }
     }
 
-    pub fn on_key(&mut self, c: char, _: (u16, u16)) -> Result<Option<UpdateInfo>> {
-        if self.show_add_todo {
-            self.new_todo_string.push(c);
-        } else {
-            match c {
-                'b' => {
-                    return Ok(Some(UpdateInfo::ArchiveFinishedTodo(false)));
-                }
-                'a' => {
-                    self.show_add_todo = true;
-                }
-                'e' => {
-                    self.should_quit = true;
-                }
-                't' => {
-                    self.show_chart = !self.show_chart;
-                }
-                'j' => {
-                    self.on_down();
-                }
-                'k' => {
-                    self.on_up();
+    pub fn on_reassign_task_log(&self, logs: &StatefulList<TaskLogJson>) -> Option<UpdateInfo> {
+        match (logs.state.selected(), self.todos.state.selected()) {
+            (Some(log_ind), Some(todo_ind)) => Some(UpdateInfo::ReassignTaskLog(
+                logs.items[log_ind].clone(),
+                self.todos.items[todo_ind].clone(),
+            )),
+            _ => None,
+        }
+    }
+
+    pub fn on_delete_task_log(&mut self) {
+        self.confirm_delete = self
+            .logs
+            .state
+            .selected()
+            .map(|ind| Deletion::Log(self.logs.items[ind].clone()));
+    }
+
+    // back-dated pomodoro for the selected todo, from "YYYY/MM/DD HH:MM".
+    fn get_new_task_log(&self) -> Result<TaskLogJson> {
+        let naive = NaiveDateTime::parse_from_str(self.new_log_string.trim(), "%Y/%m/%d %H:%M")?;
+        let date = Local
+            .from_local_datetime(&naive)
+            .single()
+            .ok_or_else(|| anyhow!("date is ambiguous."))?;
+
+        let id = match self.todos.state.selected() {
+            Some(ind) => self.todos.items[ind].id.clone(),
+            None => UNASSIGNED_ID.to_string(),
+        };
+        let state = State::WORK(1);
+
+        Ok(TaskLogJson::new(
+            &id,
+            &date,
+            &state,
+            State::get_limit_time(&state) as i64,
+            false,
+        ))
+    }
+
+    pub fn on_action(&mut self, action: Action) -> Result<Option<UpdateInfo>> {
+        match (&self.selected_tab, action) {
+            (_, Action::Quit) => self.should_quit = true,
+            (_, Action::Help) => self.show_help = true,
+            (_, Action::CommandLine) => self.command_line = Some(CommandLine::default()),
+            (_, Action::Palette) => self.palette = Some(Palette::default()),
+            (_, Action::NextTab) => self.on_change_tab(),
+            (_, Action::Select) => return self.on_enter(),
+            (Tab::Board, action) => return self.on_board_action(action),
+            (_, Action::TodoUp) => self.on_up(),
+            (_, Action::TodoDown) => self.on_down(),
+            (Tab::Unassigned, Action::Up) => self.unassigned_logs.previous(),
+            (Tab::Unassigned, Action::Down) => self.unassigned_logs.next(),
+            (Tab::Log, Action::Up) => self.logs.previous(),
+            (Tab::Log, Action::Down) => self.logs.next(),
+            (Tab::Log, Action::Add) => self.show_add_log = true,
+            (Tab::Log, Action::Delete) => self.on_delete_task_log(),
+            (Tab::Main | Tab::Statistics, action) => return self.on_todo_action(action),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn on_todo_action(&mut self, action: Action) -> Result<Option<UpdateInfo>> {
+        match action {
+            Action::Up => self.on_up(),
+            Action::Down => self.on_down(),
+            Action::Add => self.todo_form = Some(TodoForm::new()),
+            Action::Edit => self.on_edit_todo(),
+            Action::EditDetail => {
+                self.detail_to_edit = self
+                    .todos
+                    .state
+                    .selected()
+                    .map(|ind| self.todos.items[ind].clone());
+            }
+            Action::Delete => self.on_delete_todo(),
+            Action::ToggleFinished => return self.on_change_finish_flag(),
+            Action::Archive => return Ok(Some(UpdateInfo::ArchiveFinishedTodo())),
+            Action::ToggleChart => self.show_chart = !self.show_chart,
+            Action::Search => self.searching = true,
+            Action::CycleSort => self.set_list_view(ListView {
+                sort: self.list_view.sort.next(),
+                ..self.list_view.clone()
+            }),
+            Action::HideFinished => self.set_list_view(ListView {
+                hide_finished: !self.list_view.hide_finished,
+                ..self.list_view.clone()
+            }),
+            Action::FilterTag => self.on_filter_tag(),
+            Action::FilterProject => self.on_filter_project(),
+            Action::OverEstimate => self.set_list_view(ListView {
+                over_estimate: !self.list_view.over_estimate,
+                ..self.list_view.clone()
+            }),
+            Action::GroupBy => {
+                self.selected_group = None;
+                self.set_list_view(ListView {
+                    group: self.list_view.group.next(),
+                    ..self.list_view.clone()
+                });
+            }
+            Action::ToggleGroup => self.on_toggle_group(),
+            Action::NextGroup => self.on_jump_group(true),
+            Action::PrevGroup => self.on_jump_group(false),
+            Action::ClearFilter => {
+                let mut list_view = self.list_view.clone();
+                list_view.clear_filter();
+                self.set_list_view(list_view);
+            }
+            Action::StartPause => {
+                if self.engine.is_running() {
+                    return Ok(Some(UpdateInfo::PausePhase()));
                 }
-                'l' => {
-                    return self.on_next_state();
+                return Ok(Some(UpdateInfo::StartTodo(
+                    if let Some(focus) = &self.todo_focus {
+                        focus.id.to_string()
+                    } else {
+                        "".to_string()
+                    },
+                )));
+            }
+            Action::NextState => return self.on_next_state(),
+            Action::PrevState => return self.on_prev_state(),
+            Action::SkipBreak => return self.on_skip_break(),
+            Action::ExtendPhase => return self.on_extend_phase(),
+            Action::FinishPhase => return self.on_finish_phase(),
+            Action::ToggleAutoStart => return Ok(Some(UpdateInfo::ToggleAutoStart())),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    pub fn on_submit_command(&mut self) -> Result<Option<UpdateInfo>> {
+        let line = match self.command_line.take() {
+            Some(command_line) => command_line.input.trim().to_string(),
+            None => return Ok(None),
+        };
+        if line.is_empty() {
+            return Ok(None);
+        }
+        if self.command_history.last() != Some(&line) {
+            self.command_history.push(line.clone());
+            let path = Path::new(command_line::HISTORY_FILE);
+            if let Err(e) = command_line::write_history(path, &self.command_history) {
+                self.status = format!("can't save the command history: {}", e);
+            }
+        }
+
+        match command_line::parse(&line)? {
+            LineCommand::Action(action) => self.on_action(action),
+            LineCommand::Jump(key) => {
+                self.select_todo(&key)?;
+                Ok(None)
+            }
+            LineCommand::Cli(Command::Add(todo)) => {
+                Ok(Some(UpdateInfo::AddNewTodo(TodoItem::from_str(&todo)?)))
+            }
+            LineCommand::Cli(Command::Done(key)) => {
+                let todo = self.select_todo(&key)?;
+                if todo.finished {
+                    return Err(anyhow!("{} is already finished.", todo.title));
                 }
-                'h' => {
-                    return self.on_prev_state();
+                Ok(Some(UpdateInfo::ChangeFinishStatus(todo)))
+            }
+            LineCommand::Cli(Command::Start(key)) => {
+                if self.engine.is_running() {
+                    return Err(anyhow!("a phase is already running."));
                 }
-                'f' => {
-                    return self.on_change_finish_flag();
+                let todo = self.select_todo(&key)?;
+                self.todo_focus = Some(todo.clone());
+                Ok(Some(UpdateInfo::StartTodo(todo.id)))
+            }
+            LineCommand::Cli(Command::Stats { week }) => {
+                self.status = cli::stats_lines(&self.task_log, week)?.join(", ");
+                Ok(None)
+            }
+            LineCommand::Cli(Command::Export { path, week }) => {
+                let count = cli::export_report(&self.task_log, &self.all_todos, week, &path)?;
+                self.status = format!("exported {} session(s) to {}", count, path.display());
+                Ok(None)
+            }
+            LineCommand::Sort(sort) => {
+                self.set_list_view(ListView {
+                    sort,
+                    ..self.list_view.clone()
+                });
+                Ok(None)
+            }
+            LineCommand::Tag(tag) => {
+                self.set_list_view(ListView {
+                    tag: Some(tag),
+                    ..self.list_view.clone()
+                });
+                Ok(None)
+            }
+            LineCommand::Project(project) => {
+                self.set_list_view(ListView {
+                    project: Some(project),
+                    ..self.list_view.clone()
+                });
+                Ok(None)
+            }
+            LineCommand::Profile(profile) => {
+                self.status = format!("profile {}", profile);
+                Ok(Some(UpdateInfo::SetProfile(profile)))
+            }
+            LineCommand::Cli(_) => Ok(None),
+        }
+    }
+
+    pub fn on_submit_palette(&mut self) -> Result<Option<UpdateInfo>> {
+        match self
+            .palette
+            .take()
+            .and_then(|palette| palette.selected_action())
+        {
+            Some(action) => self.on_action(action),
+            None => Ok(None),
+        }
+    }
+
+    // by id, else by title, else by the start of a title when only one has it.
+    // a todo hidden by the list view is shown by clearing the filter and opening its group.
+    fn select_todo(&mut self, key: &str) -> Result<TodoItem> {
+        let all = &self.all_todos;
+        let todo = all
+            .iter()
+            .find(|todo| todo.id == key)
+            .or_else(|| all.iter().find(|todo| todo.title == key))
+            .or_else(|| {
+                let mut starts = all.iter().filter(|todo| todo.title.starts_with(key));
+                match (starts.next(), starts.next()) {
+                    (Some(todo), None) => Some(todo),
+                    _ => None,
                 }
-                ' ' => {
-                    self.on_progress = !self.on_progress;
-                    if self.start_time.is_none() {
-                        self.start_time = Some(Local::now());
-                        return Ok(Some(UpdateInfo::StartTodo(
-                            self.start_time.unwrap(),
-                            if let Some(focus) = &self.todo_focus {
-                                focus.id.to_string()
-                            } else {
-                                "".to_string()
-                            },
-                            self.state.clone(),
-                        )));
+            })
+            .cloned()
+            .ok_or_else(|| anyhow!("no todo matches {}.", key))?;
+
+        if !self.list_view.is_match(&todo) {
+            let mut list_view = self.list_view.clone();
+            list_view.clear_filter();
+            self.set_list_view(list_view);
+        }
+        self.show_todo(&todo);
+        self.selected_tab = Tab::Main;
+        Ok(todo)
+    }
+
+    // selects a todo the list view matches, opening its group.
+    fn show_todo(&mut self, todo: &TodoItem) {
+        let group = match self.list_view.group {
+            GroupBy::Project => &todo.project,
+            _ => &todo.tag,
+        };
+        if self.collapsed.remove(group) {
+            self.refresh_todos();
+        }
+        self.selected_group = None;
+        let ind = self.todos.items.iter().position(|item| item.id == todo.id);
+        self.todos.state.select(ind);
+    }
+
+    /// Whether nothing is being typed or asked, so clicks act on what is drawn.
+    pub fn accepts_mouse(&self) -> bool {
+        !(self.show_help
+            || self.palette.is_some()
+            || self.command_line.is_some()
+            || self.searching
+            || self.todo_form.is_some()
+            || self.confirm_delete.is_some()
+            || self.show_add_log)
+    }
+
+    // a click selects, a double click focuses the todo or toggles the group.
+    pub fn on_click(&mut self, column: u16, row: u16, now: Instant) -> Result<Option<UpdateInfo>> {
+        let double = self.clicks.click(now, column, row);
+        match self.hit_areas.target(column, row) {
+            Some(Target::Tab(tab)) => self.select_tab(tab),
+            Some(Target::ListLine(line)) => {
+                if let Some(row) = self.rows().get(self.row_offset + line).copied() {
+                    self.select_row(row);
+                    if double {
+                        return self.on_enter();
                     }
                 }
-                _ => {}
             }
+            Some(Target::Timer) => return self.on_todo_action(Action::StartPause),
+            None => {}
         }
 
         Ok(None)
     }
 
-    pub fn on_tick(&mut self) -> Option<UpdateInfo> {
+    pub fn on_scroll(&mut self, column: u16, row: u16, down: bool) {
+        if let Some(Target::ListLine(_)) = self.hit_areas.target(column, row) {
+            if down {
+                self.on_down();
+            } else {
+                self.on_up();
+            }
+        }
+    }
+
+    pub fn hint_context(&self) -> HintContext {
+        if self.show_help {
+            HintContext::Help
+        } else if self.palette.is_some() {
+            HintContext::Palette
+        } else if self.searching {
+            HintContext::Search
+        } else if self.todo_form.is_some() {
+            HintContext::TodoForm
+        } else if self.confirm_delete.is_some() {
+            HintContext::ConfirmDelete
+        } else {
+            match self.selected_tab {
+                Tab::Main => HintContext::TodoList,
+                Tab::Board => HintContext::Board,
+                Tab::Statistics => HintContext::Statistics,
+                Tab::Unassigned => HintContext::Unassigned,
+                Tab::Log if self.show_add_log => HintContext::AddLog,
+                Tab::Log => HintContext::Log,
+            }
+        }
+    }
+
+    pub fn on_tick(&mut self) {
         // Update progress
         self.progress += 0.001;
         if self.progress > 1.0 {
             self.progress = 0.0;
         }
+    }
 
-        if let Some(start) = self.start_time {
-            if (Local::now() - start).num_seconds() as i64 >= self.limit_time as i64 {
-                send_notification(self.state).expect("can't send notification.");
-
-                return match &self.todo_focus {
-                    // TODO!:このCloneは微妙。Lifetime付けたいが、、、
-                    Some(todo) => {
-                        if let State::WORK(_) = self.state {
-                            Some(UpdateInfo::CountIncrement(todo.clone(), true))
-                        } else {
-                            Some(UpdateInfo::MoveNextState())
-                        }
-                    }
-                    None => Some(UpdateInfo::MoveNextState()),
-                };
+    // the timer ran out: either the phase ended or it went into overtime.
+    pub fn on_timer_events(&mut self, events: &[EngineEvent]) {
+        for event in events {
+            match event {
+                EngineEvent::PhaseFinished { finished, .. } => self.notify(finished),
+                EngineEvent::Overtime { state } => self.notify(state),
+                _ => {}
             }
         }
-        None
+    }
+
+    fn notify(&mut self, state: &State) {
+        if let Err(e) = self.notifier.notify(state) {
+            self.status = e.to_string();
+        }
     }
 }
@@ -0,0 +1,560 @@
+use crate::controller::Controller;
+use crate::date_manage::get_this_week;
+use crate::tui::UpdateInfo;
+use anyhow::{anyhow, Result};
+use chrono::prelude::*;
+use pomorks_core::engine::Engine;
+use pomorks_daemon::protocol::Request;
+use pomorks_daemon::remote::{default_socket_path, Remote};
+use pomorks_data_manage::config::Config;
+use pomorks_data_manage::data_manage_trait::{
+    DataManage, TaskLogJson, TypeDataManager, UNASSIGNED_ID,
+};
+use pomorks_data_manage::hooks::HookRunner;
+use pomorks_data_manage::statistics::SessionSummary;
+use pomorks_data_manage::status::{StatusSnapshot, DEFAULT_STATUS_FORMAT};
+use pomorks_data_manage::todo::{State, TodoItem, TodoList};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+pub const USAGE: &str = "\
+Usage: pomorks_tui [--backend <json|firebase>] [--data-dir <dir>] [<command>]
+
+Without a command the interactive TUI is launched.
+Commands default to the json backend when --backend is not given.
+
+Commands:
+  add <title> <tag> <project> <estimate>   add a todo
+  list [--all] [--finished] [--tag <tag>] [--project <project>]
+  done <id>                                mark a todo as finished
+  start <id>                               start the current phase on a todo,
+                                           through pomorksd when it runs
+  status [--json | --format <format>]      show the current phase
+           format placeholders: {state} {remaining} {time} {title} {count} {running}
+  stats [--week]                           summary of today or this week
+  export [--week] <file>                   write the sessions of today or this week as CSV
+  archive                                  archive finished todos";
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Args {
+    pub backend: Option<TypeDataManager>,
+    pub data_dir: Option<PathBuf>,
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Add(String),
+    List(ListFilter),
+    Done(String),
+    Start(String),
+    Status(StatusOutput),
+    Stats { week: bool },
+    Export { path: PathBuf, week: bool },
+    Archive,
+    Help,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum StatusOutput {
+    Text(String),
+    Json,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ListFilter {
+    pub all: bool,
+    pub finished: bool,
+    pub tag: Option<String>,
+    pub project: Option<String>,
+}
+
+impl ListFilter {
+    fn is_match(&self, todo: &TodoItem) -> bool {
+        let status_match = if self.finished {
+            todo.finished
+        } else {
+            self.all || !todo.finished
+        };
+
+        status_match
+            && self.tag.iter().all(|tag| &todo.tag == tag)
+            && self.project.iter().all(|project| &todo.project == project)
+    }
+}
+
+impl Args {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Args> {
+        let mut backend = None;
+        let mut data_dir = None;
+        let mut rest = vec![];
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--backend" => backend = Some(parse_backend(&next_value(&mut args, &arg)?)?),
+                "--data-dir" => data_dir = Some(PathBuf::from(next_value(&mut args, &arg)?)),
+                "-h" | "--help" => rest = vec!["help".to_string()],
+                _ => rest.push(arg),
+            }
+        }
+
+        let command = match rest.split_first() {
+            Some((name, command_args)) => Some(Command::parse(name, command_args)?),
+            None => None,
+        };
+
+        Ok(Args {
+            backend,
+            data_dir,
+            command,
+        })
+    }
+}
+
+impl Command {
+    pub fn parse(name: &str, args: &[String]) -> Result<Command> {
+        match name {
+            "add" if !args.is_empty() => Ok(Command::Add(args.join(" "))),
+            "add" => Err(anyhow!(
+                "add needs a todo: <title> <tag> <project> <estimate>"
+            )),
+            "list" => {
+                let mut filter = ListFilter::default();
+                let mut args = args.iter().cloned();
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--all" => filter.all = true,
+                        "--finished" => filter.finished = true,
+                        "--tag" => filter.tag = Some(next_value(&mut args, &arg)?),
+                        "--project" => filter.project = Some(next_value(&mut args, &arg)?),
+                        _ => return Err(anyhow!("unexpected argument for list: {}", arg)),
+                    }
+                }
+                Ok(Command::List(filter))
+            }
+            "done" => Ok(Command::Done(single_id(name, args)?)),
+            "start" => Ok(Command::Start(single_id(name, args)?)),
+            "status" => match args {
+                [] => Ok(Command::Status(StatusOutput::Text(
+                    DEFAULT_STATUS_FORMAT.to_string(),
+                ))),
+                [json] if json == "--json" => Ok(Command::Status(StatusOutput::Json)),
+                [flag, format] if flag == "--format" => {
+                    Ok(Command::Status(StatusOutput::Text(format.clone())))
+                }
+                _ => Err(anyhow!(
+                    "unexpected argument for status: {}",
+                    args.join(" ")
+                )),
+            },
+            "stats" => match args {
+                [] => Ok(Command::Stats { week: false }),
+                [week] if week == "--week" => Ok(Command::Stats { week: true }),
+                _ => Err(anyhow!("unexpected argument for stats: {}", args.join(" "))),
+            },
+            "export" => match args {
+                [path] => Ok(Command::Export {
+                    path: PathBuf::from(path),
+                    week: false,
+                }),
+                [week, path] if week == "--week" => Ok(Command::Export {
+                    path: PathBuf::from(path),
+                    week: true,
+                }),
+                _ => Err(anyhow!("export needs a file: [--week] <file>")),
+            },
+            "archive" if args.is_empty() => Ok(Command::Archive),
+            "help" => Ok(Command::Help),
+            "archive" => Err(anyhow!("{} takes no argument.", name)),
+            _ => Err(anyhow!("unknown command: {}", name)),
+        }
+    }
+}
+
+fn next_value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String> {
+    args.next()
+        .ok_or_else(|| anyhow!("{} needs a value.", flag))
+}
+
+fn single_id(name: &str, args: &[String]) -> Result<String> {
+    match args {
+        [id] => Ok(id.clone()),
+        _ => Err(anyhow!("{} needs exactly one todo id.", name)),
+    }
+}
+
+fn parse_backend(name: &str) -> Result<TypeDataManager> {
+    TypeDataManager::from_name_ignore_case(name).ok_or_else(|| anyhow!("unknown backend: {}", name))
+}
+
+pub fn run(command: Command, data_manager: &dyn DataManage, config: &Config) -> Result<()> {
+    // the commands going through the controller write the cache from it.
+    let changes_status = matches!(command, Command::Add(_));
+    match command {
+        Command::Add(todo) => {
+            let mut todo_list = read_todo_list(data_manager)?;
+            let todo = TodoItem::from_str(&todo)?;
+            println!("{}", todo.id);
+            todo_list.add_todo(todo)?;
+            data_manager.write_all_todo(todo_list)?;
+        }
+        Command::List(filter) => {
+            let mut todos: Vec<TodoItem> = read_todo_list(data_manager)?
+                .get_vec_of_todo()
+                .into_iter()
+                .filter(|todo| filter.is_match(todo))
+                .collect();
+            todos.sort_by(|a, b| (&a.project, &a.title).cmp(&(&b.project, &b.title)));
+            for todo in todos {
+                println!(
+                    "{} [{}] {} #{} @{} {}/{}",
+                    todo.id,
+                    if todo.finished { "x" } else { " " },
+                    todo.title,
+                    todo.tag,
+                    todo.project,
+                    todo.executed_count,
+                    todo.estimate_count
+                );
+            }
+        }
+        Command::Done(id) => {
+            let mut controller = open_controller(data_manager, config)?;
+            let todo = get_todo(&controller.todo_list, &id)?;
+            if !todo.finished {
+                controller.dispatch(UpdateInfo::ChangeFinishStatus(todo))?;
+            }
+            close_controller(controller)?;
+        }
+        Command::Start(id) => {
+            let mut controller = open_controller(data_manager, config)?;
+            let todo = get_todo(&controller.todo_list, &id)?;
+            // a paused phase goes on from where it stopped.
+            controller.dispatch(UpdateInfo::StartTodo(todo.id))?;
+            println!(
+                "{} started on {}",
+                State::get_state_name(controller.engine.state()),
+                todo.title
+            );
+            close_controller(controller)?;
+        }
+        Command::Status(output) => {
+            // the TUI, the daemon and the commands that change things keep the cache up to date.
+            let now = Local::now();
+            let snapshot = match StatusSnapshot::read_cache()? {
+                Some(snapshot) if !snapshot.is_stale(&now) => snapshot,
+                _ => {
+                    let snapshot = read_status_snapshot(data_manager)?;
+                    snapshot.write_cache()?;
+                    snapshot
+                }
+            };
+            let status_line = snapshot.to_status_line(&now, config.timer.overtime);
+            match output {
+                StatusOutput::Text(format) => println!("{}", status_line.format(&format)),
+                StatusOutput::Json => println!("{}", serde_json::to_string(&status_line)?),
+            }
+        }
+        Command::Stats { week } => {
+            for line in stats_lines(&data_manager.get_log_all()?, week)? {
+                println!("{}", line);
+            }
+        }
+        Command::Export { path, week } => {
+            let todos = read_todo_list(data_manager)?.get_vec_of_todo();
+            let count = export_report(&data_manager.get_log_all()?, &todos, week, &path)?;
+            println!("exported {} session(s) to {}", count, path.display());
+        }
+        Command::Archive => {
+            let mut controller = open_controller(data_manager, config)?;
+            let count = controller
+                .todo_list
+                .get_vec_of_todo()
+                .iter()
+                .filter(|todo| todo.finished)
+                .count();
+            controller.dispatch(UpdateInfo::ArchiveFinishedTodo())?;
+            println!("archived {} todo(s)", count);
+            close_controller(controller)?;
+        }
+        Command::Help => println!("{}", USAGE),
+    }
+    if changes_status {
+        read_status_snapshot(data_manager)?.write_cache()?;
+    }
+
+    Ok(())
+}
+
+/// Summary of today, or of this week, as printed by `stats`.
+pub fn stats_lines(task_logs: &[TaskLogJson], week: bool) -> Result<Vec<String>> {
+    let summary = SessionSummary::from_logs(logs_in_range(task_logs, week)?.into_iter());
+
+    let mut lines = vec![
+        format!(
+            "work: {} ({}min, {} skipped)",
+            summary.work_count,
+            summary.work_seconds / 60,
+            summary.skipped_work_count
+        ),
+        format!(
+            "break: {} ({}min, {} skipped)",
+            summary.break_count,
+            summary.break_seconds / 60,
+            summary.skipped_break_count
+        ),
+    ];
+    if let Some(ratio) = summary.break_ratio() {
+        lines.push(format!("break ratio: {:.2}", ratio));
+    }
+    Ok(lines)
+}
+
+/// Writes the sessions of today, or of this week, to `path` as CSV and returns how many.
+pub fn export_report(
+    task_logs: &[TaskLogJson],
+    todos: &[TodoItem],
+    week: bool,
+    path: &Path,
+) -> Result<usize> {
+    let logs = logs_in_range(task_logs, week)?;
+    let mut report =
+        String::from("date,kind,title,project,planned_seconds,actual_seconds,skipped\n");
+    for log in &logs {
+        // archived or deleted todos leave the title empty.
+        let todo = todos.iter().find(|todo| todo.id == log.id);
+        let fields = [
+            log.date.clone(),
+            format!("{:?}", log.kind),
+            todo.map(|todo| todo.title.clone()).unwrap_or_default(),
+            todo.map(|todo| todo.project.clone()).unwrap_or_default(),
+            log.planned_seconds.to_string(),
+            log.actual_seconds.to_string(),
+            log.skipped.to_string(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        report.push_str(&fields.join(","));
+        report.push('\n');
+    }
+    fs::write(path, report)?;
+
+    Ok(logs.len())
+}
+
+// quoted when it holds a comma, a quote or a line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn logs_in_range(task_logs: &[TaskLogJson], week: bool) -> Result<Vec<&TaskLogJson>> {
+    let days = if week {
+        get_this_week(Local::today())?
+    } else {
+        vec![Local::today()]
+    };
+
+    Ok(task_logs
+        .iter()
+        .filter(|log| {
+            log.get_date()
+                .iter()
+                .any(|date| days.contains(&date.date()))
+        })
+        .collect())
+}
+
+// done, start and archive go through the controller as in the TUI, so they fire the same hooks
+// and move the todo on the board; the timer is pomorksd's when it runs.
+fn open_controller<'a>(
+    data_manager: &'a dyn DataManage,
+    config: &Config,
+) -> Result<Controller<'a>> {
+    let todo_list = read_todo_list(data_manager)?;
+    let debounce = Duration::from_secs(config.autosave.debounce_seconds);
+    let hooks = HookRunner::new(config.hooks.clone());
+    match Remote::connect(&default_socket_path()) {
+        Some(mut remote) => {
+            let events = remote.events()?;
+            let status = remote.request(&Request::Status)?;
+            let engine = status.engine(&config.timer, Local::now());
+            Ok(
+                Controller::new(data_manager, todo_list, engine, None, debounce, hooks)?
+                    .with_daemon(remote, events, config.timer.clone(), status),
+            )
+        }
+        None => {
+            let task_dealing = data_manager.read_task_dealing()?;
+            let state = task_dealing.state.clone().unwrap_or(State::WORK(1));
+            let engine =
+                Engine::new(state, &config.timer).resumed_from(&task_dealing, Local::now());
+            Controller::new(
+                data_manager,
+                todo_list,
+                engine,
+                task_dealing.id.filter(|id| id != UNASSIGNED_ID),
+                debounce,
+                hooks,
+            )
+        }
+    }
+}
+
+// the hooks are waited for, or they would be cut short when the command exits.
+fn close_controller(mut controller: Controller) -> Result<()> {
+    controller.flush()?;
+    controller.status_snapshot().write_cache()?;
+    controller.hooks.wait();
+    for error in controller.hooks.take_errors() {
+        eprintln!("{}", error);
+    }
+
+    Ok(())
+}
+
+fn read_todo_list(data_manager: &dyn DataManage) -> Result<TodoList> {
+    Ok(data_manager.read_all_todo()?.unwrap_or_default())
+}
+
+fn read_status_snapshot(data_manager: &dyn DataManage) -> Result<StatusSnapshot> {
+    let task_dealing = data_manager.read_task_dealing()?;
+    let title = match &task_dealing.id {
+        Some(id) => read_todo_list(data_manager)?
+            .get_todo(id)
+            .map(|todo| todo.title)
+            .unwrap_or_default(),
+        None => String::new(),
+    };
+    let now = Local::now();
+
+    Ok(StatusSnapshot {
+        state: task_dealing.state.unwrap_or(State::WORK(1)),
+        start_time: task_dealing.date,
+        extended_seconds: task_dealing.extended_seconds,
+        paused_seconds: task_dealing.paused_seconds,
+        id: task_dealing.id,
+        title,
+        todays_count: data_manager.get_executed_count_by_day(&now)?,
+        written: now,
+    })
+}
+
+fn get_todo(todo_list: &TodoList, id: &str) -> Result<TodoItem> {
+    todo_list
+        .get_todo(id)
+        .ok_or_else(|| anyhow!("todo {} is not exist.", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_global_flags() {
+        let args = parse(&["--backend", "firebase", "--data-dir", "/tmp/pomo"]).unwrap();
+        assert_eq!(args.backend, Some(TypeDataManager::DataManageFirebase));
+        assert_eq!(args.data_dir, Some(PathBuf::from("/tmp/pomo")));
+        assert_eq!(args.command, None);
+
+        let args = parse(&["list", "--backend", "Json"]).unwrap();
+        assert_eq!(args.backend, Some(TypeDataManager::DataManageJson));
+        assert!(parse(&["--backend", "sqlite"]).is_err());
+        assert!(parse(&["--data-dir"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            parse(&["add", "write", "doc", "pomorks", "2"])
+                .unwrap()
+                .command,
+            Some(Command::Add("write doc pomorks 2".to_string()))
+        );
+        assert_eq!(
+            parse(&["list", "--tag", "doc", "--all"]).unwrap().command,
+            Some(Command::List(ListFilter {
+                all: true,
+                tag: Some("doc".to_string()),
+                ..ListFilter::default()
+            }))
+        );
+        assert_eq!(
+            parse(&["done", "abcdefghij"]).unwrap().command,
+            Some(Command::Done("abcdefghij".to_string()))
+        );
+        assert_eq!(
+            parse(&["stats", "--week"]).unwrap().command,
+            Some(Command::Stats { week: true })
+        );
+        assert_eq!(
+            parse(&["status", "--format", "{state} {time}"])
+                .unwrap()
+                .command,
+            Some(Command::Status(StatusOutput::Text(
+                "{state} {time}".to_string()
+            )))
+        );
+        assert_eq!(
+            parse(&["status", "--json"]).unwrap().command,
+            Some(Command::Status(StatusOutput::Json))
+        );
+        assert_eq!(parse(&["--help"]).unwrap().command, Some(Command::Help));
+        assert_eq!(
+            parse(&["export", "--week", "week.csv"]).unwrap().command,
+            Some(Command::Export {
+                path: PathBuf::from("week.csv"),
+                week: true
+            })
+        );
+        assert!(parse(&["export"]).is_err());
+        assert!(parse(&["start"]).is_err());
+        assert!(parse(&["archive", "now"]).is_err());
+        assert!(parse(&["launch"]).is_err());
+    }
+
+    #[test]
+    fn test_export_report() {
+        let todo = TodoItem::from_str("write,review doc pomorks 2").unwrap();
+        let now = Local::now();
+        let task_logs = vec![
+            TaskLogJson::new(&todo.id, &now, &State::WORK(1), 1500, false),
+            TaskLogJson::new("", &now, &State::BREAK(1), 0, true),
+            TaskLogJson::new(
+                &todo.id,
+                &(now - chrono::Duration::days(8)),
+                &State::WORK(1),
+                1500,
+                false,
+            ),
+        ];
+        let path = std::env::temp_dir().join(format!("pomorks-export-{}.csv", std::process::id()));
+
+        let count = export_report(&task_logs, &[todo], false, &path).unwrap();
+        let report = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(count, 2);
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(
+            lines[0],
+            "date,kind,title,project,planned_seconds,actual_seconds,skipped"
+        );
+        let work = State::get_limit_time(&State::WORK(1));
+        let rest = State::get_limit_time(&State::BREAK(1));
+        assert!(lines[1].ends_with(&format!(
+            ",Work,\"write,review\",pomorks,{},1500,false",
+            work
+        )));
+        assert!(lines[2].ends_with(&format!(",Break,,,{},0,true", rest)));
+    }
+}
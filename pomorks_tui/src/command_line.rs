@@ -0,0 +1,399 @@
+//! The `:` command line and the action palette.
+//!
+//! Every action of the keymap is a command under its config name, and the CLI
+//! subcommands that make sense in the TUI run against the TUI's own state.
+
+use crate::cli::Command;
+use crate::keymap::Action;
+use anyhow::{anyhow, Result};
+use pomorks_data_manage::todo::TodoItem;
+use pomorks_data_manage::view::SortMode;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// Commands beside the action names.
+const COMMANDS: [&str; 10] = [
+    "add", "done", "start", "stats", "export", "jump", "sort", "tag", "project", "profile",
+];
+
+/// Where the lines entered in the command line are kept between runs.
+pub const HISTORY_FILE: &str = "command_history.txt";
+/// Lines kept in the history file; older ones are dropped.
+const HISTORY_LIMIT: usize = 200;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum LineCommand {
+    Action(Action),
+    Cli(Command),
+    /// selects a todo by id or title.
+    Jump(String),
+    Sort(SortMode),
+    /// shows only the tag, or the project.
+    Tag(String),
+    Project(String),
+    /// switches to the timer settings of `[profiles.<name>]`.
+    Profile(String),
+}
+
+/// `add` alone opens the form like its key; `add <todo>` adds it as the CLI does.
+pub fn parse(line: &str) -> Result<LineCommand> {
+    let words: Vec<String> = line.split_whitespace().map(str::to_string).collect();
+    let (name, args) = words
+        .split_first()
+        .ok_or_else(|| anyhow!("command is empty."))?;
+    if args.is_empty() {
+        if let Some(action) = Action::from_name(name) {
+            return Ok(LineCommand::Action(action));
+        }
+    }
+
+    match name.as_str() {
+        "jump" if !args.is_empty() => Ok(LineCommand::Jump(args.join(" "))),
+        "jump" => Err(anyhow!("jump needs a todo id or title.")),
+        "sort" => match args {
+            [mode] => SortMode::from_name(mode)
+                .map(LineCommand::Sort)
+                .ok_or_else(|| anyhow!("unknown sort: {}", mode)),
+            _ => Err(anyhow!(
+                "sort needs one of priority, remaining, project, recent."
+            )),
+        },
+        "tag" | "project" => match args {
+            [value] if name == "tag" => Ok(LineCommand::Tag(value.clone())),
+            [value] => Ok(LineCommand::Project(value.clone())),
+            _ => Err(anyhow!("{} needs exactly one value.", name)),
+        },
+        "profile" => match args {
+            [profile] => Ok(LineCommand::Profile(profile.clone())),
+            _ => Err(anyhow!("profile needs a profile name.")),
+        },
+        // titles may have spaces here, unlike ids on the shell.
+        "done" | "start" if !args.is_empty() => {
+            Ok(LineCommand::Cli(Command::parse(name, &[args.join(" ")])?))
+        }
+        _ => match Command::parse(name, args)? {
+            command @ (Command::Add(_)
+            | Command::Done(_)
+            | Command::Start(_)
+            | Command::Stats { .. }
+            | Command::Export { .. }) => Ok(LineCommand::Cli(command)),
+            _ => Err(anyhow!("{} is only available from the shell.", name)),
+        },
+    }
+}
+
+/// Lines `input` can be completed to: command names, then their argument.
+pub fn completions(input: &str, todos: &[TodoItem], profiles: &[String]) -> Vec<String> {
+    match input.split_once(' ') {
+        None => {
+            let mut names: Vec<&str> = Action::ALL
+                .iter()
+                .map(|action| action.name())
+                .chain(COMMANDS.iter().copied())
+                .filter(|name| name.starts_with(input))
+                .collect();
+            names.sort_unstable();
+            names.dedup();
+            names.into_iter().map(str::to_string).collect()
+        }
+        Some((name, arg)) if ["done", "start", "jump"].contains(&name) => todos
+            .iter()
+            .filter(|todo| todo.title.starts_with(arg.trim_start()))
+            .map(|todo| format!("{} {}", name, todo.title))
+            .collect(),
+        Some((name, arg)) => {
+            let mut values: Vec<&str> = match name {
+                "sort" => SortMode::ALL.iter().map(|mode| mode.name()).collect(),
+                "tag" => todos.iter().map(|todo| todo.tag.as_str()).collect(),
+                "project" => todos.iter().map(|todo| todo.project.as_str()).collect(),
+                "profile" => profiles.iter().map(String::as_str).collect(),
+                _ => vec![],
+            };
+            values.sort_unstable();
+            values.dedup();
+            values
+                .into_iter()
+                .filter(|value| value.starts_with(arg.trim_start()))
+                .map(|value| format!("{} {}", name, value))
+                .collect()
+        }
+    }
+}
+
+/// The lines of earlier runs, oldest first.
+pub fn read_history(path: &Path) -> Result<Vec<String>> {
+    match fs::read_to_string(path) {
+        Ok(history) => Ok(history.lines().map(str::to_string).collect()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(vec![]),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn write_history(path: &Path, history: &[String]) -> Result<()> {
+    let kept = &history[history.len().saturating_sub(HISTORY_LIMIT)..];
+    let lines: String = kept.iter().map(|line| format!("{}\n", line)).collect();
+    fs::write(path, lines)?;
+
+    Ok(())
+}
+
+fn common_prefix(candidates: &[String]) -> Option<String> {
+    let (first, rest) = candidates.split_first()?;
+    let mut prefix: &str = first;
+    for candidate in rest {
+        let len = prefix
+            .char_indices()
+            .zip(candidate.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(prefix.len().min(candidate.len()), |((ind, _), _)| ind);
+        prefix = &prefix[..len];
+    }
+    Some(prefix.to_string())
+}
+
+#[derive(Debug, Default)]
+pub struct CommandLine {
+    pub input: String,
+    // position in the history while going through it with up and down.
+    history_ind: Option<usize>,
+}
+
+impl CommandLine {
+    pub fn insert(&mut self, c: char) {
+        self.input.push(c);
+        self.history_ind = None;
+    }
+
+    pub fn backspace(&mut self) {
+        self.input.pop();
+        self.history_ind = None;
+    }
+
+    /// Extends the input as far as all candidates agree and returns them.
+    pub fn complete(&mut self, todos: &[TodoItem], profiles: &[String]) -> Vec<String> {
+        let candidates = completions(&self.input, todos, profiles);
+        if let Some(prefix) = common_prefix(&candidates) {
+            if prefix.len() > self.input.len() {
+                self.input = prefix;
+            }
+        }
+        candidates
+    }
+
+    pub fn prev(&mut self, history: &[String]) {
+        let ind = match self.history_ind {
+            Some(ind) => ind.saturating_sub(1),
+            None if history.is_empty() => return,
+            None => history.len() - 1,
+        };
+        self.history_ind = Some(ind);
+        self.input = history[ind].clone();
+    }
+
+    pub fn next(&mut self, history: &[String]) {
+        if let Some(ind) = self.history_ind {
+            if ind + 1 < history.len() {
+                self.history_ind = Some(ind + 1);
+                self.input = history[ind + 1].clone();
+            } else {
+                self.history_ind = None;
+                self.input.clear();
+            }
+        }
+    }
+}
+
+/// Lists every action, fuzzy matched against the query.
+#[derive(Debug, Default)]
+pub struct Palette {
+    pub query: String,
+    pub selected: usize,
+}
+
+impl Palette {
+    /// Matching actions, best first.
+    pub fn matches(&self) -> Vec<Action> {
+        let mut scored: Vec<(i64, Action)> = Action::ALL
+            .iter()
+            .filter_map(|action| {
+                let text = format!("{} {}", action.name(), action.description());
+                fuzzy_score(&self.query, &text).map(|score| (score, *action))
+            })
+            .collect();
+        // stable, so equal scores keep the order of `Action::ALL`.
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_, action)| action).collect()
+    }
+
+    pub fn selected_action(&self) -> Option<Action> {
+        self.matches().get(self.selected).copied()
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.query.push(c);
+        self.selected = 0;
+    }
+
+    pub fn backspace(&mut self) {
+        self.query.pop();
+        self.selected = 0;
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        let len = self.matches().len();
+        self.selected = (self.selected + 1).min(len.saturating_sub(1));
+    }
+}
+
+/// Whether `query` is a subsequence of `text`, ignoring case.
+/// Runs of chars and chars starting a word score higher.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut from = 0;
+    for c in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = (from..text.len()).find(|ind| text[*ind] == c)?;
+        score += 1;
+        if found > 0 && found == from {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        from = found + 1;
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("archive").unwrap(),
+            LineCommand::Action(Action::Archive)
+        );
+        assert_eq!(parse(" add ").unwrap(), LineCommand::Action(Action::Add));
+        assert_eq!(
+            parse("add write doc pomorks 2").unwrap(),
+            LineCommand::Cli(Command::Add("write doc pomorks 2".to_string()))
+        );
+        assert_eq!(
+            parse("done write doc").unwrap(),
+            LineCommand::Cli(Command::Done("write doc".to_string()))
+        );
+        assert_eq!(
+            parse("stats --week").unwrap(),
+            LineCommand::Cli(Command::Stats { week: true })
+        );
+        assert_eq!(
+            parse("jump review").unwrap(),
+            LineCommand::Jump("review".to_string())
+        );
+        assert_eq!(
+            parse("sort recent").unwrap(),
+            LineCommand::Sort(SortMode::Recent)
+        );
+        assert_eq!(
+            parse("project pomorks").unwrap(),
+            LineCommand::Project("pomorks".to_string())
+        );
+        assert_eq!(
+            parse("profile deep").unwrap(),
+            LineCommand::Profile("deep".to_string())
+        );
+        assert!(parse("profile").is_err());
+        assert_eq!(
+            parse("export report.csv").unwrap(),
+            LineCommand::Cli(Command::Export {
+                path: "report.csv".into(),
+                week: false
+            })
+        );
+        assert!(parse("sort random").is_err());
+        assert!(parse("").is_err());
+        assert!(parse("list --all").is_err());
+        assert!(parse("fly").is_err());
+    }
+
+    #[test]
+    fn test_complete() {
+        let todos = vec![
+            TodoItem::from_str("write doc pomorks 2").unwrap(),
+            TodoItem::from_str("wrap tui pomorks 1").unwrap(),
+        ];
+
+        let mut line = CommandLine::default();
+        "sk".chars().for_each(|c| line.insert(c));
+        assert_eq!(line.complete(&todos, &[]), vec!["skip_break"]);
+        assert_eq!(line.input, "skip_break");
+
+        let mut line = CommandLine::default();
+        "jump w".chars().for_each(|c| line.insert(c));
+        assert_eq!(line.complete(&todos, &[]).len(), 2);
+        assert_eq!(line.input, "jump wr");
+
+        let mut line = CommandLine::default();
+        "tag d".chars().for_each(|c| line.insert(c));
+        assert_eq!(line.complete(&todos, &[]), vec!["tag doc"]);
+
+        let profiles = vec!["deep".to_string(), "light".to_string()];
+        let mut line = CommandLine::default();
+        "profile d".chars().for_each(|c| line.insert(c));
+        assert_eq!(line.complete(&todos, &profiles), vec!["profile deep"]);
+    }
+
+    #[test]
+    fn test_history() {
+        let history = vec!["archive".to_string(), "stats".to_string()];
+        let mut line = CommandLine::default();
+        line.prev(&history);
+        assert_eq!(line.input, "stats");
+        line.prev(&history);
+        line.prev(&history);
+        assert_eq!(line.input, "archive");
+        line.next(&history);
+        line.next(&history);
+        assert_eq!(line.input, "");
+    }
+
+    #[test]
+    fn test_history_file() {
+        let path = std::env::temp_dir().join(format!("pomorks-history-{}", std::process::id()));
+        assert!(read_history(&path).unwrap().is_empty());
+
+        let history: Vec<String> = (0..HISTORY_LIMIT + 5)
+            .map(|ind| format!("jump {}", ind))
+            .collect();
+        write_history(&path, &history).unwrap();
+        let read = read_history(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(read.len(), HISTORY_LIMIT);
+        assert_eq!(read.first().unwrap(), "jump 5");
+        assert_eq!(read.last(), history.last());
+    }
+
+    #[test]
+    fn test_palette() {
+        assert!(fuzzy_score("sb", "skip_break").is_some());
+        assert!(fuzzy_score("bs", "skip_break").is_none());
+        assert!(
+            fuzzy_score("arch", "archive").unwrap()
+                > fuzzy_score("arch", "start_pause").unwrap_or(0)
+        );
+
+        let mut palette = Palette::default();
+        assert_eq!(palette.matches().len(), Action::ALL.len());
+        "arch".chars().for_each(|c| palette.insert(c));
+        assert_eq!(palette.selected_action(), Some(Action::Archive));
+    }
+}
@@ -0,0 +1,746 @@
+use crate::tui::UpdateInfo;
+use anyhow::{anyhow, Result};
+use chrono::prelude::*;
+use pomorks_core::engine::{Engine, EngineEvent};
+use pomorks_daemon::protocol::{DaemonStatus, Event, Request};
+use pomorks_daemon::remote::Remote;
+use pomorks_data_manage::config::TimerConfig;
+use pomorks_data_manage::data_manage_trait::{DataManage, TaskLogJson, UNASSIGNED_ID};
+use pomorks_data_manage::hooks::{HookEvent, HookPayload, HookRunner};
+use pomorks_data_manage::status::StatusSnapshot;
+use pomorks_data_manage::todo::{State, TodoItem, TodoList};
+use pomorks_data_manage::webhook::{WebhookNotifier, WebhookPayload};
+use std::collections::BTreeMap;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SaveStatus {
+    Saved,
+    Pending,
+    Failed(String),
+}
+
+pub struct Controller<'a> {
+    pub data_manager: &'a dyn DataManage,
+    pub todo_list: TodoList,
+    pub engine: Engine,
+    // the todo the running phase is credited to.
+    pub focus: Option<String>,
+    pub todays_executed_count: i64,
+    pub task_log: Vec<TaskLogJson>,
+    pub save_status: SaveStatus,
+    pub hooks: HookRunner,
+    pub webhook: WebhookNotifier,
+    // the profile switched to with `:profile`, if any.
+    pub profile: Option<String>,
+    profiles: BTreeMap<String, TimerConfig>,
+    last_change: Option<Instant>,
+    debounce: Duration,
+    daemon: Option<DaemonLink>,
+}
+
+// a running `pomorksd` owns the timer; `engine` then only mirrors it.
+struct DaemonLink {
+    remote: Remote,
+    events: Receiver<Event>,
+    timer_config: TimerConfig,
+}
+
+impl<'a> Controller<'a> {
+    pub fn new(
+        data_manager: &'a dyn DataManage,
+        todo_list: TodoList,
+        engine: Engine,
+        focus: Option<String>,
+        debounce: Duration,
+        hooks: HookRunner,
+    ) -> Result<Controller<'a>> {
+        Ok(Controller {
+            data_manager,
+            todo_list,
+            engine,
+            focus,
+            todays_executed_count: data_manager.get_executed_count_by_day(&Local::now())?,
+            task_log: data_manager.get_log_all()?,
+            save_status: SaveStatus::Saved,
+            hooks,
+            webhook: WebhookNotifier::default(),
+            profile: None,
+            profiles: BTreeMap::new(),
+            last_change: None,
+            debounce,
+            daemon: None,
+        })
+    }
+
+    pub fn with_webhook(self, webhook: WebhookNotifier) -> Self {
+        Controller { webhook, ..self }
+    }
+
+    pub fn with_profiles(self, profiles: BTreeMap<String, TimerConfig>) -> Self {
+        Controller { profiles, ..self }
+    }
+
+    pub fn profile_names(&self) -> Vec<String> {
+        self.profiles.keys().cloned().collect()
+    }
+
+    /// The settings of the current profile; `None` keeps those of `[timer]`.
+    pub fn timer_profile(&self) -> Option<&TimerConfig> {
+        self.profile
+            .as_ref()
+            .and_then(|profile| self.profiles.get(profile))
+    }
+
+    /// Sends the timer requests to the daemon and draws its timer from `status`.
+    pub fn with_daemon(
+        mut self,
+        remote: Remote,
+        events: Receiver<Event>,
+        timer_config: TimerConfig,
+        status: DaemonStatus,
+    ) -> Self {
+        self.daemon = Some(DaemonLink {
+            remote,
+            events,
+            timer_config,
+        });
+        self.mirror(status);
+        self
+    }
+
+    pub fn dispatch(&mut self, info: UpdateInfo) -> Result<()> {
+        let now = Local::now();
+        match info {
+            UpdateInfo::AddNewTodo(todo) => {
+                self.todo_list.add_todo(todo)?;
+                self.mark_changed();
+            }
+            UpdateInfo::EditTodo(todo) => {
+                if self.todo_list.get_todo(&todo.id).is_none() {
+                    return Err(anyhow!("todo {} is not exist.", todo.id));
+                }
+                self.todo_list.insert_todo(todo)?;
+                self.mark_changed();
+            }
+            UpdateInfo::DeleteTodo(todo) => {
+                self.data_manager.delete_todo(&mut self.todo_list, &todo)?;
+                if self.focus.as_ref() == Some(&todo.id) {
+                    self.focus = None;
+                    if self.daemon.is_some() {
+                        self.drive(Request::Focus { id: None }, now)?;
+                    } else if self.engine.is_running() || self.engine.is_paused() {
+                        self.write_task_dealing()?;
+                    }
+                }
+                self.flush_now()?;
+                self.refresh_task_log()?;
+            }
+            UpdateInfo::ChangeFinishStatus(todo) => {
+                let mut todo = todo;
+                todo.set_finished(!todo.finished);
+                self.todo_list.insert_todo(todo.clone())?;
+                self.mark_changed();
+                if todo.finished {
+                    self.fire_hook(HookEvent::TodoFinished, self.engine.state(), vec![todo]);
+                }
+            }
+            UpdateInfo::MoveTodo(todo, status) => {
+                let mut moved = todo.clone();
+                moved.move_to(status);
+                self.todo_list.insert_todo(moved.clone())?;
+                self.mark_changed();
+                if moved.finished && !todo.finished {
+                    self.fire_hook(HookEvent::TodoFinished, self.engine.state(), vec![moved]);
+                }
+            }
+            UpdateInfo::ArchiveFinishedTodo() => {
+                let finished_todo = self.todo_list.drain_finished_todo();
+                self.data_manager.archive_todo(finished_todo.clone())?;
+                if !finished_todo.is_empty() {
+                    self.fire_hook(HookEvent::TodoArchived, self.engine.state(), finished_todo);
+                }
+                self.flush_now()?;
+            }
+            UpdateInfo::AddTaskLog(log) => {
+                self.data_manager.add_task_log(&log)?;
+                self.todo_list.increment_executed_count(&log.id);
+                self.mark_changed();
+                self.refresh_task_log()?;
+            }
+            UpdateInfo::ReassignTaskLog(log, todo) => {
+                self.data_manager.reassign_task_log(&log.log_id, &todo.id)?;
+                if log.is_completed_work() {
+                    self.todo_list.decrement_executed_count(&log.id);
+                    self.todo_list.increment_executed_count(&todo.id);
+                    self.mark_changed();
+                }
+                self.refresh_task_log()?;
+            }
+            UpdateInfo::DeleteTaskLog(log) => {
+                self.data_manager.delete_task_log(&log.log_id)?;
+                if log.is_completed_work() {
+                    self.todo_list.decrement_executed_count(&log.id);
+                    self.mark_changed();
+                }
+                self.refresh_task_log()?;
+            }
+            // the focus of a running phase is not moved by starting another todo.
+            UpdateInfo::StartTodo(_) if self.engine.is_running() => {
+                return Err(anyhow!("a phase is already running."));
+            }
+            UpdateInfo::StartTodo(id) => {
+                let previous = self.focus.clone();
+                let focus = Some(id).filter(|id| id != UNASSIGNED_ID);
+                if self.daemon.is_some() {
+                    if self
+                        .todo_list
+                        .move_focus(previous.as_deref(), focus.as_deref())
+                    {
+                        self.mark_changed();
+                    }
+                    // the daemon looks the todo up in the backend.
+                    self.flush_now()?;
+                    self.drive(Request::Focus { id: focus }, now)?;
+                    self.drive(Request::Start, now)?;
+                } else {
+                    // a phase that could not start leaves the focus and the board as they were.
+                    let events = self.engine.start(now)?;
+                    self.focus = focus;
+                    if self
+                        .todo_list
+                        .move_focus(previous.as_deref(), self.focus.as_deref())
+                    {
+                        self.mark_changed();
+                    }
+                    self.on_engine_events(&events, now)?;
+                }
+            }
+            UpdateInfo::PausePhase() => self.drive(Request::Pause, now)?,
+            UpdateInfo::FinishPhase() => self.drive(Request::Finish, now)?,
+            UpdateInfo::SkipPhase() => self.drive(Request::Skip, now)?,
+            UpdateInfo::MovePrevState() => self.drive(Request::Prev, now)?,
+            UpdateInfo::ExtendPhase(extend_time) => self.drive(
+                Request::Extend {
+                    seconds: extend_time,
+                },
+                now,
+            )?,
+            UpdateInfo::ToggleAutoStart() if self.daemon.is_some() => {
+                return Err(anyhow!(
+                    "pomorksd runs the timer; set auto_start_next in its config."
+                ));
+            }
+            UpdateInfo::ToggleAutoStart() => {
+                let auto_start = !self.engine.auto_start();
+                self.engine.set_auto_start(auto_start);
+            }
+            UpdateInfo::SetProfile(_) if self.daemon.is_some() => {
+                return Err(anyhow!(
+                    "pomorksd runs the timer; set its settings in its config."
+                ));
+            }
+            UpdateInfo::SetProfile(profile) => {
+                let timer_config = self
+                    .profiles
+                    .get(&profile)
+                    .ok_or_else(|| anyhow!("unknown profile: {}", profile))?;
+                self.engine.set_timer_config(timer_config);
+                self.profile = Some(profile);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Advances the timer; the events are returned for notifications.
+    pub fn tick(&mut self, now: DateTime<Local>) -> Result<Vec<EngineEvent>> {
+        // the daemon ends its phases and notifies about them itself.
+        let events = match self.daemon {
+            Some(_) => vec![],
+            None => self.engine.tick(now),
+        };
+        self.on_engine_events(&events, now)?;
+        self.webhook.retry_if_due(now);
+
+        Ok(events)
+    }
+
+    /// Picks up what the daemon did since the last call; true when the timer or the todos changed.
+    pub fn sync_with_daemon(&mut self) -> Result<bool> {
+        let daemon = match self.daemon.as_mut() {
+            Some(daemon) => daemon,
+            None => return Ok(false),
+        };
+        let mut credited = vec![];
+        let mut finished = false;
+        loop {
+            match daemon.events.try_recv() {
+                Ok(Event::PhaseFinished {
+                    finished: finished_state,
+                    skipped,
+                    status,
+                    ..
+                }) => {
+                    finished = true;
+                    if let (State::WORK(_), false) = (finished_state, skipped) {
+                        credited.extend(status.focus);
+                    }
+                }
+                Ok(_) => {}
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    return Err(anyhow!(
+                        "pomorksd has stopped; restart to run the timer here."
+                    ))
+                }
+            }
+        }
+        let status = daemon.remote.request(&Request::Status)?;
+
+        // the daemon wrote the count to the backend; the list here must not write the old one back.
+        for id in &credited {
+            self.todo_list.increment_executed_count(id);
+            self.mark_changed();
+        }
+        if finished {
+            self.refresh_task_log()?;
+        }
+        let changed = finished || status.phase != self.engine.task_dealing(self.focus_id());
+        self.mirror(status);
+
+        Ok(changed)
+    }
+
+    // write-behind: todos are written once no change came in for `debounce`.
+    pub fn flush_if_due(&mut self) -> Result<()> {
+        match self.last_change {
+            Some(changed) if changed.elapsed() >= self.debounce => self.flush(),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        match self.data_manager.write_all_todo(self.todo_list.clone()) {
+            Ok(()) => {
+                self.last_change = None;
+                self.save_status = SaveStatus::Saved;
+                Ok(())
+            }
+            Err(e) => {
+                // retried after the next debounce.
+                self.last_change = Some(Instant::now());
+                self.save_status = SaveStatus::Failed(e.to_string());
+                Err(e)
+            }
+        }
+    }
+
+    pub fn status_snapshot(&self) -> StatusSnapshot {
+        StatusSnapshot {
+            state: self.engine.state().clone(),
+            start_time: self.engine.start_time(),
+            extended_seconds: self.engine.extended_seconds(),
+            paused_seconds: self.engine.paused_seconds(),
+            id: self.focus.clone(),
+            title: self
+                .focus
+                .as_ref()
+                .and_then(|id| self.todo_list.get_todo(id))
+                .map(|todo| todo.title)
+                .unwrap_or_default(),
+            todays_count: self.todays_executed_count,
+            written: Local::now(),
+        }
+    }
+
+    pub fn save(mut self) -> Result<()> {
+        if self.daemon.is_none() && !self.engine.is_running() && !self.engine.is_paused() {
+            self.data_manager.delete_task_dealing()?;
+        }
+        self.flush()
+    }
+
+    fn write_task_dealing(&self) -> Result<()> {
+        self.data_manager
+            .write_task_dealing(&self.engine.task_dealing(self.focus_id()))
+    }
+
+    fn focus_id(&self) -> &str {
+        self.focus.as_deref().unwrap_or(UNASSIGNED_ID)
+    }
+
+    // a timer request goes to the daemon when one runs, to the engine here otherwise.
+    fn drive(&mut self, request: Request, now: DateTime<Local>) -> Result<()> {
+        if let Some(daemon) = self.daemon.as_mut() {
+            let status = daemon.remote.request(&request)?;
+            self.mirror(status);
+            return Ok(());
+        }
+
+        let events = match request {
+            Request::Start => self.engine.start(now)?,
+            Request::Pause => self.engine.pause(now)?,
+            Request::Finish => self.engine.finish(now)?,
+            Request::Skip => self.engine.skip(now),
+            Request::Prev => self.engine.move_prev()?,
+            Request::Extend { seconds } => self.engine.extend(seconds)?,
+            _ => vec![],
+        };
+        self.on_engine_events(&events, now)
+    }
+
+    fn mirror(&mut self, status: DaemonStatus) {
+        if let Some(daemon) = &self.daemon {
+            self.engine = status.engine(&daemon.timer_config, Local::now());
+            self.focus = status.focus;
+        }
+    }
+
+    // for a change whose other half went to the backend right away, e.g. the reassigned logs
+    // of a deleted todo; a debounced write could leave the two out of step after a crash.
+    fn flush_now(&mut self) -> Result<()> {
+        self.mark_changed();
+        self.flush()
+    }
+
+    fn mark_changed(&mut self) {
+        self.last_change = Some(Instant::now());
+        self.save_status = SaveStatus::Pending;
+    }
+
+    fn on_engine_events(&mut self, events: &[EngineEvent], now: DateTime<Local>) -> Result<()> {
+        for event in events {
+            match event {
+                EngineEvent::PhaseStarted { state } => {
+                    self.write_task_dealing()?;
+                    self.fire_phase_hook(state, true);
+                }
+                EngineEvent::Paused { .. } | EngineEvent::Extended { .. } => {
+                    self.write_task_dealing()?
+                }
+                EngineEvent::PhaseFinished {
+                    finished,
+                    started,
+                    skipped,
+                    actual_seconds,
+                    planned_seconds,
+                    ..
+                } => {
+                    let id = match (finished, &self.focus) {
+                        (State::WORK(_), Some(focus)) if *started => focus.clone(),
+                        _ => UNASSIGNED_ID.to_string(),
+                    };
+                    if let (State::WORK(_), false) = (finished, skipped) {
+                        self.todo_list.increment_executed_count(&id);
+                        self.mark_changed();
+                    }
+
+                    let log = TaskLogJson::new(&id, &now, finished, *actual_seconds, *skipped)
+                        .extended(planned_seconds - State::get_limit_time(finished) as i64);
+                    self.data_manager.add_task_log(&log)?;
+                    self.data_manager.delete_task_dealing()?;
+                    self.webhook.session_completed(WebhookPayload::from_session(
+                        &log,
+                        finished,
+                        self.todo_list.get_todo(&id).as_ref(),
+                    ));
+                    // skipped before it started, the phase fired no start hook to pair an end hook with.
+                    if *started {
+                        self.fire_phase_hook(finished, false);
+                    }
+                    self.refresh_task_log()?;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fire_phase_hook(&self, state: &State, is_start: bool) {
+        let todos = match state {
+            State::WORK(_) => self
+                .focus
+                .as_ref()
+                .and_then(|id| self.todo_list.get_todo(id))
+                .into_iter()
+                .collect(),
+            _ => vec![],
+        };
+        self.fire_hook(HookEvent::from_phase(state, is_start), state, todos);
+    }
+
+    fn fire_hook(&self, event: HookEvent, state: &State, todos: Vec<TodoItem>) {
+        self.hooks.fire(HookPayload::new(event, state, todos));
+    }
+
+    fn refresh_task_log(&mut self) -> Result<()> {
+        self.todays_executed_count = self.data_manager.get_executed_count_by_day(&Local::now())?;
+        self.task_log = self.data_manager.get_log_all()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use pomorks_data_manage::config::TimerConfig;
+    use pomorks_data_manage::data_manage_trait::TaskDealing;
+    use pomorks_data_manage::recovery::RecoveryLogJson;
+    use pomorks_data_manage::todo::TodoStatus;
+    use std::cell::{Cell, RefCell};
+    use std::str::FromStr;
+
+    // keeps only the task logs and how often todos were written.
+    #[derive(Default)]
+    pub(crate) struct StandInDataManage {
+        written: Cell<usize>,
+        task_logs: RefCell<Vec<TaskLogJson>>,
+    }
+
+    impl DataManage for StandInDataManage {
+        fn write_all_todo(&self, _todo_list: TodoList) -> Result<()> {
+            self.written.set(self.written.get() + 1);
+            Ok(())
+        }
+        fn read_all_todo(&self) -> Result<Option<TodoList>> {
+            Ok(None)
+        }
+        fn archive_todo(&self, _archived_todo_list: Vec<TodoItem>) -> Result<()> {
+            Ok(())
+        }
+        fn write_task_dealing(&self, _task_dealing: &TaskDealing) -> Result<()> {
+            Ok(())
+        }
+        fn read_task_dealing(&self) -> Result<TaskDealing> {
+            Ok(no_task_dealing())
+        }
+        fn delete_task_dealing(&self) -> Result<()> {
+            Ok(())
+        }
+        fn add_task_log(&self, task_log: &TaskLogJson) -> Result<()> {
+            self.task_logs.borrow_mut().push(task_log.clone());
+            Ok(())
+        }
+        fn get_executed_count_by_day(&self, _date: &DateTime<Local>) -> Result<i64> {
+            Ok(0)
+        }
+        fn get_log_all(&self) -> Result<Vec<TaskLogJson>> {
+            Ok(self.task_logs.borrow().clone())
+        }
+        fn reassign_task_log(&self, log_id: &str, id: &str) -> Result<()> {
+            for log in self.task_logs.borrow_mut().iter_mut() {
+                if log.log_id == log_id {
+                    log.id = id.to_string();
+                }
+            }
+            Ok(())
+        }
+        fn delete_task_log(&self, _log_id: &str) -> Result<()> {
+            Ok(())
+        }
+        fn add_recovery_log(&self, _recovery_log: &RecoveryLogJson) -> Result<()> {
+            Ok(())
+        }
+        fn migrate(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn no_task_dealing() -> TaskDealing {
+        TaskDealing::default()
+    }
+
+    pub(crate) fn controller_of(
+        data_manager: &StandInDataManage,
+        todo_list: TodoList,
+        debounce: Duration,
+    ) -> Controller<'_> {
+        Controller::new(
+            data_manager,
+            todo_list,
+            Engine::new(State::WORK(1), &TimerConfig::default()),
+            None,
+            debounce,
+            HookRunner::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_autosave_is_debounced() {
+        let data_manager = StandInDataManage::default();
+        let mut controller =
+            controller_of(&data_manager, TodoList::new(), Duration::from_millis(50));
+
+        controller
+            .dispatch(UpdateInfo::AddNewTodo(
+                TodoItem::from_str("first tag project 1").unwrap(),
+            ))
+            .unwrap();
+        controller
+            .dispatch(UpdateInfo::AddNewTodo(
+                TodoItem::from_str("second tag project 1").unwrap(),
+            ))
+            .unwrap();
+        controller.flush_if_due().unwrap();
+        assert_eq!(data_manager.written.get(), 0);
+        assert_eq!(controller.save_status, SaveStatus::Pending);
+
+        std::thread::sleep(Duration::from_millis(60));
+        controller.flush_if_due().unwrap();
+        controller.flush_if_due().unwrap();
+        assert_eq!(data_manager.written.get(), 1);
+        assert_eq!(controller.save_status, SaveStatus::Saved);
+    }
+
+    #[test]
+    fn test_finished_work_is_credited_to_the_focus() {
+        let data_manager = StandInDataManage::default();
+        let todo = TodoItem::from_str("write doc pomorks 2").unwrap();
+        let mut todo_list = TodoList::new();
+        todo_list.add_todo(todo.clone()).unwrap();
+        let mut controller = controller_of(&data_manager, todo_list, Duration::from_secs(60));
+
+        controller
+            .dispatch(UpdateInfo::StartTodo(todo.id.clone()))
+            .unwrap();
+        assert!(controller.engine.is_running());
+        let limit = chrono::Duration::seconds(controller.engine.limit_seconds());
+        let events = controller.tick(Local::now() + limit).unwrap();
+        assert!(matches!(
+            events.as_slice(),
+            [EngineEvent::PhaseFinished { .. }]
+        ));
+
+        assert_eq!(controller.engine.state(), &State::BREAK(1));
+        assert_eq!(
+            controller
+                .todo_list
+                .get_todo(&todo.id)
+                .unwrap()
+                .executed_count,
+            1
+        );
+        let task_logs = data_manager.task_logs.borrow();
+        assert_eq!(task_logs.len(), 1);
+        assert_eq!(task_logs[0].id, todo.id);
+        assert!(task_logs[0].is_completed_work());
+
+        // skipping the break credits nobody.
+        drop(task_logs);
+        controller.dispatch(UpdateInfo::SkipPhase()).unwrap();
+        assert_eq!(controller.engine.state(), &State::WORK(2));
+        assert_eq!(data_manager.task_logs.borrow()[1].id, UNASSIGNED_ID);
+    }
+
+    #[test]
+    fn test_started_todo_moves_to_doing() {
+        let data_manager = StandInDataManage::default();
+        let todo = TodoItem::from_str("write doc pomorks 2").unwrap();
+        let mut todo_list = TodoList::new();
+        todo_list.add_todo(todo.clone()).unwrap();
+        let mut controller = controller_of(&data_manager, todo_list, Duration::from_secs(60));
+
+        controller
+            .dispatch(UpdateInfo::StartTodo(todo.id.clone()))
+            .unwrap();
+        let started = controller.todo_list.get_todo(&todo.id).unwrap();
+        assert_eq!(started.column(), TodoStatus::Doing);
+
+        // the next todo worked on takes its place in doing.
+        let next = TodoItem::from_str("review doc pomorks 1").unwrap();
+        controller.todo_list.add_todo(next.clone()).unwrap();
+        controller.dispatch(UpdateInfo::PausePhase()).unwrap();
+        controller
+            .dispatch(UpdateInfo::StartTodo(next.id.clone()))
+            .unwrap();
+        let column = |id: &str| controller.todo_list.get_todo(id).unwrap().column();
+        assert_eq!(column(&todo.id), TodoStatus::Today);
+        assert_eq!(column(&next.id), TodoStatus::Doing);
+        let started = controller.todo_list.get_todo(&todo.id).unwrap();
+
+        controller
+            .dispatch(UpdateInfo::MoveTodo(started, TodoStatus::Done))
+            .unwrap();
+        assert!(controller.todo_list.get_todo(&todo.id).unwrap().finished);
+    }
+
+    #[test]
+    fn test_todo_not_started_keeps_the_focus() {
+        let data_manager = StandInDataManage::default();
+        let todo = TodoItem::from_str("write doc pomorks 2").unwrap();
+        let next = TodoItem::from_str("review doc pomorks 1").unwrap();
+        let mut todo_list = TodoList::new();
+        todo_list.add_todo(todo.clone()).unwrap();
+        todo_list.add_todo(next.clone()).unwrap();
+        let mut controller = controller_of(&data_manager, todo_list, Duration::from_secs(60));
+
+        controller
+            .dispatch(UpdateInfo::StartTodo(todo.id.clone()))
+            .unwrap();
+        // the phase is still running.
+        assert!(controller
+            .dispatch(UpdateInfo::StartTodo(next.id.clone()))
+            .is_err());
+
+        assert_eq!(controller.focus, Some(todo.id.clone()));
+        let column = |id: &str| controller.todo_list.get_todo(id).unwrap().column();
+        assert_eq!(column(&todo.id), TodoStatus::Doing);
+        assert_eq!(column(&next.id), TodoStatus::Backlog);
+    }
+
+    #[test]
+    fn test_deleted_todo_leaves_its_logs_unassigned() {
+        let data_manager = StandInDataManage::default();
+        let todo = TodoItem::from_str("write doc pomorks 2").unwrap();
+        let mut todo_list = TodoList::new();
+        todo_list.add_todo(todo.clone()).unwrap();
+        let mut controller = controller_of(&data_manager, todo_list, Duration::from_secs(60));
+
+        let log = TaskLogJson::new(&todo.id, &Local::now(), &State::WORK(1), 1500, false);
+        controller
+            .dispatch(UpdateInfo::AddTaskLog(log.clone()))
+            .unwrap();
+        controller
+            .dispatch(UpdateInfo::StartTodo(todo.id.clone()))
+            .unwrap();
+        controller
+            .dispatch(UpdateInfo::DeleteTodo(todo.clone()))
+            .unwrap();
+
+        assert!(controller.todo_list.get_todo(&todo.id).is_none());
+        assert!(controller.focus.is_none());
+        assert_eq!(controller.save_status, SaveStatus::Saved);
+        assert!(controller.task_log[0].is_unassigned());
+        assert_eq!(controller.task_log[0].log_id, log.log_id);
+        assert!(controller.dispatch(UpdateInfo::DeleteTodo(todo)).is_err());
+    }
+
+    #[test]
+    fn test_profile_switches_the_timer_settings() {
+        let data_manager = StandInDataManage::default();
+        let deep = TimerConfig {
+            auto_start_next: true,
+            extend_minutes: 10,
+            ..TimerConfig::default()
+        };
+        let mut controller = controller_of(&data_manager, TodoList::new(), Duration::from_secs(60))
+            .with_profiles([("deep".to_string(), deep)].into_iter().collect());
+        assert!(!controller.engine.auto_start());
+
+        assert!(controller
+            .dispatch(UpdateInfo::SetProfile("shallow".to_string()))
+            .is_err());
+        assert!(controller.timer_profile().is_none());
+
+        controller
+            .dispatch(UpdateInfo::SetProfile("deep".to_string()))
+            .unwrap();
+        assert!(controller.engine.auto_start());
+        assert_eq!(controller.timer_profile().unwrap().extend_minutes, 10);
+    }
+}
@@ -0,0 +1,100 @@
+use anyhow::{anyhow, Context, Result};
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::process::Command;
+
+/// `$VISUAL`, then `$EDITOR`, then vi.
+pub fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|command| !command.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+/// Lets the user edit `text` as a Markdown file and returns the result.
+///
+/// `command` may carry arguments, e.g. `code --wait`; the file path is appended.
+pub fn edit_text(command: &str, name: &str, text: &str) -> Result<String> {
+    let (path, mut file) = create_temp_file(name)?;
+    let written = file.write_all(text.as_bytes());
+    drop(file);
+    if let Err(error) = written {
+        let _ = fs::remove_file(&path);
+        return Err(error.into());
+    }
+
+    let result = run_editor(command, &path).and_then(|_| Ok(fs::read_to_string(&path)?));
+    let _ = fs::remove_file(&path);
+
+    // editors end the file with a newline the detail never had.
+    Ok(result?.trim_end_matches(['\n', '\r']).to_string())
+}
+
+// a fresh file only we can read; a name someone else already took, or linked, is skipped.
+fn create_temp_file(name: &str) -> Result<(PathBuf, File)> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    for attempt in 0..100 {
+        let path = env::temp_dir().join(format!(
+            "pomorks-{}-{}-{}.md",
+            name,
+            std::process::id(),
+            attempt
+        ));
+        match options.open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(error) if error.kind() == ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error.into()),
+        }
+    }
+
+    Err(anyhow!("can't create a temporary file for {}.", name))
+}
+
+fn run_editor(command: &str, path: &std::path::Path) -> Result<()> {
+    let mut words = command.split_whitespace();
+    let program = words.next().ok_or_else(|| anyhow!("editor is not set."))?;
+    let status = Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .with_context(|| format!("can't start the editor: {}", command))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(anyhow!("{} exited with {}.", command, status))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_text() {
+        let edited = edit_text("sed -i s/draft/final/", "test-edit", "# draft\n").unwrap();
+        assert_eq!(edited, "# final");
+
+        assert!(edit_text("false", "test-fail", "text").is_err());
+    }
+
+    #[test]
+    fn test_temp_file_is_never_reused() {
+        let (first, _) = create_temp_file("test-temp").unwrap();
+        let (second, _) = create_temp_file("test-temp").unwrap();
+        assert_ne!(first, second);
+
+        fs::remove_file(first).unwrap();
+        fs::remove_file(second).unwrap();
+    }
+}
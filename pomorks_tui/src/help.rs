@@ -0,0 +1,150 @@
+//! The `?` overlay and the hint line, both read from the keymap in use.
+
+use crate::keymap::{Action, Keymap};
+
+/// What the keys act on right now; the hint line follows it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintContext {
+    TodoList,
+    Board,
+    TodoForm,
+    ConfirmDelete,
+    Statistics,
+    Unassigned,
+    Log,
+    AddLog,
+    Palette,
+    Search,
+    Help,
+}
+
+/// `(keys, description)` of every action, in the order of `Action::ALL`.
+pub fn help_rows(keymap: &Keymap) -> Vec<(String, &'static str)> {
+    Action::ALL
+        .iter()
+        .map(|action| (keymap.hint(*action), action.description()))
+        .collect()
+}
+
+/// `(keys, label)` pairs for the hint line.
+pub fn hints(context: HintContext, keymap: &Keymap) -> Vec<(String, &'static str)> {
+    let bound = |actions: &[(Action, &'static str)]| {
+        actions
+            .iter()
+            .map(|(action, label)| (keymap.hint(*action), *label))
+            .collect()
+    };
+    // text input reads these keys itself, whatever the keymap says.
+    let fixed = |keys: &[(&str, &'static str)]| {
+        keys.iter()
+            .map(|(key, label)| (key.to_string(), *label))
+            .collect()
+    };
+
+    match context {
+        HintContext::TodoList => bound(&[
+            (Action::Add, "add"),
+            (Action::Edit, "edit"),
+            (Action::Delete, "delete"),
+            (Action::Select, "focus"),
+            (Action::StartPause, "start/pause"),
+            (Action::ToggleFinished, "finish"),
+            (Action::Search, "search"),
+            (Action::CycleSort, "sort"),
+            (Action::GroupBy, "group"),
+            (Action::NextTab, "next tab"),
+            (Action::CommandLine, "command"),
+            (Action::Help, "help"),
+            (Action::Quit, "quit"),
+        ]),
+        HintContext::Board => bound(&[
+            (Action::ColumnRight, "next column"),
+            (Action::MoveLeft, "move left"),
+            (Action::MoveRight, "move right"),
+            (Action::Select, "focus"),
+            (Action::Add, "add"),
+            (Action::Edit, "edit"),
+            (Action::StartPause, "start/pause"),
+            (Action::NextTab, "next tab"),
+            (Action::Help, "help"),
+            (Action::Quit, "quit"),
+        ]),
+        HintContext::TodoForm => fixed(&[
+            ("tab/up/down", "field"),
+            ("enter", "save"),
+            ("esc", "cancel"),
+        ]),
+        HintContext::ConfirmDelete => fixed(&[("y", "delete"), ("any other key", "cancel")]),
+        HintContext::Statistics => bound(&[
+            (Action::NextTab, "next tab"),
+            (Action::StartPause, "start/pause"),
+            (Action::Help, "help"),
+            (Action::Quit, "quit"),
+        ]),
+        HintContext::Unassigned => bound(&[
+            (Action::Down, "next log"),
+            (Action::TodoDown, "next todo"),
+            (Action::Select, "assign"),
+            (Action::NextTab, "next tab"),
+            (Action::Help, "help"),
+            (Action::Quit, "quit"),
+        ]),
+        HintContext::Log => bound(&[
+            (Action::Down, "next log"),
+            (Action::TodoDown, "next todo"),
+            (Action::Select, "assign"),
+            (Action::Add, "add"),
+            (Action::Delete, "delete"),
+            (Action::NextTab, "next tab"),
+            (Action::Help, "help"),
+            (Action::Quit, "quit"),
+        ]),
+        HintContext::AddLog => fixed(&[
+            ("YYYY/MM/DD HH:MM", "start of the pomodoro"),
+            ("enter", "add"),
+            ("esc", "cancel"),
+        ]),
+        HintContext::Palette => {
+            fixed(&[("up/down", "select"), ("enter", "run"), ("esc", "cancel")])
+        }
+        HintContext::Search => fixed(&[("enter", "keep"), ("esc", "clear")]),
+        HintContext::Help => fixed(&[("any key", "close")]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pomorks_data_manage::config::KeysConfig;
+
+    #[test]
+    fn test_help_follows_the_keymap() {
+        let rows = help_rows(&Keymap::default());
+        assert_eq!(rows.len(), Action::ALL.len());
+        assert!(rows.contains(&("ctrl+c/q".to_string(), "quit")));
+
+        let mut config = KeysConfig::default();
+        config
+            .bindings
+            .insert("quit".to_string(), vec!["ctrl+q".to_string()]);
+        let rows = help_rows(&Keymap::from_config(&config).unwrap());
+        assert!(rows.contains(&("ctrl+q".to_string(), "quit")));
+    }
+
+    #[test]
+    fn test_hints_follow_the_context() {
+        let keymap = Keymap::default();
+        let labels = |context| -> Vec<&str> {
+            hints(context, &keymap)
+                .into_iter()
+                .map(|(_, label)| label)
+                .collect()
+        };
+
+        assert!(labels(HintContext::TodoList).contains(&"add"));
+        assert!(labels(HintContext::TodoForm).contains(&"save"));
+        assert!(!labels(HintContext::Statistics).contains(&"add"));
+        assert!(hints(HintContext::Board, &keymap).contains(&(">".to_string(), "move right")));
+        assert!(hints(HintContext::TodoList, &keymap).contains(&("a".to_string(), "add")));
+    }
+}
@@ -0,0 +1,606 @@
+use anyhow::{anyhow, Error, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use pomorks_data_manage::config::KeysConfig;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Everything a key can do outside of text input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    Help,
+    CommandLine,
+    Palette,
+    NextTab,
+    /// the list of the tab: todos, or logs in the log tabs.
+    Up,
+    Down,
+    /// the todos, also in the log tabs where they are the reassign target.
+    TodoUp,
+    TodoDown,
+    Select,
+    Add,
+    Edit,
+    EditDetail,
+    Delete,
+    ToggleFinished,
+    Archive,
+    ToggleChart,
+    Search,
+    CycleSort,
+    HideFinished,
+    FilterTag,
+    FilterProject,
+    OverEstimate,
+    ClearFilter,
+    GroupBy,
+    ToggleGroup,
+    NextGroup,
+    PrevGroup,
+    /// the board's columns, and moving the card between them.
+    ColumnLeft,
+    ColumnRight,
+    MoveLeft,
+    MoveRight,
+    StartPause,
+    NextState,
+    PrevState,
+    SkipBreak,
+    ExtendPhase,
+    FinishPhase,
+    ToggleAutoStart,
+}
+
+impl Action {
+    pub const ALL: [Action; 39] = [
+        Action::Quit,
+        Action::Help,
+        Action::CommandLine,
+        Action::Palette,
+        Action::NextTab,
+        Action::Up,
+        Action::Down,
+        Action::TodoUp,
+        Action::TodoDown,
+        Action::Select,
+        Action::Add,
+        Action::Edit,
+        Action::EditDetail,
+        Action::Delete,
+        Action::ToggleFinished,
+        Action::Archive,
+        Action::ToggleChart,
+        Action::Search,
+        Action::CycleSort,
+        Action::HideFinished,
+        Action::FilterTag,
+        Action::FilterProject,
+        Action::OverEstimate,
+        Action::ClearFilter,
+        Action::GroupBy,
+        Action::ToggleGroup,
+        Action::NextGroup,
+        Action::PrevGroup,
+        Action::ColumnLeft,
+        Action::ColumnRight,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::StartPause,
+        Action::NextState,
+        Action::PrevState,
+        Action::SkipBreak,
+        Action::ExtendPhase,
+        Action::FinishPhase,
+        Action::ToggleAutoStart,
+    ];
+
+    /// The name used in `[keys.bindings]`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Help => "help",
+            Action::CommandLine => "command_line",
+            Action::Palette => "palette",
+            Action::NextTab => "next_tab",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::TodoUp => "todo_up",
+            Action::TodoDown => "todo_down",
+            Action::Select => "select",
+            Action::Add => "add",
+            Action::Edit => "edit",
+            Action::EditDetail => "edit_detail",
+            Action::Delete => "delete",
+            Action::ToggleFinished => "toggle_finished",
+            Action::Archive => "archive",
+            Action::ToggleChart => "toggle_chart",
+            Action::Search => "search",
+            Action::CycleSort => "cycle_sort",
+            Action::HideFinished => "hide_finished",
+            Action::FilterTag => "filter_tag",
+            Action::FilterProject => "filter_project",
+            Action::OverEstimate => "over_estimate",
+            Action::ClearFilter => "clear_filter",
+            Action::GroupBy => "group_by",
+            Action::ToggleGroup => "toggle_group",
+            Action::NextGroup => "next_group",
+            Action::PrevGroup => "prev_group",
+            Action::ColumnLeft => "column_left",
+            Action::ColumnRight => "column_right",
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::StartPause => "start_pause",
+            Action::NextState => "next_state",
+            Action::PrevState => "prev_state",
+            Action::SkipBreak => "skip_break",
+            Action::ExtendPhase => "extend_phase",
+            Action::FinishPhase => "finish_phase",
+            Action::ToggleAutoStart => "toggle_auto_start",
+        }
+    }
+
+    /// What the help overlay says about the action.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Help => "show this help",
+            Action::CommandLine => "run a command, e.g. `done <todo>` or `jump <todo>`",
+            Action::Palette => "find an action by name",
+            Action::NextTab => "next tab",
+            Action::Up => "move up (logs in the log tabs)",
+            Action::Down => "move down (logs in the log tabs)",
+            Action::TodoUp => "move up the todos",
+            Action::TodoDown => "move down the todos",
+            Action::Select => "focus the todo, or assign the log to it",
+            Action::Add => "add a todo, or a log in the log tab",
+            Action::Edit => "edit the todo",
+            Action::EditDetail => "edit the detail in $EDITOR",
+            Action::Delete => "delete the todo, or the log in the log tab",
+            Action::ToggleFinished => "finish or reopen the todo",
+            Action::Archive => "archive the finished todos",
+            Action::ToggleChart => "toggle the chart",
+            Action::Search => "search title, tag, project and detail",
+            Action::CycleSort => "next sort: priority, remaining, project, recent",
+            Action::HideFinished => "hide or show the finished todos",
+            Action::FilterTag => "show only the tag of the todo, or all tags",
+            Action::FilterProject => "show only the project of the todo, or all projects",
+            Action::OverEstimate => "show only the todos over their estimate",
+            Action::ClearFilter => "clear the search and the filters",
+            Action::GroupBy => "group: none, per project, per tag",
+            Action::ToggleGroup => "collapse or expand the group",
+            Action::NextGroup => "jump to the next group",
+            Action::PrevGroup => "jump to the group, or the previous one",
+            Action::ColumnLeft => "previous column of the board",
+            Action::ColumnRight => "next column of the board",
+            Action::MoveLeft => "move the card to the previous column",
+            Action::MoveRight => "move the card to the next column",
+            Action::StartPause => "start or pause the timer",
+            Action::NextState => "next phase (while stopped)",
+            Action::PrevState => "previous phase (while stopped)",
+            Action::SkipBreak => "skip the break",
+            Action::ExtendPhase => "extend the running phase",
+            Action::FinishPhase => "finish the running phase now",
+            Action::ToggleAutoStart => "toggle auto start",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Action::ALL
+            .iter()
+            .copied()
+            .find(|action| action.name() == name)
+    }
+}
+
+/// A key with its modifiers, e.g. `ctrl+s` or `space`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    // the char tells whether shift was held, so `shift+a` in the config is `A`.
+    fn normalized(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => KeyBinding {
+                code: KeyCode::Char(c.to_ascii_uppercase()),
+                modifiers: modifiers - KeyModifiers::SHIFT,
+            },
+            _ => KeyBinding { code, modifiers },
+        }
+    }
+}
+
+impl From<&KeyEvent> for KeyBinding {
+    fn from(event: &KeyEvent) -> Self {
+        KeyBinding::normalized(event.code, event.modifiers)
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = Error;
+    fn from_str(str: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut parts: Vec<&str> = str.split('+').collect();
+        // "+" alone, or "ctrl++", is the plus key.
+        if str.ends_with("++") || str == "+" {
+            parts.pop();
+            parts.pop();
+            parts.push("+");
+        }
+        let key = parts.pop().filter(|key| !key.is_empty());
+        let key = key.ok_or_else(|| anyhow!("key is empty: {}", str))?;
+        for modifier in parts {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(anyhow!("unknown modifier in {}: {}", str, modifier)),
+            };
+        }
+
+        let code = match key.to_lowercase().as_str() {
+            "space" => KeyCode::Char(' '),
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            lower => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => match lower.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                        Some(n) => KeyCode::F(n),
+                        None => return Err(anyhow!("unknown key: {}", str)),
+                    },
+                }
+            }
+        };
+
+        Ok(KeyBinding::normalized(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl+"),
+            (KeyModifiers::ALT, "alt+"),
+            (KeyModifiers::SHIFT, "shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}", name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "f{}", n),
+            KeyCode::Enter => write!(f, "enter"),
+            KeyCode::Esc => write!(f, "esc"),
+            KeyCode::Tab => write!(f, "tab"),
+            KeyCode::BackTab => write!(f, "backtab"),
+            KeyCode::Backspace => write!(f, "backspace"),
+            KeyCode::Delete => write!(f, "delete"),
+            KeyCode::Insert => write!(f, "insert"),
+            KeyCode::Up => write!(f, "up"),
+            KeyCode::Down => write!(f, "down"),
+            KeyCode::Left => write!(f, "left"),
+            KeyCode::Right => write!(f, "right"),
+            KeyCode::Home => write!(f, "home"),
+            KeyCode::End => write!(f, "end"),
+            KeyCode::PageUp => write!(f, "pageup"),
+            KeyCode::PageDown => write!(f, "pagedown"),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<KeyBinding, Action>,
+}
+
+impl Keymap {
+    pub fn preset(name: &str) -> Result<Self> {
+        Keymap::build(preset_keys(name)?)
+    }
+
+    /// The preset with `[keys.bindings]` on top of it.
+    pub fn from_config(config: &KeysConfig) -> Result<Self> {
+        let mut keys = preset_keys(&config.preset)?;
+        for (name, bound) in &config.bindings {
+            let action =
+                Action::from_name(name).ok_or_else(|| anyhow!("unknown action: {}", name))?;
+            match keys.iter_mut().find(|(a, _)| *a == action) {
+                Some((_, preset_keys)) => *preset_keys = bound.clone(),
+                None => keys.push((action, bound.clone())),
+            }
+        }
+
+        Keymap::build(keys)
+    }
+
+    // a key bound to two actions is an error, not a silent override.
+    fn build(keys: Vec<(Action, Vec<String>)>) -> Result<Self> {
+        let mut bindings = HashMap::new();
+        for (action, keys) in keys {
+            for key in keys {
+                let binding: KeyBinding = key.parse()?;
+                match bindings.insert(binding, action) {
+                    Some(other) if other != action => {
+                        return Err(anyhow!(
+                            "key {} is bound to both {} and {}.",
+                            binding,
+                            other.name(),
+                            action.name()
+                        ))
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(Keymap { bindings })
+    }
+
+    pub fn action(&self, event: &KeyEvent) -> Option<Action> {
+        self.bindings.get(&KeyBinding::from(event)).copied()
+    }
+
+    /// Keys of `action`, sorted for display.
+    pub fn keys(&self, action: Action) -> Vec<KeyBinding> {
+        let mut keys: Vec<KeyBinding> = self
+            .bindings
+            .iter()
+            .filter(|(_, a)| **a == action)
+            .map(|(binding, _)| *binding)
+            .collect();
+        keys.sort_by_key(|binding| binding.to_string());
+        keys
+    }
+
+    /// Keys of `action` for a title or a hint, e.g. `ctrl+c/q`.
+    pub fn hint(&self, action: Action) -> String {
+        let keys: Vec<String> = self
+            .keys(action)
+            .iter()
+            .map(|key| key.to_string())
+            .collect();
+        if keys.is_empty() {
+            "unbound".to_string()
+        } else {
+            keys.join("/")
+        }
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::preset("vim").expect("the vim preset is valid")
+    }
+}
+
+fn preset_keys(name: &str) -> Result<Vec<(Action, Vec<String>)>> {
+    let preset: &[(Action, &[&str])] = match name {
+        "vim" => &[
+            (Action::Quit, &["q", "ctrl+c"]),
+            (Action::Help, &["?"]),
+            (Action::CommandLine, &[":"]),
+            (Action::Palette, &["ctrl+p"]),
+            (Action::NextTab, &["tab"]),
+            (Action::Up, &["k"]),
+            (Action::Down, &["j"]),
+            (Action::TodoUp, &["up"]),
+            (Action::TodoDown, &["down"]),
+            (Action::Select, &["enter"]),
+            (Action::Add, &["a"]),
+            (Action::Edit, &["e"]),
+            (Action::EditDetail, &["v"]),
+            (Action::Delete, &["d"]),
+            (Action::ToggleFinished, &["f"]),
+            (Action::Archive, &["b"]),
+            (Action::ToggleChart, &["t"]),
+            (Action::Search, &["/"]),
+            (Action::CycleSort, &["S"]),
+            (Action::HideFinished, &["H"]),
+            (Action::FilterTag, &["#"]),
+            (Action::FilterProject, &["@"]),
+            (Action::OverEstimate, &["O"]),
+            (Action::ClearFilter, &["C"]),
+            (Action::GroupBy, &["g"]),
+            (Action::ToggleGroup, &["z"]),
+            (Action::NextGroup, &["}"]),
+            (Action::PrevGroup, &["{"]),
+            (Action::ColumnLeft, &["left"]),
+            (Action::ColumnRight, &["right"]),
+            (Action::MoveLeft, &["<"]),
+            (Action::MoveRight, &[">"]),
+            (Action::StartPause, &["space"]),
+            (Action::NextState, &["l"]),
+            (Action::PrevState, &["h"]),
+            (Action::SkipBreak, &["s"]),
+            (Action::ExtendPhase, &["x"]),
+            (Action::FinishPhase, &["n"]),
+            (Action::ToggleAutoStart, &["o"]),
+        ],
+        "arrows" => &[
+            (Action::Quit, &["q", "ctrl+c"]),
+            (Action::Help, &["?"]),
+            (Action::CommandLine, &[":"]),
+            (Action::Palette, &["ctrl+p"]),
+            (Action::NextTab, &["tab"]),
+            (Action::Up, &["up"]),
+            (Action::Down, &["down"]),
+            (Action::TodoUp, &["shift+up"]),
+            (Action::TodoDown, &["shift+down"]),
+            (Action::Select, &["enter"]),
+            (Action::Add, &["insert", "a"]),
+            (Action::Edit, &["f2", "e"]),
+            (Action::EditDetail, &["shift+f2", "v"]),
+            (Action::Delete, &["delete"]),
+            (Action::ToggleFinished, &["f"]),
+            (Action::Archive, &["b"]),
+            (Action::ToggleChart, &["t"]),
+            (Action::Search, &["/"]),
+            (Action::CycleSort, &["S"]),
+            (Action::HideFinished, &["H"]),
+            (Action::FilterTag, &["#"]),
+            (Action::FilterProject, &["@"]),
+            (Action::OverEstimate, &["O"]),
+            (Action::ClearFilter, &["C"]),
+            (Action::GroupBy, &["g"]),
+            (Action::ToggleGroup, &["z"]),
+            (Action::NextGroup, &["pagedown"]),
+            (Action::PrevGroup, &["pageup"]),
+            (Action::ColumnLeft, &["ctrl+left"]),
+            (Action::ColumnRight, &["ctrl+right"]),
+            (Action::MoveLeft, &["<"]),
+            (Action::MoveRight, &[">"]),
+            (Action::StartPause, &["space"]),
+            (Action::NextState, &["right"]),
+            (Action::PrevState, &["left"]),
+            (Action::SkipBreak, &["s"]),
+            (Action::ExtendPhase, &["x"]),
+            (Action::FinishPhase, &["n"]),
+            (Action::ToggleAutoStart, &["o"]),
+        ],
+        _ => return Err(anyhow!("unknown key preset: {}", name)),
+    };
+
+    Ok(preset
+        .iter()
+        .map(|(action, keys)| (*action, keys.iter().map(|key| key.to_string()).collect()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent { code, modifiers }
+    }
+
+    fn config(preset: &str, bindings: &[(&str, &[&str])]) -> KeysConfig {
+        KeysConfig {
+            preset: preset.to_string(),
+            bindings: bindings
+                .iter()
+                .map(|(name, keys)| {
+                    let keys = keys.iter().map(|key| key.to_string()).collect();
+                    (name.to_string(), keys)
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_key_binding() {
+        let binding: KeyBinding = "ctrl+s".parse().unwrap();
+        assert_eq!(binding.code, KeyCode::Char('s'));
+        assert_eq!(binding.modifiers, KeyModifiers::CONTROL);
+        assert_eq!(binding.to_string(), "ctrl+s");
+
+        assert_eq!(
+            "space".parse::<KeyBinding>().unwrap().code,
+            KeyCode::Char(' ')
+        );
+        assert_eq!("F5".parse::<KeyBinding>().unwrap().code, KeyCode::F(5));
+        assert_eq!(
+            "ctrl++".parse::<KeyBinding>().unwrap().code,
+            KeyCode::Char('+')
+        );
+        // a capital is typed with shift; the char alone tells it.
+        assert_eq!(
+            KeyBinding::from(&key(KeyCode::Char('G'), KeyModifiers::SHIFT)),
+            "G".parse().unwrap()
+        );
+        assert_eq!(
+            "shift+a".parse::<KeyBinding>().unwrap(),
+            "A".parse().unwrap()
+        );
+        assert_eq!(
+            "ctrl+shift+a".parse::<KeyBinding>().unwrap(),
+            KeyBinding::from(&key(
+                KeyCode::Char('A'),
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT
+            ))
+        );
+        assert!("hyper+x".parse::<KeyBinding>().is_err());
+        assert!("ctrl+".parse::<KeyBinding>().is_err());
+    }
+
+    #[test]
+    fn test_presets() {
+        let vim = Keymap::preset("vim").unwrap();
+        assert_eq!(
+            vim.action(&key(KeyCode::Char('j'), KeyModifiers::NONE)),
+            Some(Action::Down)
+        );
+        assert_eq!(
+            vim.action(&key(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            vim.action(&key(KeyCode::Char('e'), KeyModifiers::NONE)),
+            Some(Action::Edit)
+        );
+
+        let arrows = Keymap::preset("arrows").unwrap();
+        assert_eq!(
+            arrows.action(&key(KeyCode::Right, KeyModifiers::NONE)),
+            Some(Action::NextState)
+        );
+        assert_eq!(
+            arrows.action(&key(KeyCode::Char('j'), KeyModifiers::NONE)),
+            None
+        );
+
+        assert!(Keymap::preset("emacs").is_err());
+    }
+
+    #[test]
+    fn test_config_replaces_the_preset_keys() {
+        let keymap = Keymap::from_config(&config("vim", &[("quit", &["ctrl+q"])])).unwrap();
+        assert_eq!(
+            keymap.action(&key(KeyCode::Char('q'), KeyModifiers::CONTROL)),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            keymap.action(&key(KeyCode::Char('q'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(keymap.keys(Action::Quit).len(), 1);
+    }
+
+    #[test]
+    fn test_conflicts_are_errors() {
+        let error = Keymap::from_config(&config("vim", &[("archive", &["j"])])).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "key j is bound to both down and archive."
+        );
+
+        // moving the other action away resolves it.
+        assert!(Keymap::from_config(&config(
+            "vim",
+            &[("archive", &["j"]), ("down", &["ctrl+n"])]
+        ))
+        .is_ok());
+        assert!(Keymap::from_config(&config("vim", &[("fly", &["z"])])).is_err());
+    }
+}
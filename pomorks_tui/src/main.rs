@@ -1,25 +1,74 @@
 mod app;
+mod cli;
+mod command_line;
+mod controller;
 mod date_manage;
-mod notifications;
+mod editor;
+mod help;
+mod keymap;
+mod markdown;
+mod mouse;
 mod statefull_list;
+mod todo_form;
 mod tui;
 mod ui;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use chrono::prelude::*;
+use cli::{Args, Command};
+use controller::Controller;
+use keymap::Keymap;
+use pomorks_core::engine::Engine;
+use pomorks_daemon::protocol::Request;
+use pomorks_daemon::remote::{default_socket_path, Remote};
 use pomorks_data_manage::data_manage_firebase::DataManageFirebase;
+use std::env;
 use std::io;
+use std::time::Duration;
 
+use pomorks_data_manage::config::Config;
 use pomorks_data_manage::data_manage_json::DataManageJson;
-use pomorks_data_manage::data_manage_trait::{DataManage, TypeDataManager};
-use pomorks_data_manage::todo::{State, TodoItem, TodoList};
+use pomorks_data_manage::data_manage_trait::{
+    DataManage, TaskDealing, TypeDataManager, UNASSIGNED_ID,
+};
+use pomorks_data_manage::hooks::HookRunner;
+use pomorks_data_manage::notifications;
+use pomorks_data_manage::recovery::{InFlightSession, RecoveryAction};
+use pomorks_data_manage::todo::{State, TodoList, ONE_MINUTE};
+use pomorks_data_manage::webhook::WebhookNotifier;
 
 fn main() -> Result<()> {
-    let selected_data_manager = input_selected_data_manager()?;
+    let args = Args::parse(env::args().skip(1))?;
+    if let Some(Command::Help) = args.command {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+    if let Some(data_dir) = &args.data_dir {
+        env::set_current_dir(data_dir)
+            .with_context(|| format!("can't open data dir: {}", data_dir.display()))?;
+    }
+
+    let config = Config::load()?;
+    let selected_data_manager = match (args.backend, &args.command) {
+        (Some(backend), _) => backend,
+        (None, Some(_)) => TypeDataManager::DataManageJson,
+        (None, None) => input_selected_data_manager()?,
+    };
     let data_manager: &dyn DataManage = match selected_data_manager {
         TypeDataManager::DataManageJson => &DataManageJson {},
         TypeDataManager::DataManageFirebase => &DataManageFirebase {},
     };
+    data_manager.migrate()?;
+
+    match args.command {
+        Some(command) => cli::run(command, data_manager, &config),
+        None => launch(data_manager, &config),
+    }
+}
+
+fn launch(data_manager: &dyn DataManage, config: &Config) -> Result<()> {
+    // a bad binding is reported before the terminal is taken over.
+    let keymap = Keymap::from_config(&config.keys)?;
 
     let mut todo_list = match data_manager.read_all_todo()? {
         Some(todo_list) => todo_list,
@@ -28,94 +77,52 @@ fn main() -> Result<()> {
 
     println!("{:?}", todo_list);
 
-    let mut status = String::new();
-    let mut task_dealing = data_manager.read_task_dealing()?;
-
-    let mut state = State::WORK(1);
-    if let Some(state_first) = task_dealing.state {
-        state = state_first;
-    }
-
-    let mut todays_executed_count = data_manager.get_executed_count_by_day(&Local::now())?;
-    let task_log = data_manager.get_log_all()?;
-
-    loop {
-        // TODO:start_timeの制御が各フローに散ってるの良くないが、、、?
-        match tui::launch_tui(
-            &mut todo_list,
-            &state,
-            &status,
-            &task_dealing.id,
-            &task_dealing.date,
-            todays_executed_count,
-            &task_log,
-        ) {
-            Ok(res) => match res {
-                Some(info) => match info {
-                    tui::UpdateInfo::CountIncrement(todo, is_go_next_state) => {
-                        todo_list.insert_todo(TodoItem {
-                            executed_count: todo.executed_count + 1,
-                            ..todo.clone()
-                        })?;
-                        if is_go_next_state {
-                            state = State::get_next_state(&state);
-                        }
-                        task_dealing.date = None;
-                        data_manager.add_task_log(&todo.id, &Local::now())?;
-                        todays_executed_count =
-                            data_manager.get_executed_count_by_day(&Local::now())?;
-                    }
-                    tui::UpdateInfo::AddNewTodo(todo, is_go_next_state) => {
-                        todo_list.add_todo(todo)?;
-                        if is_go_next_state {
-                            state = State::get_next_state(&state);
-                        }
-                    }
-                    tui::UpdateInfo::ChangeFinishStatus(todo, is_go_next_state) => {
-                        todo_list.insert_todo(TodoItem {
-                            finished: !todo.finished,
-                            ..todo
-                        })?;
-                        if is_go_next_state {
-                            state = State::get_next_state(&state);
-                        }
-                    }
-                    tui::UpdateInfo::MoveNextState() => {
-                        state = State::get_next_state(&state);
-                        task_dealing.date = None;
-                    }
-                    tui::UpdateInfo::MovePrevState() => {
-                        state = State::get_prev_state(&state);
-                        task_dealing.date = None;
-                    }
-                    tui::UpdateInfo::ArchiveFinishedTodo(is_go_next_state) => {
-                        let finished_todo = todo_list.drain_finished_todo();
-                        data_manager.archive_todo(finished_todo)?;
-                        if is_go_next_state {
-                            state = State::get_next_state(&state);
-                        }
-                    }
-                    tui::UpdateInfo::StartTodo(_start_time, _id, _state) => {
-                        data_manager.write_task_dealing(&_id, &_start_time, &_state)?;
-                        task_dealing.id = Some(_id.clone());
-                        task_dealing.date = Some(_start_time);
-                        state = _state;
-                    }
-                },
-                None => break,
-            },
-            Err(e) => {
-                status = e.to_string();
+    let debounce = Duration::from_secs(config.autosave.debounce_seconds);
+    let hooks = HookRunner::new(config.hooks.clone());
+    let mut controller =
+        match Remote::connect(&default_socket_path()) {
+            // the daemon keeps the running phase, so there is nothing to recover here.
+            Some(mut remote) => {
+                let events = remote.events()?;
+                let status = remote.request(&Request::Status)?;
+                let engine = status.engine(&config.timer, Local::now());
+                Controller::new(data_manager, todo_list, engine, None, debounce, hooks)?
+                    .with_daemon(remote, events, config.timer.clone(), status)
+            }
+            None => {
+                let mut task_dealing = data_manager.read_task_dealing()?;
+                recover_in_flight_session(data_manager, config, &mut todo_list, &mut task_dealing)?;
+
+                let state = task_dealing.state.clone().unwrap_or(State::WORK(1));
+                let engine =
+                    Engine::new(state, &config.timer).resumed_from(&task_dealing, Local::now());
+                Controller::new(
+                    data_manager,
+                    todo_list,
+                    engine,
+                    task_dealing.id.filter(|id| id != UNASSIGNED_ID),
+                    debounce,
+                    hooks,
+                )?
             }
         }
-    }
-
-    if task_dealing.date.is_none() {
-        data_manager.delete_task_dealing()?;
-    }
-    data_manager.write_all_todo(todo_list)?;
-
-    Ok(())
+        .with_webhook(WebhookNotifier::new(config.webhook.clone()))
+        .with_profiles(config.profiles.clone());
+    controller.webhook.retry_in_background();
+
+    let notifier = notifications::get_notifier(&config.notifier);
+
+    // save even when the terminal could not be restored, e.g. after SIGHUP.
+    let result = tui::run_tui(
+        &mut controller,
+        &config.timer,
+        &config.board,
+        keymap,
+        notifier.as_ref(),
+    );
+    controller.save()?;
+
+    result
 }
 
 fn input_selected_data_manager() -> Result<TypeDataManager> {
@@ -140,3 +147,66 @@ fn input_selected_data_manager() -> Result<TypeDataManager> {
         None => Err(anyhow!("input number is incorrect")),
     }
 }
+
+fn recover_in_flight_session(
+    data_manager: &dyn DataManage,
+    config: &Config,
+    todo_list: &mut TodoList,
+    task_dealing: &mut TaskDealing,
+) -> Result<()> {
+    let now = Local::now();
+    let session = match InFlightSession::from_task_dealing(task_dealing, &now) {
+        Some(session) => session,
+        None => return Ok(()),
+    };
+
+    let grace_seconds = (config.recovery.grace_minutes * ONE_MINUTE) as i64;
+    let action = input_recovery_action(&session, session.default_action(grace_seconds))?;
+
+    session.recover(action, task_dealing, todo_list, data_manager, &now)
+}
+
+fn input_recovery_action(
+    session: &InFlightSession,
+    default_action: RecoveryAction,
+) -> Result<RecoveryAction> {
+    let actions = session.available_actions();
+
+    println!(
+        "Found an interrupted session: {} started at {}",
+        State::get_state_name(&session.state),
+        session.start_time.format("%Y/%m/%d %H:%M:%S")
+    );
+    if session.is_expired() {
+        println!("It ended {} seconds ago.", session.elapsed - session.limit);
+    } else {
+        println!("{} seconds remain.", session.remaining());
+    }
+
+    loop {
+        println!(
+            "Please Select Action [{}] (default: {})",
+            actions
+                .iter()
+                .map(|action| action.name())
+                .collect::<Vec<String>>()
+                .join("/"),
+            default_action.name()
+        );
+
+        let mut result = String::new();
+
+        io::stdin()
+            .read_line(&mut result)
+            .expect("Failed to read line.");
+
+        if result.trim().is_empty() {
+            return Ok(default_action);
+        }
+
+        match RecoveryAction::from_input(&result) {
+            Some(action) if actions.contains(&action) => return Ok(action),
+            _ => println!("input action is incorrect"),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Just enough Markdown for todo details: headings, lists, checkboxes and code.
+
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+
+pub fn to_spans(text: &str) -> Vec<Spans<'static>> {
+    let mut in_code_block = false;
+    let mut lines = vec![];
+
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            lines.push(Spans::from(Span::styled(
+                format!("  {}", line),
+                code_style(),
+            )));
+            continue;
+        }
+        lines.push(to_line(line));
+    }
+
+    lines
+}
+
+fn to_line(line: &str) -> Spans<'static> {
+    let body = line.trim_start();
+    let indent = line[..line.len() - body.len()].to_string();
+
+    let heading_level = body.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&heading_level) && body[heading_level..].starts_with(' ') {
+        let color = match heading_level {
+            1 => Color::LightRed,
+            2 => Color::LightYellow,
+            _ => Color::LightCyan,
+        };
+        return Spans::from(Span::styled(
+            body[heading_level..].trim().to_string(),
+            Style::default()
+                .fg(color)
+                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+        ));
+    }
+
+    let mut spans = vec![Span::raw(indent)];
+    let mut text_style = Style::default().fg(Color::Gray);
+    let rest = if let Some(rest) = strip_bullet(body) {
+        if let Some(task) = rest.strip_prefix("[ ] ") {
+            spans.push(Span::styled("☐ ", Style::default().fg(Color::Yellow)));
+            task
+        } else if let Some(task) = rest
+            .strip_prefix("[x] ")
+            .or_else(|| rest.strip_prefix("[X] "))
+        {
+            spans.push(Span::styled("☑ ", Style::default().fg(Color::Green)));
+            text_style = text_style
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::CROSSED_OUT);
+            task
+        } else {
+            spans.push(Span::styled("• ", Style::default().fg(Color::LightBlue)));
+            rest
+        }
+    } else if let Some((number, rest)) = split_ordered(body) {
+        spans.push(Span::styled(
+            format!("{} ", number),
+            Style::default().fg(Color::LightBlue),
+        ));
+        rest
+    } else {
+        body
+    };
+
+    spans.extend(inline_code(rest, text_style));
+    Spans::from(spans)
+}
+
+fn strip_bullet(body: &str) -> Option<&str> {
+    ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|bullet| body.strip_prefix(bullet))
+}
+
+// "1. first" -> ("1.", "first")
+fn split_ordered(body: &str) -> Option<(&str, &str)> {
+    let digits = body.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 && body[digits..].starts_with(". ") {
+        Some((&body[..digits + 1], &body[digits + 2..]))
+    } else {
+        None
+    }
+}
+
+// `code` between backticks; an unclosed backtick is left as it is.
+fn inline_code(text: &str, style: Style) -> Vec<Span<'static>> {
+    if text.matches('`').count() % 2 == 1 {
+        return vec![Span::styled(text.to_string(), style)];
+    }
+
+    text.split('`')
+        .enumerate()
+        .filter(|(_, part)| !part.is_empty())
+        .map(|(ind, part)| {
+            if ind % 2 == 1 {
+                Span::styled(part.to_string(), code_style())
+            } else {
+                Span::styled(part.to_string(), style)
+            }
+        })
+        .collect()
+}
+
+fn code_style() -> Style {
+    Style::default().fg(Color::Yellow).bg(Color::Black)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(spans: &Spans) -> String {
+        spans.0.iter().map(|span| span.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn test_to_spans() {
+        let lines = to_spans(
+            "# Plan\n- [ ] write `doc`\n  - [x] outline\n* note\n2. review\n```\nlet a = 1;\n```\n#tag",
+        );
+        let texts: Vec<String> = lines.iter().map(plain).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "Plan",
+                "☐ write doc",
+                "  ☑ outline",
+                "• note",
+                "2. review",
+                "  let a = 1;",
+                "#tag",
+            ]
+        );
+
+        assert!(lines[0].0[0].style.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(lines[1].0.last().unwrap().style, code_style());
+        assert!(lines[2].0[2]
+            .style
+            .add_modifier
+            .contains(Modifier::CROSSED_OUT));
+    }
+
+    #[test]
+    fn test_unclosed_backtick_is_text() {
+        let spans = inline_code("a ` b", Style::default());
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].content, "a ` b");
+    }
+}
@@ -0,0 +1,109 @@
+//! Clicks and the wheel, hit-tested against the areas of the last draw.
+
+use crate::app::Tab;
+use std::time::{Duration, Instant};
+use tui::layout::Rect;
+
+/// Two clicks on the same cell within this make a double click.
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// What the last draw put where. Cleared before every draw.
+#[derive(Debug, Default)]
+pub struct HitAreas {
+    pub tabs: Vec<(Tab, Rect)>,
+    /// inside the borders of the todo list, a line per row.
+    pub todo_list: Option<Rect>,
+    pub timer: Option<Rect>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Tab(Tab),
+    /// the line of the todo list, counted from the first one shown.
+    ListLine(usize),
+    Timer,
+}
+
+impl HitAreas {
+    pub fn target(&self, column: u16, row: u16) -> Option<Target> {
+        if let Some((tab, _)) = self
+            .tabs
+            .iter()
+            .find(|(_, area)| contains(area, column, row))
+        {
+            return Some(Target::Tab(*tab));
+        }
+        match (self.todo_list, self.timer) {
+            (Some(area), _) if contains(&area, column, row) => {
+                Some(Target::ListLine((row - area.y) as usize))
+            }
+            (_, Some(area)) if contains(&area, column, row) => Some(Target::Timer),
+            _ => None,
+        }
+    }
+}
+
+fn contains(area: &Rect, column: u16, row: u16) -> bool {
+    area.x <= column && column < area.right() && area.y <= row && row < area.bottom()
+}
+
+#[derive(Debug, Default)]
+pub struct ClickTracker {
+    last: Option<(Instant, u16, u16)>,
+}
+
+impl ClickTracker {
+    /// Whether the click completes a double click. A third click starts over.
+    pub fn click(&mut self, at: Instant, column: u16, row: u16) -> bool {
+        let double = match self.last {
+            Some((last, last_column, last_row)) => {
+                at.duration_since(last) <= DOUBLE_CLICK && (last_column, last_row) == (column, row)
+            }
+            None => false,
+        };
+        self.last = if double {
+            None
+        } else {
+            Some((at, column, row))
+        };
+        double
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target() {
+        let areas = HitAreas {
+            tabs: vec![
+                (Tab::Main, Rect::new(0, 3, 6, 1)),
+                (Tab::Board, Rect::new(7, 3, 7, 1)),
+            ],
+            todo_list: Some(Rect::new(0, 5, 40, 10)),
+            timer: Some(Rect::new(0, 20, 20, 5)),
+        };
+
+        assert_eq!(areas.target(8, 3), Some(Target::Tab(Tab::Board)));
+        assert_eq!(areas.target(6, 3), None);
+        assert_eq!(areas.target(10, 5), Some(Target::ListLine(0)));
+        assert_eq!(areas.target(39, 14), Some(Target::ListLine(9)));
+        assert_eq!(areas.target(40, 14), None);
+        assert_eq!(areas.target(0, 24), Some(Target::Timer));
+        assert_eq!(HitAreas::default().target(0, 0), None);
+    }
+
+    #[test]
+    fn test_double_click() {
+        let start = Instant::now();
+        let mut clicks = ClickTracker::default();
+        assert!(!clicks.click(start, 1, 1));
+        assert!(clicks.click(start + Duration::from_millis(200), 1, 1));
+        assert!(!clicks.click(start + Duration::from_millis(300), 1, 1));
+
+        // too slow, or on another cell.
+        assert!(!clicks.click(start + Duration::from_secs(2), 1, 1));
+        assert!(!clicks.click(start + Duration::from_millis(2100), 2, 1));
+    }
+}
@@ -12,7 +12,21 @@ impl<T> StatefulList<T> {
         }
     }
 
+    // replaces items, keeping the selection inside the new range.
+    pub fn set_items(&mut self, items: Vec<T>) {
+        self.items = items;
+        let selected = match self.state.selected() {
+            Some(_) if self.items.is_empty() => None,
+            Some(i) => Some(i.min(self.items.len() - 1)),
+            None => None,
+        };
+        self.state.select(selected);
+    }
+
     pub fn next(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i >= self.items.len() - 1 {
@@ -27,6 +41,9 @@ impl<T> StatefulList<T> {
     }
 
     pub fn previous(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i == 0 {
@@ -40,3 +57,25 @@ impl<T> StatefulList<T> {
         self.state.select(Some(i));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_items_keeps_selection_in_range() {
+        let mut list = StatefulList::with_items(vec![1, 2, 3]);
+        list.next();
+        list.next();
+        list.next();
+        assert_eq!(list.state.selected(), Some(2));
+
+        list.set_items(vec![1, 2]);
+        assert_eq!(list.state.selected(), Some(1));
+
+        list.set_items(vec![]);
+        assert_eq!(list.state.selected(), None);
+        list.next();
+        assert_eq!(list.state.selected(), None);
+    }
+}
@@ -0,0 +1,268 @@
+use anyhow::{anyhow, Result};
+use pomorks_data_manage::todo::{generate_id, TodoItem, TodoStatus};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormField {
+    Title,
+    Tag,
+    Project,
+    Estimate,
+    Detail,
+}
+
+impl FormField {
+    pub const ALL: [FormField; 5] = [
+        FormField::Title,
+        FormField::Tag,
+        FormField::Project,
+        FormField::Estimate,
+        FormField::Detail,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FormField::Title => "Title",
+            FormField::Tag => "Tag",
+            FormField::Project => "Project",
+            FormField::Estimate => "Estimate",
+            FormField::Detail => "Detail",
+        }
+    }
+}
+
+/// Popup for adding a todo or editing the fields of an existing one.
+#[derive(Debug, Clone)]
+pub struct TodoForm {
+    // the todo being edited; `None` while adding.
+    original: Option<TodoItem>,
+    values: [String; 5],
+    focused: usize,
+    // in chars, within the focused field.
+    cursor: usize,
+    pub error: Option<String>,
+}
+
+impl TodoForm {
+    pub fn new() -> Self {
+        TodoForm {
+            original: None,
+            values: Default::default(),
+            focused: 0,
+            cursor: 0,
+            error: None,
+        }
+    }
+
+    pub fn edit(todo: &TodoItem) -> Self {
+        let mut form = TodoForm {
+            original: Some(todo.clone()),
+            values: [
+                todo.title.clone(),
+                todo.tag.clone(),
+                todo.project.clone(),
+                todo.estimate_count.to_string(),
+                todo.detail.clone(),
+            ],
+            ..TodoForm::new()
+        };
+        form.cursor = form.values[0].chars().count();
+        form
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.original.is_some()
+    }
+
+    pub fn title(&self) -> &'static str {
+        if self.is_editing() {
+            "EDIT TODO"
+        } else {
+            "ADD TODO"
+        }
+    }
+
+    pub fn value(&self, field: FormField) -> &str {
+        &self.values[field as usize]
+    }
+
+    pub fn focused(&self) -> FormField {
+        FormField::ALL[self.focused]
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn next_field(&mut self) {
+        self.focus((self.focused + 1) % FormField::ALL.len());
+    }
+
+    pub fn prev_field(&mut self) {
+        self.focus((self.focused + FormField::ALL.len() - 1) % FormField::ALL.len());
+    }
+
+    fn focus(&mut self, ind: usize) {
+        self.focused = ind;
+        self.cursor = self.values[ind].chars().count();
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let at = self.byte_index(self.cursor);
+        self.values[self.focused].insert(at, c);
+        self.cursor += 1;
+    }
+
+    /// Removes the char before the cursor.
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            let at = self.byte_index(self.cursor);
+            self.values[self.focused].remove(at);
+        }
+    }
+
+    /// Removes the char under the cursor.
+    pub fn delete(&mut self) {
+        if self.cursor < self.values[self.focused].chars().count() {
+            let at = self.byte_index(self.cursor);
+            self.values[self.focused].remove(at);
+        }
+    }
+
+    pub fn left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.values[self.focused].chars().count());
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.values[self.focused].chars().count();
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        let value = &self.values[self.focused];
+        value
+            .char_indices()
+            .nth(cursor)
+            .map_or(value.len(), |(ind, _)| ind)
+    }
+
+    /// The todo as entered. An edit keeps the id, the executed count, the finished flag and the status.
+    pub fn to_todo(&self) -> Result<TodoItem> {
+        let title = self.value(FormField::Title).trim();
+        if title.is_empty() {
+            return Err(anyhow!("title is empty."));
+        }
+        let estimate = self.value(FormField::Estimate).trim();
+        let estimate_count = match estimate {
+            "" => 0,
+            _ => estimate
+                .parse()
+                .map_err(|_| anyhow!("estimate must be a number: {}", estimate))?,
+        };
+        let (id, executed_count, finished, status) = match &self.original {
+            Some(todo) => (
+                todo.id.clone(),
+                todo.executed_count,
+                todo.finished,
+                todo.status,
+            ),
+            None => (generate_id(), 0, false, TodoStatus::Backlog),
+        };
+
+        Ok(TodoItem {
+            id,
+            title: title.to_string(),
+            tag: self.value(FormField::Tag).trim().to_string(),
+            project: self.value(FormField::Project).trim().to_string(),
+            estimate_count,
+            executed_count,
+            finished,
+            detail: self.value(FormField::Detail).to_string(),
+            status,
+        })
+    }
+}
+
+impl Default for TodoForm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn type_in(form: &mut TodoForm, text: &str) {
+        text.chars().for_each(|c| form.insert(c));
+    }
+
+    #[test]
+    fn test_add_todo() {
+        let mut form = TodoForm::new();
+        assert!(form.to_todo().is_err());
+
+        type_in(&mut form, "write doc");
+        form.next_field();
+        type_in(&mut form, "doc");
+        form.next_field();
+        type_in(&mut form, "pomorks");
+        form.next_field();
+        type_in(&mut form, "x");
+        assert!(form.to_todo().is_err());
+        form.backspace();
+        type_in(&mut form, "3");
+
+        let todo = form.to_todo().unwrap();
+        assert_eq!(todo.title, "write doc");
+        assert_eq!(todo.tag, "doc");
+        assert_eq!(todo.project, "pomorks");
+        assert_eq!(todo.estimate_count, 3);
+        assert_eq!(todo.executed_count, 0);
+    }
+
+    #[test]
+    fn test_edit_keeps_progress() {
+        let mut todo = TodoItem::from_str("write doc pomorks 2").unwrap();
+        todo.executed_count = 1;
+        todo.finished = true;
+
+        let mut form = TodoForm::edit(&todo);
+        assert!(form.is_editing());
+        form.prev_field();
+        assert_eq!(form.focused(), FormField::Detail);
+        type_in(&mut form, "see #12");
+
+        let edited = form.to_todo().unwrap();
+        assert_eq!(edited.id, todo.id);
+        assert_eq!(edited.executed_count, 1);
+        assert!(edited.finished);
+        assert_eq!(edited.detail, "see #12");
+    }
+
+    #[test]
+    fn test_cursor_movement() {
+        let mut form = TodoForm::new();
+        type_in(&mut form, "wrté");
+        form.left();
+        form.left();
+        form.insert('i');
+        assert_eq!(form.value(FormField::Title), "writé");
+
+        form.home();
+        form.delete();
+        form.end();
+        form.right();
+        form.backspace();
+        assert_eq!(form.value(FormField::Title), "rit");
+        assert_eq!(form.cursor(), 3);
+    }
+}
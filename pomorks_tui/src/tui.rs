@@ -1,40 +1,76 @@
 use crate::app::App;
+use crate::command_line;
+use crate::controller::Controller;
+use crate::editor;
+use crate::keymap::Keymap;
 use crate::ui;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use chrono::prelude::*;
 use crossterm::{
     event::{
-        self, DisableMouseCapture, EnableMouseCapture, Event as CEvent, KeyCode, KeyModifiers,
+        self, DisableMouseCapture, EnableMouseCapture, Event as CEvent, KeyCode, KeyEvent,
+        KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
     },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use pomorks_data_manage::config::{BoardConfig, TimerConfig};
 use pomorks_data_manage::data_manage_trait::TaskLogJson;
-use pomorks_data_manage::todo::State;
-use pomorks_data_manage::todo::TodoItem;
-use pomorks_data_manage::todo::TodoList;
+use pomorks_data_manage::notifications::Notifier;
+use pomorks_data_manage::todo::{TodoItem, TodoStatus};
+use pomorks_data_manage::view::ListView;
 use std::{
     io::stdout,
-    sync::mpsc::{self},
-    thread,
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+    sync::mpsc::{self, Receiver, Sender},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    thread::{self, JoinHandle},
     time::{Duration, Instant},
 };
-use tui::{backend::CrosstermBackend, Terminal};
+use tui::{
+    backend::{Backend, CrosstermBackend},
+    Terminal,
+};
 
 enum Event<I> {
     Input(I),
+    Mouse(MouseEvent),
+    Resize,
     Tick,
 }
 
-type ShouldGoNextState = bool;
 pub enum UpdateInfo {
-    CountIncrement(TodoItem, ShouldGoNextState),
-    AddNewTodo(TodoItem, ShouldGoNextState),
-    ChangeFinishStatus(TodoItem, ShouldGoNextState),
-    ArchiveFinishedTodo(ShouldGoNextState),
-    StartTodo(DateTime<Local>, String, State),
+    AddNewTodo(TodoItem),
+    EditTodo(TodoItem),
+    DeleteTodo(TodoItem),
+    ChangeFinishStatus(TodoItem),
+    MoveTodo(TodoItem, TodoStatus),
+    ArchiveFinishedTodo(),
+    StartTodo(String),
+    PausePhase(),
+    FinishPhase(),
+    SkipPhase(),
+    AddTaskLog(TaskLogJson),
+    ReassignTaskLog(TaskLogJson, TodoItem),
+    DeleteTaskLog(TaskLogJson),
+    ExtendPhase(usize),
+    ToggleAutoStart(),
+    SetProfile(String),
     MovePrevState(),
-    MoveNextState(),
+}
+
+#[cfg(unix)]
+const TERMINATE_SIGNALS: &[i32] = &[signal_hook::consts::SIGTERM, signal_hook::consts::SIGHUP];
+#[cfg(not(unix))]
+const TERMINATE_SIGNALS: &[i32] = &[signal_hook::consts::SIGTERM];
+
+// keeps the input thread off stdin while an external editor owns the terminal.
+#[derive(Default)]
+struct InputGate {
+    suspended: AtomicBool,
+    // held by the input thread while it polls.
+    polling: Mutex<()>,
 }
 
 /// Crossterm demo
@@ -42,23 +78,16 @@ pub enum UpdateInfo {
 struct Cli {
     /// time in ms between two ticks.
     tick_rate: u64,
-    /// whether unicode symbols are used to improve the overall look of the app
-    enhanced_graphics: bool,
-}
-
-pub fn launch_tui(
-    todo_list: &mut TodoList,
-    state: &State,
-    status: &str,
-    id: &Option<String>,
-    start_time: &Option<DateTime<Local>>,
-    todays_executed_count: i64,
-    task_log: &Vec<TaskLogJson>,
-) -> Result<Option<UpdateInfo>> {
-    let cli: Cli = Cli {
-        tick_rate: 1000,
-        enhanced_graphics: true,
-    };
+}
+
+pub fn run_tui(
+    controller: &mut Controller,
+    timer_config: &TimerConfig,
+    board_config: &BoardConfig,
+    keymap: Keymap,
+    notifier: &dyn Notifier,
+) -> Result<()> {
+    let cli: Cli = Cli { tick_rate: 1000 };
 
     enable_raw_mode()?;
 
@@ -69,22 +98,96 @@ pub fn launch_tui(
 
     let mut terminal = Terminal::new(backend)?;
 
+    // pending saves are flushed by the caller once the loop ends.
+    let terminate = Arc::new(AtomicBool::new(false));
+    for signal in TERMINATE_SIGNALS {
+        signal_hook::flag::register(*signal, terminate.clone())?;
+    }
+
     // Setup input handling
     let (tx, rx) = mpsc::channel();
+    let should_stop = Arc::new(AtomicBool::new(false));
+    let gate = Arc::new(InputGate::default());
+    let input_handle = spawn_input_thread(
+        tx,
+        Duration::from_millis(cli.tick_rate),
+        should_stop.clone(),
+        gate.clone(),
+    );
 
-    let tick_rate = Duration::from_millis(cli.tick_rate);
+    let mut app = App::new(controller, timer_config, board_config, keymap, notifier);
+    match command_line::read_history(Path::new(command_line::HISTORY_FILE)) {
+        Ok(history) => app.command_history = history,
+        Err(e) => app.status = e.to_string(),
+    }
+
+    terminal.clear()?;
+    if let Err(e) = controller.status_snapshot().write_cache() {
+        app.status = e.to_string();
+    }
+    match ListView::read() {
+        Ok(list_view) => {
+            app.list_view = list_view;
+            app.refresh_todos();
+        }
+        Err(e) => app.status = e.to_string(),
+    }
+
+    let result = run_event_loop(&mut terminal, &mut app, controller, &rx, &terminate, &gate);
+
+    should_stop.store(true, Ordering::Relaxed);
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    terminal.show_cursor()?;
+    input_handle
+        .join()
+        .map_err(|_| anyhow!("input thread panicked."))?;
+
+    result
+}
+
+fn spawn_input_thread(
+    tx: Sender<Event<KeyEvent>>,
+    tick_rate: Duration,
+    should_stop: Arc<AtomicBool>,
+    gate: Arc<InputGate>,
+) -> JoinHandle<()> {
     thread::spawn(move || {
         let mut last_tick = Instant::now();
-        loop {
+        while !should_stop.load(Ordering::Relaxed) {
+            if gate.suspended.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(10));
+                continue;
+            }
             // poll for tick rate duration, if no events, sent tick event.
             let timeout = tick_rate
                 .checked_sub(last_tick.elapsed())
                 .unwrap_or_else(|| Duration::from_secs(0));
-            if event::poll(timeout).unwrap() {
-                if let CEvent::Key(key) = event::read().unwrap() {
-                    if tx.send(Event::Input(key)).is_err() {
-                        break;
-                    }
+            let polling = gate.polling.lock();
+            let event = match event::poll(timeout) {
+                Ok(true) => match event::read() {
+                    Ok(CEvent::Key(key)) => Some(Event::Input(key)),
+                    // moves would redraw on every motion; only presses and the wheel are used.
+                    Ok(CEvent::Mouse(mouse)) => match mouse.kind {
+                        MouseEventKind::Down(_)
+                        | MouseEventKind::ScrollUp
+                        | MouseEventKind::ScrollDown => Some(Event::Mouse(mouse)),
+                        _ => None,
+                    },
+                    Ok(CEvent::Resize(_, _)) => Some(Event::Resize),
+                    Err(_) => break,
+                },
+                Ok(false) => None,
+                Err(_) => break,
+            };
+            drop(polling);
+            if let Some(event) = event {
+                if tx.send(event).is_err() {
+                    break;
                 }
             }
             if last_tick.elapsed() >= tick_rate {
@@ -94,77 +197,301 @@ pub fn launch_tui(
                 last_tick = Instant::now();
             }
         }
-    });
-
-    let mut app = App::new(
-        "Crossterm Demo",
-        cli.enhanced_graphics,
-        todo_list,
-        state,
-        status.to_owned(),
-        id,
-        start_time,
-        todays_executed_count,
-        task_log,
-    );
-
-    terminal.clear()?;
+    })
+}
 
+fn run_event_loop<B: Backend + std::io::Write>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    controller: &mut Controller,
+    rx: &Receiver<Event<KeyEvent>>,
+    terminate: &AtomicBool,
+    gate: &InputGate,
+) -> Result<()> {
     loop {
-        terminal.draw(|f| ui::draw(f, &mut app))?;
-        match rx.recv()? {
-            Event::Input(event) => {
-                if event.modifiers == KeyModifiers::NONE {
-                    match event.code {
-                        KeyCode::Char(c) => match app.on_key(c, terminal.get_cursor().unwrap())? {
-                            Some(info) => return Ok(Some(info)),
-                            None => (),
-                        },
-                        KeyCode::Left => app.on_left(),
-                        KeyCode::Up => app.on_up(),
-                        KeyCode::Right => app.on_right(),
-                        KeyCode::Down => app.on_down(),
-                        KeyCode::Enter => {
-                            let res = app.on_enter()?;
-                            match res {
-                                Some(info) => return Ok(Some(info)),
-                                None => (),
-                            }
-                        }
-                        KeyCode::Delete => app.on_delete(),
-                        KeyCode::Backspace => app.on_delete(),
-                        KeyCode::Tab => app.on_change_tab(),
-                        KeyCode::Esc => {
-                            disable_raw_mode()?;
-                            execute!(
-                                terminal.backend_mut(),
-                                LeaveAlternateScreen,
-                                DisableMouseCapture
-                            )?;
-                            terminal.show_cursor()?;
-                            return Ok(None);
+        terminal.draw(|f| ui::draw(f, app))?;
+        let res = match rx.recv()? {
+            Event::Input(event) => on_input(app, event),
+            Event::Mouse(event) => on_mouse_input(app, event),
+            Event::Resize => {
+                terminal.autoresize()?;
+                Ok(None)
+            }
+            Event::Tick => {
+                if let Err(e) = controller.flush_if_due() {
+                    app.status = e.to_string();
+                }
+                // hooks, webhooks and notifications run in the background, so they report back here.
+                let mut errors = controller.hooks.take_errors();
+                errors.append(&mut controller.webhook.take_errors());
+                errors.append(&mut app.notifier.take_errors());
+                if let Some(error) = errors.pop() {
+                    app.status = error;
+                }
+                app.save_status = controller.save_status.clone();
+                app.on_tick();
+                match controller.tick(Local::now()) {
+                    Ok(events) if events.is_empty() => {}
+                    Ok(events) => {
+                        app.on_timer_events(&events);
+                        if let Err(e) = controller.status_snapshot().write_cache() {
+                            app.status = e.to_string();
                         }
-                        _ => {}
+                        app.sync(controller);
                     }
+                    Err(e) => app.status = e.to_string(),
+                }
+                match controller.sync_with_daemon() {
+                    Ok(true) => app.sync(controller),
+                    Ok(false) => {}
+                    Err(e) => app.status = e.to_string(),
                 }
+                Ok(None)
             }
-            Event::Tick => {
-                if let Some(info) = app.on_tick() {
-                    return Ok(Some(info));
+        };
+        let res = match app.detail_to_edit.take() {
+            Some(todo) => edit_detail(terminal, gate, todo).map(Some),
+            None => res,
+        };
+        match res {
+            Ok(Some(info)) => {
+                if let Err(e) = controller
+                    .dispatch(info)
+                    .and_then(|_| controller.status_snapshot().write_cache())
+                {
+                    app.status = e.to_string();
                 }
+                app.sync(controller);
             }
+            Ok(None) => {}
+            Err(e) => app.status = e.to_string(),
         }
-        if app.should_quit {
-            disable_raw_mode()?;
-            execute!(
-                terminal.backend_mut(),
-                LeaveAlternateScreen,
-                DisableMouseCapture
-            )?;
-            terminal.show_cursor()?;
+        if app.should_quit || terminate.load(Ordering::Relaxed) {
             break;
         }
     }
 
+    Ok(())
+}
+
+// the terminal, handed to another program until dropped, so every return path takes it back.
+struct Suspended<'a, B: Backend + std::io::Write> {
+    terminal: &'a mut Terminal<B>,
+    gate: &'a InputGate,
+    _polling: MutexGuard<'a, ()>,
+}
+
+impl<'a, B: Backend + std::io::Write> Suspended<'a, B> {
+    fn new(terminal: &'a mut Terminal<B>, gate: &'a InputGate) -> Result<Self> {
+        gate.suspended.store(true, Ordering::Relaxed);
+        let suspended = Suspended {
+            terminal,
+            gate,
+            _polling: gate.polling.lock().unwrap_or_else(PoisonError::into_inner),
+        };
+
+        disable_raw_mode()?;
+        execute!(
+            suspended.terminal.backend_mut(),
+            LeaveAlternateScreen,
+            DisableMouseCapture
+        )?;
+        Ok(suspended)
+    }
+}
+
+impl<B: Backend + std::io::Write> Drop for Suspended<'_, B> {
+    fn drop(&mut self) {
+        // nothing can report a failure here; the next draw repaints the whole screen anyway.
+        let _ = enable_raw_mode();
+        let _ = execute!(
+            self.terminal.backend_mut(),
+            EnterAlternateScreen,
+            EnableMouseCapture
+        );
+        let _ = self.terminal.clear();
+        self.gate.suspended.store(false, Ordering::Relaxed);
+    }
+}
+
+fn edit_detail<B: Backend + std::io::Write>(
+    terminal: &mut Terminal<B>,
+    gate: &InputGate,
+    todo: TodoItem,
+) -> Result<UpdateInfo> {
+    let detail = {
+        let _suspended = Suspended::new(terminal, gate)?;
+        editor::edit_text(&editor::editor_command(), &todo.id, &todo.detail)?
+    };
+
+    Ok(UpdateInfo::EditTodo(TodoItem { detail, ..todo }))
+}
+
+fn on_input(app: &mut App, event: KeyEvent) -> Result<Option<UpdateInfo>> {
+    if app.todo_form.is_some() {
+        return Ok(on_form_input(app, event));
+    }
+    if app.confirm_delete.is_some() {
+        return Ok(app.on_confirm_delete(event.code == KeyCode::Char('y')));
+    }
+    if app.show_help {
+        app.show_help = false;
+        return Ok(None);
+    }
+    if app.command_line.is_some() {
+        return on_command_line_input(app, event);
+    }
+    if app.palette.is_some() {
+        return on_palette_input(app, event);
+    }
+    if app.searching {
+        on_search_input(app, event);
+        return Ok(None);
+    }
+    if app.show_add_log {
+        return on_add_log_input(app, event);
+    }
+    match app.keymap.action(&event) {
+        Some(action) => app.on_action(action),
+        None => Ok(None),
+    }
+}
+
+fn on_mouse_input(app: &mut App, event: MouseEvent) -> Result<Option<UpdateInfo>> {
+    if !app.accepts_mouse() {
+        return Ok(None);
+    }
+    match event.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            return app.on_click(event.column, event.row, Instant::now())
+        }
+        MouseEventKind::ScrollUp => app.on_scroll(event.column, event.row, false),
+        MouseEventKind::ScrollDown => app.on_scroll(event.column, event.row, true),
+        _ => {}
+    }
+
+    Ok(None)
+}
+
+fn on_command_line_input(app: &mut App, event: KeyEvent) -> Result<Option<UpdateInfo>> {
+    if !(event.modifiers - KeyModifiers::SHIFT).is_empty() {
+        return Ok(None);
+    }
+    let line = match app.command_line.as_mut() {
+        Some(line) => line,
+        None => return Ok(None),
+    };
+    match event.code {
+        KeyCode::Char(c) => line.insert(c),
+        // like vim, backspace on an empty line leaves it.
+        KeyCode::Backspace if line.input.is_empty() => app.command_line = None,
+        KeyCode::Backspace => line.backspace(),
+        KeyCode::Tab => {
+            let candidates = line.complete(&app.todos.items, &app.profiles);
+            if candidates.len() > 1 {
+                app.status = candidates.join(" ");
+            }
+        }
+        KeyCode::Up => line.prev(&app.command_history),
+        KeyCode::Down => line.next(&app.command_history),
+        KeyCode::Enter => return app.on_submit_command(),
+        KeyCode::Esc => app.command_line = None,
+        _ => {}
+    }
+
+    Ok(None)
+}
+
+fn on_palette_input(app: &mut App, event: KeyEvent) -> Result<Option<UpdateInfo>> {
+    if !(event.modifiers - KeyModifiers::SHIFT).is_empty() {
+        return Ok(None);
+    }
+    let palette = match app.palette.as_mut() {
+        Some(palette) => palette,
+        None => return Ok(None),
+    };
+    match event.code {
+        KeyCode::Char(c) => palette.insert(c),
+        KeyCode::Backspace => palette.backspace(),
+        KeyCode::Up => palette.up(),
+        KeyCode::Down => palette.down(),
+        KeyCode::Enter => return app.on_submit_palette(),
+        KeyCode::Esc => app.palette = None,
+        _ => {}
+    }
+
+    Ok(None)
+}
+
+// narrows the list as the query is typed; it is saved once the search ends.
+fn on_search_input(app: &mut App, event: KeyEvent) {
+    if !(event.modifiers - KeyModifiers::SHIFT).is_empty() {
+        return;
+    }
+    match event.code {
+        KeyCode::Char(c) => app.list_view.search.push(c),
+        KeyCode::Backspace => {
+            app.list_view.search.pop();
+        }
+        KeyCode::Enter => {
+            app.searching = false;
+            app.set_list_view(app.list_view.clone());
+            return;
+        }
+        KeyCode::Esc => {
+            app.searching = false;
+            app.set_list_view(ListView {
+                search: String::new(),
+                ..app.list_view.clone()
+            });
+            return;
+        }
+        _ => return,
+    }
+    app.refresh_todos();
+    if app.todos.state.selected().is_none() && !app.todos.items.is_empty() {
+        app.todos.state.select(Some(0));
+    }
+}
+
+// the date of a back-dated log is typed in as text, whatever the keymap says.
+fn on_add_log_input(app: &mut App, event: KeyEvent) -> Result<Option<UpdateInfo>> {
+    if !(event.modifiers - KeyModifiers::SHIFT).is_empty() {
+        return Ok(None);
+    }
+    match event.code {
+        KeyCode::Char(c) => app.new_log_string.push(c),
+        KeyCode::Backspace | KeyCode::Delete => {
+            app.new_log_string.pop();
+        }
+        KeyCode::Enter => return app.on_enter(),
+        KeyCode::Esc => app.show_add_log = false,
+        _ => {}
+    }
+
     Ok(None)
 }
+
+fn on_form_input(app: &mut App, event: KeyEvent) -> Option<UpdateInfo> {
+    // shift is needed for capitals and for BackTab.
+    if !(event.modifiers - KeyModifiers::SHIFT).is_empty() {
+        return None;
+    }
+    let form = app.todo_form.as_mut()?;
+    match event.code {
+        KeyCode::Char(c) => form.insert(c),
+        KeyCode::Left => form.left(),
+        KeyCode::Right => form.right(),
+        KeyCode::Home => form.home(),
+        KeyCode::End => form.end(),
+        KeyCode::Backspace => form.backspace(),
+        KeyCode::Delete => form.delete(),
+        KeyCode::Tab | KeyCode::Down => form.next_field(),
+        KeyCode::BackTab | KeyCode::Up => form.prev_field(),
+        KeyCode::Enter => return app.on_submit_form(),
+        KeyCode::Esc => app.todo_form = None,
+        _ => {}
+    }
+
+    None
+}
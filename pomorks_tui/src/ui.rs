@@ -1,10 +1,20 @@
-use crate::app::{App, Tab};
+use crate::app::{App, Deletion, ListRow, Tab};
+use crate::command_line::Palette;
+use crate::controller::SaveStatus;
 use crate::date_manage::{get_this_month, get_this_week};
+use crate::help;
+use crate::keymap::{Action, Keymap};
+use crate::markdown;
+use crate::mouse::HitAreas;
+use crate::statefull_list::StatefulList;
+use crate::todo_form::{FormField, TodoForm};
 use chrono::prelude::*;
 use num_traits::FromPrimitive;
-use pomorks_data_manage::data_manage_trait::DATE_FORMAT;
-use pomorks_data_manage::todo::TodoItem;
+use pomorks_data_manage::data_manage_trait::{TaskLogJson, DATE_FORMAT, UNASSIGNED_ID};
+use pomorks_data_manage::statistics::SessionSummary;
 use pomorks_data_manage::todo::{State, ONE_MINUTE};
+use pomorks_data_manage::todo::{TodoItem, TodoStatus};
+use pomorks_data_manage::view::TodoGroup;
 use std::cmp::min;
 use std::ops::Div;
 use tui::{
@@ -12,15 +22,25 @@ use tui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Span, Spans},
-    widgets::{BarChart, Block, Borders, Gauge, List, ListItem, Paragraph, Wrap},
+    widgets::{BarChart, Block, Borders, Clear, Gauge, List, ListItem, ListState, Paragraph, Wrap},
     Frame,
 };
 
 pub fn draw<B: Backend>(f: &mut Frame<B>, app: &mut App) {
     let chunks = Layout::default()
-        .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+        .constraints(
+            [
+                Constraint::Length(3),
+                Constraint::Length(1),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ]
+            .as_ref(),
+        )
         .split(f.size());
+    app.hit_areas = HitAreas::default();
     draw_title(f, chunks[0]);
+    draw_tabs(f, app, chunks[1]);
     match app.selected_tab {
         Tab::Main => {
             let chunks = Layout::default()
@@ -32,21 +52,40 @@ pub fn draw<B: Backend>(f: &mut Frame<B>, app: &mut App) {
                     ]
                     .as_ref(),
                 )
-                .split(chunks[1]);
-            if app.show_add_todo {
-                draw_add_todo(f, app);
-            } else {
-                draw_tasks(f, app, chunks[0]);
+                .split(chunks[2]);
+            match &app.todo_form {
+                Some(form) => draw_todo_form(f, form),
+                None => draw_tasks(f, app, chunks[0]),
             }
             draw_status(f, app, chunks[1]);
             draw_under_status_bar(f, app, chunks[2]);
         }
-        Tab::Statistics => draw_statics(f, app, chunks[1]),
+        Tab::Board => match &app.todo_form {
+            Some(form) => draw_todo_form(f, form),
+            None => draw_board(f, app, chunks[2]),
+        },
+        Tab::Statistics => draw_statics(f, app, chunks[2]),
+        Tab::Unassigned => draw_unassigned(f, app, chunks[2]),
+        Tab::Log => draw_log(f, app, chunks[2]),
     };
+    match &app.command_line {
+        Some(line) => draw_input_line(f, ':', &line.input, chunks[3]),
+        None if app.searching => draw_input_line(f, '/', &app.list_view.search, chunks[3]),
+        None => draw_hint_line(f, app, chunks[3]),
+    }
+    if let Some(deletion) = &app.confirm_delete {
+        draw_confirm_delete(f, deletion, app);
+    }
+    if let Some(palette) = &app.palette {
+        draw_palette(f, palette, &app.keymap);
+    }
+    if app.show_help {
+        draw_help(f, &app.keymap);
+    }
 }
 
 // タイトルの描画
-fn draw_title<B: Backend>(f: &mut Frame<B>, area: Rect)
+fn draw_title<B>(f: &mut Frame<B>, area: Rect)
 where
     B: Backend,
 {
@@ -73,6 +112,26 @@ where
     f.render_widget(paragraph, area);
 }
 
+// laid out by hand, so each name's area is known for clicks.
+fn draw_tabs<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    let mut x = area.x;
+    for tab in Tab::ALL {
+        let label = format!(" {} ", tab.name());
+        let width = (label.len() as u16).min(area.right().saturating_sub(x));
+        let tab_area = Rect::new(x, area.y, width, 1);
+        let style = if tab == app.selected_tab {
+            Style::default()
+                .add_modifier(Modifier::REVERSED)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        f.render_widget(Paragraph::new(Span::styled(label, style)), tab_area);
+        app.hit_areas.tabs.push((tab, tab_area));
+        x = tab_area.right().saturating_add(1).min(area.right());
+    }
+}
+
 fn draw_tasks<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
 where
     B: Backend,
@@ -106,47 +165,56 @@ where
         }
     };
 
-    let todos_title: Vec<ListItem> = app
-        .todos
-        .items
-        .iter()
-        .map(|todo| {
-            ListItem::new(vec![Spans::from(Span::styled(
-                todo.title.to_string(),
-                get_style(is_selected(todo), todo.finished),
-            ))])
-        })
-        .collect();
-
-    let todos_tag: Vec<ListItem> = app
-        .todos
-        .items
-        .iter()
-        .map(|todo| {
-            ListItem::new(vec![Spans::from(Span::styled(
-                todo.tag.to_string(),
-                get_style(is_selected(todo), todo.finished),
-            ))])
-        })
-        .collect();
+    // only the rows that fit are built, from `row_offset`, which keeps the selection in view.
+    let rows = app.rows();
+    let selected = app.selected_row();
+    let height = area.height.saturating_sub(2) as usize;
+    app.row_offset = app.row_offset.min(rows.len().saturating_sub(height));
+    // with no room for a row there is nothing to scroll to, nor anything to highlight.
+    let selected = selected.filter(|_| height > 0);
+    if let Some(selected) = selected {
+        if selected < app.row_offset {
+            app.row_offset = selected;
+        } else if selected >= app.row_offset + height {
+            app.row_offset = selected + 1 - height;
+        }
+    }
+    app.hit_areas.todo_list = Some(Rect::new(area.x, area.y + 1, area.width, height as u16));
 
-    let todos_project: Vec<ListItem> = app
-        .todos
-        .items
-        .iter()
-        .map(|todo| {
-            ListItem::new(vec![Spans::from(Span::styled(
-                todo.project.to_string(),
-                get_style(is_selected(todo), todo.finished),
-            ))])
-        })
-        .collect();
+    let mut todos_title: Vec<ListItem> = vec![];
+    let mut todos_tag: Vec<ListItem> = vec![];
+    let mut todos_project: Vec<ListItem> = vec![];
+    for row in rows.into_iter().skip(app.row_offset).take(height) {
+        let [title, tag, project] = match row {
+            ListRow::Group(ind) => group_header(
+                &app.groups[ind],
+                app.collapsed.contains(&app.groups[ind].name),
+            ),
+            ListRow::Todo(ind) => {
+                let todo = &app.todos.items[ind];
+                let style = get_style(is_selected(todo), todo.finished);
+                [
+                    Span::styled(todo.title.to_string(), style),
+                    Span::styled(todo.tag.to_string(), style),
+                    Span::styled(todo.project.to_string(), style),
+                ]
+            }
+        };
+        todos_title.push(ListItem::new(Spans::from(title)));
+        todos_tag.push(ListItem::new(Spans::from(tag)));
+        todos_project.push(ListItem::new(Spans::from(project)));
+    }
 
     let todos_title = List::new(todos_title)
         .block(
             Block::default()
                 .borders(Borders::LEFT | Borders::TOP | Borders::BOTTOM)
-                .title("Todo"),
+                .title(format!(
+                    "Todo ({}/{}, {})",
+                    app.todos.items.len(),
+                    app.all_todos.len(),
+                    app.list_view.describe()
+                )),
         )
         .highlight_style(Style::default().fg(Color::Red))
         .highlight_symbol("> ");
@@ -159,9 +227,96 @@ where
         .block(Block::default().borders(Borders::RIGHT | Borders::TOP | Borders::BOTTOM))
         .highlight_style(Style::default().fg(Color::Red));
 
-    f.render_stateful_widget(todos_title, chunks[0], &mut app.todos.state);
-    f.render_stateful_widget(todos_tag, chunks[1], &mut app.todos.state);
-    f.render_stateful_widget(todos_project, chunks[2], &mut app.todos.state);
+    let mut state = ListState::default();
+    state.select(selected.map(|selected| selected - app.row_offset));
+    f.render_stateful_widget(todos_title, chunks[0], &mut state.clone());
+    f.render_stateful_widget(todos_tag, chunks[1], &mut state.clone());
+    f.render_stateful_widget(todos_project, chunks[2], &mut state);
+}
+
+// a column per status, titled with its count and WIP limit, red when over it.
+fn draw_board<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
+where
+    B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Ratio(1, TodoStatus::ALL.len() as u32); 4])
+        .direction(Direction::Horizontal)
+        .split(area);
+
+    let columns = app.board_columns();
+    let card = app.board_card(&columns);
+    for (ind, (status, cards)) in TodoStatus::ALL.iter().zip(&columns).enumerate() {
+        let limit = app.board.wip_limit(*status);
+        let count = match limit {
+            Some(limit) => format!("{}/{}", cards.len(), limit),
+            None => cards.len().to_string(),
+        };
+        let mut border = Style::default();
+        if matches!(limit, Some(limit) if cards.len() > limit) {
+            border = border.fg(Color::Red);
+        } else if ind == app.board_column {
+            border = border.fg(Color::LightCyan);
+        }
+
+        let items: Vec<ListItem> = cards
+            .iter()
+            .map(|todo| {
+                let focused = app.todo_focus.as_ref().map(|focus| &focus.id) == Some(&todo.id);
+                ListItem::new(vec![
+                    Spans::from(Span::styled(
+                        todo.title.to_string(),
+                        get_style(focused, todo.finished),
+                    )),
+                    Spans::from(Span::styled(
+                        format!(
+                            "{} {} {}/{}",
+                            todo.tag, todo.project, todo.executed_count, todo.estimate_count
+                        ),
+                        Style::default().fg(Color::DarkGray),
+                    )),
+                ])
+            })
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(border)
+                    .title(format!("{} {}", status.name(), count)),
+            )
+            .highlight_style(Style::default().fg(Color::Red))
+            .highlight_symbol("> ");
+
+        let mut state = ListState::default();
+        if ind == app.board_column {
+            state.select(card);
+        }
+        f.render_stateful_widget(list, chunks[ind], &mut state);
+    }
+}
+
+// the name, the unfinished count and the executed per estimated pomodoros.
+fn group_header(group: &TodoGroup, collapsed: bool) -> [Span<'static>; 3] {
+    let style = Style::default()
+        .fg(Color::LightCyan)
+        .add_modifier(Modifier::BOLD);
+    let name = if group.name.is_empty() {
+        "(none)"
+    } else {
+        &group.name
+    };
+    [
+        Span::styled(
+            format!("{} {}", if collapsed { "▸" } else { "▾" }, name),
+            style,
+        ),
+        Span::styled(format!("{} unfinished", group.unfinished_count()), style),
+        Span::styled(
+            format!("{}/{}", group.executed_count(), group.estimate_count()),
+            style,
+        ),
+    ]
 }
 
 fn get_style(is_selected: bool, is_finished: bool) -> Style {
@@ -183,66 +338,61 @@ where
 {
     let selected_index = app.todos.state.selected();
     let task_detail = match selected_index {
-        Some(ind) => {
-            vec![
-                Spans::from(vec![Span::styled(
-                    format!("title: {}", app.todos.items[ind].title),
-                    Style::default()
-                        .add_modifier(Modifier::BOLD)
-                        .fg(Color::LightRed),
-                )]),
-                Spans::from(vec![Span::styled(
-                    format!("tag: #{}", app.todos.items[ind].tag),
-                    Style::default()
-                        .add_modifier(Modifier::BOLD)
-                        .fg(Color::LightBlue),
-                )]),
-                Spans::from(vec![Span::styled(
-                    format!("project: @{}", app.todos.items[ind].project),
-                    Style::default()
-                        .add_modifier(Modifier::BOLD)
-                        .fg(Color::LightGreen),
-                )]),
-                Spans::from(vec![Span::styled(
-                    format!(
-                        "Pomodoro: {}",
-                        if app.todos.items[ind].executed_count < app.todos.items[ind].estimate_count
-                        {
-                            "�?".repeat(app.todos.items[ind].executed_count)
-                                + &"□".repeat(
-                                    app.todos.items[ind].estimate_count
-                                        - app.todos.items[ind].executed_count,
-                                )
-                        } else {
-                            "�?".repeat(app.todos.items[ind].executed_count)
-                        }
-                    ),
-                    Style::default()
-                        .add_modifier(Modifier::BOLD)
-                        .fg(Color::Gray),
-                )]),
-                Spans::from(vec![Span::raw("")]),
-                Spans::from(vec![Span::styled(
-                    "Detail:",
-                    Style::default()
-                        .add_modifier(Modifier::BOLD)
-                        .fg(Color::Gray),
-                )]),
-                Spans::from(vec![Span::styled(
-                    app.todos.items[ind].detail.to_string(),
-                    Style::default()
-                        .add_modifier(Modifier::BOLD)
-                        .fg(Color::Gray),
-                )]),
-            ]
-        }
+        Some(ind) => vec![
+            Spans::from(vec![Span::styled(
+                format!("title: {}", app.todos.items[ind].title),
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .fg(Color::LightRed),
+            )]),
+            Spans::from(vec![Span::styled(
+                format!("tag: #{}", app.todos.items[ind].tag),
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .fg(Color::LightBlue),
+            )]),
+            Spans::from(vec![Span::styled(
+                format!("project: @{}", app.todos.items[ind].project),
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .fg(Color::LightGreen),
+            )]),
+            Spans::from(vec![Span::styled(
+                format!(
+                    "Pomodoro: {}",
+                    if app.todos.items[ind].executed_count < app.todos.items[ind].estimate_count {
+                        "�?".repeat(app.todos.items[ind].executed_count)
+                            + &"□".repeat(
+                                app.todos.items[ind].estimate_count
+                                    - app.todos.items[ind].executed_count,
+                            )
+                    } else {
+                        "�?".repeat(app.todos.items[ind].executed_count)
+                    }
+                ),
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .fg(Color::Gray),
+            )]),
+            Spans::from(vec![Span::raw("")]),
+            Spans::from(vec![Span::styled(
+                "Detail (v: edit):",
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .fg(Color::Gray),
+            )]),
+        ]
+        .into_iter()
+        .chain(markdown::to_spans(&app.todos.items[ind].detail))
+        .collect(),
         None => vec![Spans::from(vec![Span::raw("")])],
     };
 
     let block = Block::default().borders(Borders::ALL);
+    // trimming would drop the indent of nested lists and code.
     let parahraph = Paragraph::new(task_detail)
         .block(block)
-        .wrap(Wrap { trim: true });
+        .wrap(Wrap { trim: false });
     f.render_widget(parahraph, area);
 }
 
@@ -263,6 +413,7 @@ fn draw_timer<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
 where
     B: Backend,
 {
+    app.hit_areas.timer = Some(area);
     let block = Block::default().borders(Borders::ALL);
     f.render_widget(block, area);
 
@@ -272,33 +423,40 @@ where
         .margin(2)
         .split(area);
 
-    let progressed_time = if let Some(start) = app.start_time {
-        (Local::now() - start).num_seconds() as i64
-    } else {
-        0
-    };
+    let now = Local::now();
+    let progressed_time = app.engine.elapsed_seconds(now);
 
-    let remaind_time = app.limit_time as i64 - progressed_time;
+    let remaind_time = app.engine.remaining_seconds(now);
+    let is_overtime = remaind_time < 0;
 
     let timer = Spans::from(vec![Span::styled(
         format!(
-            "{}:{:>02}",
-            remaind_time.div(ONE_MINUTE as i64),
-            remaind_time % ONE_MINUTE as i64
+            "{}{}:{:>02}",
+            if is_overtime { "-" } else { "" },
+            remaind_time.abs().div(ONE_MINUTE as i64),
+            remaind_time.abs() % ONE_MINUTE as i64
         ),
         Style::default()
             .add_modifier(Modifier::BOLD)
-            .fg(Color::White),
+            .fg(if is_overtime {
+                Color::Yellow
+            } else {
+                Color::White
+            }),
     )]);
 
-    let percentage = (progressed_time as f64 / app.limit_time as f64) * 100.0;
+    let percentage = (progressed_time as f64 / app.engine.limit_seconds() as f64) * 100.0;
     let percentage = if percentage > 100.0 {
         100.0
     } else {
         percentage
     };
     let gauge = Gauge::default()
-        .gauge_style(Style::default().fg(Color::Red))
+        .gauge_style(Style::default().fg(if is_overtime {
+            Color::Yellow
+        } else {
+            Color::Red
+        }))
         .percent(percentage as u16);
     f.render_widget(gauge, chunks[0]);
 
@@ -357,7 +515,25 @@ where
             Style::default().add_modifier(Modifier::BOLD),
         )]),
         Spans::from(vec![Span::styled(
-            format!("Process: {}", State::get_state_name(app.state)),
+            format!(
+                "Process: {}{}{}{}",
+                State::get_state_name(app.engine.state()),
+                if app.engine.extended_seconds() > 0 {
+                    format!(" (+{}min)", app.engine.extended_seconds() / ONE_MINUTE)
+                } else {
+                    "".to_string()
+                },
+                if app.engine.is_paused() {
+                    "  [paused]"
+                } else {
+                    ""
+                },
+                if app.engine.auto_start() {
+                    "  [auto]"
+                } else {
+                    ""
+                }
+            ),
             Style::default()
                 .add_modifier(Modifier::BOLD)
                 .fg(Color::Gray),
@@ -372,10 +548,243 @@ where
     f.render_widget(task_paragraph, area);
 }
 
-fn draw_add_todo<B>(f: &mut Frame<B>, app: &mut App)
+fn draw_todo_form<B>(f: &mut Frame<B>, form: &TodoForm)
+where
+    B: Backend,
+{
+    let area = popup_area(f.size());
+    let label_width = FormField::ALL
+        .iter()
+        .map(|field| field.label().len())
+        .max()
+        .unwrap_or(0)
+        + 2;
+
+    let mut lines: Vec<Spans> = FormField::ALL
+        .iter()
+        .map(|field| {
+            let style = if *field == form.focused() {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            Spans::from(vec![
+                Span::styled(
+                    format!("{:width$}", field.label(), width = label_width),
+                    style.add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(form.value(*field).to_string(), style),
+            ])
+        })
+        .collect();
+    lines.push(Spans::from(""));
+    if let Some(error) = &form.error {
+        lines.push(Spans::from(Span::styled(
+            error.to_string(),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    lines.push(Spans::from(Span::styled(
+        "Tab/Up/Down: field, Enter: save, Esc: cancel",
+        Style::default().fg(Color::Gray),
+    )));
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(form.title())
+        .style(Style::default().bg(Color::DarkGray));
+    f.render_widget(Paragraph::new(lines).block(block), area);
+
+    f.set_cursor(
+        min(
+            area.x + 1 + (label_width + form.cursor()) as u16,
+            area.right().saturating_sub(2),
+        ),
+        area.y + 1 + form.focused() as u16,
+    );
+}
+
+fn draw_confirm_delete<B>(f: &mut Frame<B>, deletion: &Deletion, app: &App)
+where
+    B: Backend,
+{
+    let (title, question, note) = match deletion {
+        Deletion::Todo(todo) => {
+            let log_count = app.task_log.iter().filter(|log| log.id == todo.id).count();
+            (
+                "DELETE TODO",
+                format!("Delete \"{}\"?", todo.title),
+                format!("its {} log(s) will be kept as unassigned.", log_count),
+            )
+        }
+        Deletion::Log(log) => {
+            let todo_title = match app.all_todos.iter().find(|todo| todo.id == log.id) {
+                Some(todo) => todo.title.to_string(),
+                None => "(unassigned)".to_string(),
+            };
+            (
+                "DELETE POMODORO",
+                format!("Delete the pomodoro of {}?", log.date),
+                format!(
+                    "{}min of \"{}\" will be gone from the stats.",
+                    log.actual_seconds / ONE_MINUTE as i64,
+                    todo_title
+                ),
+            )
+        }
+    };
+    let text = vec![
+        Spans::from(question),
+        Spans::from(""),
+        Spans::from(note),
+        Spans::from(""),
+        Spans::from(Span::styled(
+            "y: delete, any other key: cancel",
+            Style::default().fg(Color::Gray),
+        )),
+    ];
+
+    let area = popup_area(f.size());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .style(Style::default().bg(Color::DarkGray));
+    let paragraph = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .block(block)
+        .wrap(Wrap { trim: true });
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn draw_input_popup<B>(f: &mut Frame<B>, title: &str, input: &str)
+where
+    B: Backend,
+{
+    let status = vec![Spans::from(vec![Span::raw(input.to_string())])];
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .style(Style::default().bg(Color::DarkGray));
+    let task_paragraph = Paragraph::new(status)
+        .alignment(Alignment::Center)
+        .block(block)
+        .wrap(Wrap { trim: true });
+    f.render_widget(task_paragraph, popup_area(f.size()));
+}
+
+fn draw_hint_line<B>(f: &mut Frame<B>, app: &App, area: Rect)
 where
     B: Backend,
 {
+    let spans: Vec<Span> = help::hints(app.hint_context(), &app.keymap)
+        .into_iter()
+        .flat_map(|(keys, label)| {
+            vec![
+                Span::styled(
+                    keys,
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(format!(" {}  ", label), Style::default().fg(Color::Gray)),
+            ]
+        })
+        .collect();
+    f.render_widget(Paragraph::new(Spans::from(spans)), area);
+}
+
+fn draw_input_line<B>(f: &mut Frame<B>, prompt: char, input: &str, area: Rect)
+where
+    B: Backend,
+{
+    f.render_widget(Paragraph::new(format!("{}{}", prompt, input)), area);
+    f.set_cursor(
+        min(area.x + 1 + input.chars().count() as u16, area.right()),
+        area.y,
+    );
+}
+
+fn draw_palette<B>(f: &mut Frame<B>, palette: &Palette, keymap: &Keymap)
+where
+    B: Backend,
+{
+    let area = popup_area(f.size());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("ACTIONS")
+        .style(Style::default().bg(Color::DarkGray));
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+        .split(inner);
+    f.render_widget(Paragraph::new(format!("> {}", palette.query)), chunks[0]);
+    f.set_cursor(
+        min(
+            chunks[0].x + 2 + palette.query.chars().count() as u16,
+            chunks[0].right(),
+        ),
+        chunks[0].y,
+    );
+
+    let items: Vec<ListItem> = palette
+        .matches()
+        .into_iter()
+        .map(|action| {
+            ListItem::new(Spans::from(vec![
+                Span::raw(format!("{:20}", action.name())),
+                Span::styled(
+                    format!("{:14}", keymap.hint(action)),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::styled(action.description(), Style::default().fg(Color::Gray)),
+            ]))
+        })
+        .collect();
+    let mut state = ListState::default();
+    state.select(Some(palette.selected));
+    let list = List::new(items)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
+
+fn draw_help<B>(f: &mut Frame<B>, keymap: &Keymap)
+where
+    B: Backend,
+{
+    let rows = help::help_rows(keymap);
+    let key_width = rows.iter().map(|(keys, _)| keys.len()).max().unwrap_or(0) + 2;
+    let lines: Vec<Spans> = rows
+        .into_iter()
+        .map(|(keys, description)| {
+            Spans::from(vec![
+                Span::styled(
+                    format!("{:width$}", keys, width = key_width),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(description),
+            ])
+        })
+        .collect();
+
+    let area = popup_area(f.size());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("HELP (any key to close)")
+        .style(Style::default().bg(Color::DarkGray));
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+// the middle of the screen, leaving a fifth on every side.
+fn popup_area(area: Rect) -> Rect {
     let chunks_vert = Layout::default()
         .direction(Direction::Vertical)
         .constraints(
@@ -386,7 +795,7 @@ where
             ]
             .as_ref(),
         )
-        .split(f.size());
+        .split(area);
 
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -400,20 +809,10 @@ where
         )
         .split(chunks_vert[1]);
 
-    let status = vec![Spans::from(vec![Span::raw(app.new_todo_string.clone())])];
-
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .title("ADD TODO")
-        .style(Style::default().bg(Color::DarkGray));
-    let task_paragraph = Paragraph::new(status)
-        .alignment(Alignment::Center)
-        .block(block)
-        .wrap(Wrap { trim: true });
-    f.render_widget(task_paragraph, chunks[1]);
+    chunks[1]
 }
 
-fn draw_under_status_bar<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect)
+fn draw_under_status_bar<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
 where
     B: Backend,
 {
@@ -433,21 +832,29 @@ where
     draw_today_workcount(f, app, chunks[1]);
     draw_total_estimate(f, app, chunks[2]);
 }
-fn draw_message<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect)
+fn draw_message<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
 where
     B: Backend,
 {
-    let title = Spans::from(vec![Span::styled(
-        format!("message: {}", app.status),
-        Style::default().fg(Color::Red),
-    )]);
+    let save_indicator = match &app.save_status {
+        SaveStatus::Saved => Span::styled("[saved] ", Style::default().fg(Color::Green)),
+        SaveStatus::Pending => Span::styled("[unsaved] ", Style::default().fg(Color::Yellow)),
+        SaveStatus::Failed(_) => Span::styled("[save failed] ", Style::default().fg(Color::Red)),
+    };
+    let title = Spans::from(vec![
+        save_indicator,
+        Span::styled(
+            format!("message: {}", app.status),
+            Style::default().fg(Color::Red),
+        ),
+    ]);
     let block = Block::default().borders(Borders::ALL);
 
     let paragraph = Paragraph::new(title).block(block).wrap(Wrap { trim: true });
     f.render_widget(paragraph, area);
 }
 
-fn draw_today_workcount<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect)
+fn draw_today_workcount<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
 where
     B: Backend,
 {
@@ -464,7 +871,7 @@ where
     f.render_widget(paragraph, area);
 }
 
-fn draw_total_estimate<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect)
+fn draw_total_estimate<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
 where
     B: Backend,
 {
@@ -499,7 +906,137 @@ where
     f.render_widget(paragraph, area);
 }
 
-fn draw_statics<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect)
+fn draw_unassigned<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
+where
+    B: Backend,
+{
+    let keys = &app.keymap;
+    let log_title = format!(
+        "Unassigned Pomodoro ({}/{})",
+        keys.hint(Action::Down),
+        keys.hint(Action::Up)
+    );
+    let todo_title = assign_title(keys);
+    draw_log_editor(
+        f,
+        &mut app.unassigned_logs,
+        &mut app.todos,
+        &log_title,
+        &todo_title,
+        area,
+    );
+}
+
+fn draw_log<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
+where
+    B: Backend,
+{
+    let keys = &app.keymap;
+    let log_title = format!(
+        "Pomodoro Log ({}/{}, {}: add, {}: delete)",
+        keys.hint(Action::Down),
+        keys.hint(Action::Up),
+        keys.hint(Action::Add),
+        keys.hint(Action::Delete)
+    );
+    let todo_title = assign_title(keys);
+    draw_log_editor(
+        f,
+        &mut app.logs,
+        &mut app.todos,
+        &log_title,
+        &todo_title,
+        area,
+    );
+    if app.show_add_log {
+        draw_input_popup(f, "ADD POMODORO (YYYY/MM/DD HH:MM)", &app.new_log_string);
+    }
+}
+
+fn assign_title(keys: &Keymap) -> String {
+    format!(
+        "Assign To ({}/{}, {})",
+        keys.hint(Action::TodoUp),
+        keys.hint(Action::TodoDown),
+        keys.hint(Action::Select)
+    )
+}
+
+fn draw_log_editor<B>(
+    f: &mut Frame<B>,
+    logs: &mut StatefulList<TaskLogJson>,
+    todos: &mut StatefulList<TodoItem>,
+    log_title: &str,
+    todo_title: &str,
+    area: Rect,
+) where
+    B: Backend,
+{
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(area);
+
+    let get_title = |id: &str| match todos.items.iter().find(|todo| todo.id == id) {
+        Some(todo) => todo.title.to_string(),
+        None if id == UNASSIGNED_ID => "(unassigned)".to_string(),
+        None => format!("({})", id),
+    };
+
+    let log_items: Vec<ListItem> = logs
+        .items
+        .iter()
+        .map(|log| {
+            ListItem::new(vec![Spans::from(Span::styled(
+                format!(
+                    "{}  {}  {}min",
+                    log.date,
+                    get_title(&log.id),
+                    log.actual_seconds / ONE_MINUTE as i64
+                ),
+                get_style(false, log.skipped),
+            ))])
+        })
+        .collect();
+
+    let log_items = List::new(log_items)
+        .block(Block::default().borders(Borders::ALL).title(log_title))
+        .highlight_style(Style::default().fg(Color::Red))
+        .highlight_symbol("> ");
+
+    let todo_items: Vec<ListItem> = todos
+        .items
+        .iter()
+        .map(|todo| {
+            ListItem::new(vec![Spans::from(Span::styled(
+                format!("{}  #{}  @{}", todo.title, todo.tag, todo.project),
+                get_style(false, todo.finished),
+            ))])
+        })
+        .collect();
+
+    let todo_items = List::new(todo_items)
+        .block(Block::default().borders(Borders::ALL).title(todo_title))
+        .highlight_style(Style::default().fg(Color::Red))
+        .highlight_symbol("> ");
+
+    f.render_stateful_widget(log_items, chunks[0], &mut logs.state);
+    f.render_stateful_widget(todo_items, chunks[1], &mut todos.state);
+}
+
+fn draw_statics<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
+where
+    B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Min(0), Constraint::Length(4)].as_ref())
+        .split(area);
+
+    draw_charts(f, app, chunks[0]);
+    draw_session_summary(f, app, chunks[1]);
+}
+
+fn draw_charts<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
 where
     B: Backend,
 {
@@ -521,7 +1058,72 @@ where
     draw_chart_of_year(f, app, chunks[2]);
 }
 
-fn draw_chart_of_week<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect)
+fn draw_session_summary<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
+where
+    B: Backend,
+{
+    let today = Local::today();
+    let one_week = get_this_week(today).unwrap();
+
+    let summary_of = |dates: &[Date<Local>]| {
+        SessionSummary::from_logs(app.task_log.iter().filter(|log| match log.get_date() {
+            Some(date) => dates.contains(&date.date()),
+            None => false,
+        }))
+    };
+
+    let summary_line = |label: &str, summary: SessionSummary| {
+        Spans::from(vec![
+            Span::styled(
+                format!("{:<10}", label),
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .fg(Color::Gray),
+            ),
+            Span::styled(
+                format!(
+                    "work: {} ({}min)",
+                    summary.work_count,
+                    summary.work_seconds / ONE_MINUTE as i64
+                ),
+                Style::default().fg(Color::LightRed),
+            ),
+            Span::styled(
+                format!(
+                    "  break: {} ({}min)",
+                    summary.break_count,
+                    summary.break_seconds / ONE_MINUTE as i64
+                ),
+                Style::default().fg(Color::LightGreen),
+            ),
+            Span::styled(
+                format!(
+                    "  break/work: {}",
+                    match summary.break_ratio() {
+                        Some(ratio) => format!("{:.2}", ratio),
+                        None => "-".to_string(),
+                    }
+                ),
+                Style::default().fg(Color::LightBlue),
+            ),
+            Span::styled(
+                format!("  skipped break: {}", summary.skipped_break_count),
+                Style::default().fg(Color::Yellow),
+            ),
+        ])
+    };
+
+    let summary = vec![
+        summary_line("TODAY", summary_of(&[today])),
+        summary_line("THIS WEEK", summary_of(&one_week)),
+    ];
+
+    let block = Block::default().borders(Borders::ALL).title("SESSIONS");
+    let paragraph = Paragraph::new(summary).block(block);
+    f.render_widget(paragraph, area);
+}
+
+fn draw_chart_of_week<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
 where
     B: Backend,
 {
@@ -549,6 +1151,7 @@ where
         .map(|date| {
             app.task_log
                 .iter()
+                .filter(|log| log.is_completed_work())
                 .filter(|log| {
                     let date_each = Local
                         .datetime_from_str(&log.date, DATE_FORMAT)
@@ -575,7 +1178,7 @@ where
     f.render_widget(chart_weekly, area);
 }
 
-fn draw_chart_of_month<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect)
+fn draw_chart_of_month<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
 where
     B: Backend,
 {
@@ -602,6 +1205,7 @@ where
         .map(|date| {
             app.task_log
                 .iter()
+                .filter(|log| log.is_completed_work())
                 .filter(|log| {
                     let date_each = Local
                         .datetime_from_str(&log.date, DATE_FORMAT)
@@ -629,7 +1233,7 @@ where
     f.render_widget(chart_monthly, area);
 }
 
-fn draw_chart_of_year<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect)
+fn draw_chart_of_year<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
 where
     B: Backend,
 {
@@ -662,6 +1266,7 @@ where
         .map(|month| {
             app.task_log
                 .iter()
+                .filter(|log| log.is_completed_work())
                 .filter(|log| {
                     let date_each = Local
                         .datetime_from_str(&log.date, DATE_FORMAT)
@@ -688,3 +1293,49 @@ where
         .bar_gap(1);
     f.render_widget(chart_yearly, area);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::tests::{controller_of, StandInDataManage};
+    use pomorks_data_manage::config::{BoardConfig, TimerConfig};
+    use pomorks_data_manage::notifications::NoneNotifier;
+    use pomorks_data_manage::todo::TodoList;
+    use std::str::FromStr;
+    use std::time::Duration;
+    use tui::backend::TestBackend;
+    use tui::Terminal;
+
+    #[test]
+    fn test_task_list_on_a_short_terminal() {
+        let data_manager = StandInDataManage::default();
+        let mut todo_list = TodoList::new();
+        for title in ["first", "second", "third"] {
+            todo_list
+                .insert_todo(TodoItem::from_str(&format!("{} tag project 1", title)).unwrap())
+                .unwrap();
+        }
+        let controller = controller_of(&data_manager, todo_list, Duration::from_secs(1));
+        let notifier = NoneNotifier {};
+        let mut app = App::new(
+            &controller,
+            &TimerConfig::default(),
+            &BoardConfig::default(),
+            Keymap::default(),
+            &notifier,
+        );
+        app.todos.state.select(Some(2));
+        assert_eq!(app.selected_row(), Some(2));
+
+        // the borders alone take two lines.
+        for height in 0..5 {
+            let mut terminal = Terminal::new(TestBackend::new(40, height)).unwrap();
+            terminal
+                .draw(|f| draw_task_list(f, &mut app, f.size()))
+                .unwrap();
+            assert!(app.row_offset <= 2);
+        }
+        // two rows fit, so the list scrolls by one to show the third.
+        assert_eq!(app.row_offset, 1);
+    }
+}